@@ -190,6 +190,19 @@ where
     /// If `Some(n)`, uses `n` instead of the global value (0 = disabled for this action).
     /// If `None`, falls back to the global `defcfg` value.
     pub require_prior_idle: Option<u16>,
+    /// If `Some`, the timeout and prior-idle threshold of this key are learned at runtime from
+    /// likely misfires, within the given bounds. See [`crate::adaptive_tap_hold`].
+    pub adaptive: Option<AdaptiveBounds>,
+}
+
+/// Bounds within which an adaptive [`HoldTapAction`] adjusts its timeout and prior-idle
+/// threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBounds {
+    pub min_timeout: u16,
+    pub max_timeout: u16,
+    pub min_prior_idle: u16,
+    pub max_prior_idle: u16,
 }
 
 /// Define one shot key behaviour.
@@ -252,6 +265,13 @@ where
 pub enum TapDanceConfig {
     Lazy,
     Eager,
+    /// Like `Lazy`, but each number of taps has a tap action and a hold action, stored in
+    /// `actions` as `[tap1, hold1, tap2, hold2, ...]`. Releasing the key before `hold_timeout`
+    /// is a tap, which continues the sequence. Keeping it pressed for `hold_timeout` after its
+    /// latest press activates the hold action for the number of taps so far.
+    LazyHold {
+        hold_timeout: u16,
+    },
 }
 
 /// A group of chords (actions mapped to a combination of multiple physical keys pressed together).
@@ -413,6 +433,9 @@ where
     /// Disregard the entire layer stack, i.e. the current base layer and any while-held layers,
     /// and select the action from `Layout.src_keys`.
     Src,
+    /// Keep the current while-held layer active after its key is released. If a layer is already
+    /// locked, unlock it instead.
+    LayerLock,
 }
 
 impl<T> Action<'_, T> {
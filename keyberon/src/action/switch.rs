@@ -73,6 +73,9 @@ const LAYER_VAL: u16 = 853;
 const BASE_LAYER_VAL: u16 = 854;
 const HISTORICAL_DEVICE_VAL: u16 = 855;
 const CALLBACK_INDEX_VAL: u16 = 856;
+// The state variable index is added to this value, up to MAX_OPCODE_LEN.
+const STATE_VAR_VAL: u16 = 0x0C00;
+pub const MAX_STATE_VARS: u16 = MAX_OPCODE_LEN - STATE_VAR_VAL + 1;
 
 // Binary values:
 // 0b0100 ...
@@ -113,6 +116,7 @@ enum OpCodeType {
     BaseLayer(u16),
     HistoricalDevice(HistoricalDevice),
     CallbackIndex(u16),
+    StateVar(StateVar),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -144,6 +148,13 @@ struct HistoricalDevice {
     how_far_back: u8,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// An op that checks whether a state variable has the value.
+struct StateVar {
+    var: u16,
+    value: i16,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct TicksSinceNthKey {
     nth_key: u8,
@@ -164,7 +175,7 @@ impl<'a, T> Switch<'a, T> {
     ///
     /// The `historical_keys` parameter should iterate in the order of most-recent-first.
     #[allow(clippy::too_many_arguments)]
-    pub fn actions<A1, A2, H1, H2, L, D>(
+    pub fn actions<A1, A2, H1, H2, L, D, V>(
         &self,
         active_keys: A1,
         active_positions: A2,
@@ -173,7 +184,8 @@ impl<'a, T> Switch<'a, T> {
         layers: L,
         default_layer: u16,
         device_history: D,
-    ) -> SwitchActions<'a, T, A1, A2, H1, H2, L, D>
+        state_vars: V,
+    ) -> SwitchActions<'a, T, A1, A2, H1, H2, L, D, V>
     where
         A1: Iterator<Item = KeyCode> + Clone,
         A2: Iterator<Item = KCoord> + Clone,
@@ -181,6 +193,7 @@ impl<'a, T> Switch<'a, T> {
         H2: Iterator<Item = HistoricalEvent<KCoord>> + Clone,
         L: Iterator<Item = u16> + Clone,
         D: Iterator<Item = Option<NonZeroU8>> + Clone,
+        V: Iterator<Item = i16> + Clone,
     {
         SwitchActions {
             cases: self.cases,
@@ -191,6 +204,7 @@ impl<'a, T> Switch<'a, T> {
             layers,
             default_layer,
             device_history,
+            state_vars,
             case_index: 0,
             callbacks: self.callbacks,
         }
@@ -199,7 +213,7 @@ impl<'a, T> Switch<'a, T> {
 
 #[derive(Clone)]
 /// Iterator returned by `Switch::actions`.
-pub struct SwitchActions<'a, T, A1, A2, H1, H2, L, D>
+pub struct SwitchActions<'a, T, A1, A2, H1, H2, L, D, V>
 where
     A1: Iterator<Item = KeyCode> + Clone,
     A2: Iterator<Item = KCoord> + Clone,
@@ -207,6 +221,7 @@ where
     H2: Iterator<Item = HistoricalEvent<KCoord>> + Clone,
     L: Iterator<Item = u16> + Clone,
     D: Iterator<Item = Option<NonZeroU8>> + Clone,
+    V: Iterator<Item = i16> + Clone,
 {
     cases: &'a [(&'a [OpCode], &'a Action<'a, T>, BreakOrFallthrough)],
     active_keys: A1,
@@ -216,11 +231,12 @@ where
     layers: L,
     default_layer: u16,
     device_history: D,
+    state_vars: V,
     case_index: usize,
     callbacks: &'a [&'a (dyn Fn() -> bool + Send + Sync)],
 }
 
-impl<'a, T, A1, A2, H1, H2, L, D> Iterator for SwitchActions<'a, T, A1, A2, H1, H2, L, D>
+impl<'a, T, A1, A2, H1, H2, L, D, V> Iterator for SwitchActions<'a, T, A1, A2, H1, H2, L, D, V>
 where
     A1: Iterator<Item = KeyCode> + Clone,
     A2: Iterator<Item = KCoord> + Clone,
@@ -228,6 +244,7 @@ where
     H2: Iterator<Item = HistoricalEvent<KCoord>> + Clone,
     L: Iterator<Item = u16> + Clone,
     D: Iterator<Item = Option<NonZeroU8>> + Clone,
+    V: Iterator<Item = i16> + Clone,
 {
     type Item = &'a Action<'a, T>;
 
@@ -243,6 +260,7 @@ where
                 self.layers.clone(),
                 self.default_layer,
                 self.device_history.clone(),
+                self.state_vars.clone(),
                 self.callbacks,
             ) {
                 let ret_ac = case.1;
@@ -372,6 +390,13 @@ impl OpCode {
         (Self(CALLBACK_INDEX_VAL), Self(index))
     }
 
+    /// Returns OpCodes specifying a check that a state variable, referenced by index, has the
+    /// value.
+    pub fn new_state_var(var: u16, value: i16) -> (Self, Self) {
+        assert!(var < MAX_STATE_VARS);
+        (Self(STATE_VAR_VAL + var), Self(value as u16))
+    }
+
     /// Return the interpretation of this `OpCode`.
     fn opcode_type(self, next: Option<OpCode>) -> OpCodeType {
         if self.0 < KEY_MAX {
@@ -392,6 +417,10 @@ impl OpCode {
                     how_far_back: ((op2.0 >> 8) & 0x7) as u8,
                 }),
                 CALLBACK_INDEX_VAL => OpCodeType::CallbackIndex(op2.0),
+                STATE_VAR_VAL..=MAX_OPCODE_LEN => OpCodeType::StateVar(StateVar {
+                    var: self.0 - STATE_VAR_VAL,
+                    value: op2.0 as i16,
+                }),
                 _ => unreachable!("unexpected opcode {self:?}"),
             }
         } else {
@@ -439,6 +468,7 @@ fn evaluate_boolean(
     layers: impl Iterator<Item = u16> + Clone,
     default_layer: u16,
     device_history: impl Iterator<Item = Option<NonZeroU8>> + Clone,
+    state_vars: impl Iterator<Item = i16> + Clone,
     callbacks: &[&(dyn Fn() -> bool + Send + Sync)],
 ) -> bool {
     let mut ret = true;
@@ -545,6 +575,14 @@ fn evaluate_boolean(
                 current_index += 1;
                 ret = callbacks[usize::from(callback_index)]();
             }
+            OpCodeType::StateVar(sv) => {
+                // opcode has size 2
+                current_index += 1;
+                ret = state_vars
+                    .clone()
+                    .nth(usize::from(sv.var))
+                    .is_some_and(|value| value == sv.value);
+            }
         };
         if current_op == Not {
             ret = !ret;
@@ -571,7 +609,7 @@ mod test {
     where
         T: Clone,
     {
-        s.iter().cloned().collect()
+        s.to_vec()
     }
 
     // Do not use anymore.
@@ -588,6 +626,7 @@ mod test {
         testcfg.evaluate()
     }
 
+    #[derive(Default)]
     struct SwitchTestCfg {
         opcodes: Vec<OpCode>,
         key_codes: Vec<KeyCode>,
@@ -597,25 +636,10 @@ mod test {
         layers: Vec<u16>,
         default_layer: u16,
         device_history: Vec<Option<NonZeroU8>>,
+        state_vars: Vec<i16>,
         callbacks: Vec<&'static (dyn Fn() -> bool + Send + Sync)>,
     }
 
-    impl Default for SwitchTestCfg {
-        fn default() -> Self {
-            Self {
-                opcodes: vec![],
-                key_codes: vec![],
-                inputs: vec![],
-                historical_keys: vec![],
-                historical_inputs: vec![],
-                layers: vec![],
-                default_layer: 0,
-                device_history: vec![],
-                callbacks: vec![],
-            }
-        }
-    }
-
     impl SwitchTestCfg {
         fn new() -> Self {
             Self::default()
@@ -631,6 +655,7 @@ mod test {
                 self.layers.iter().copied(),
                 self.default_layer,
                 self.device_history.iter().copied(),
+                self.state_vars.iter().copied(),
                 self.callbacks.as_slice(),
             )
         }
@@ -659,6 +684,11 @@ mod test {
             self.device_history = svec(device_history);
             self
         }
+
+        fn state_vars(mut self, state_vars: &[i16]) -> Self {
+            self.state_vars = svec(state_vars);
+            self
+        }
     }
 
     #[test]
@@ -878,6 +908,7 @@ mod test {
             [].iter().copied(),
             0,
             core::iter::empty(),
+            core::iter::empty(),
         );
         assert_eq!(actions.next(), Some(&Action::<()>::KeyCode(KeyCode::A)));
         assert_eq!(actions.next(), Some(&Action::<()>::KeyCode(KeyCode::B)));
@@ -902,6 +933,7 @@ mod test {
             [].iter().copied(),
             0,
             core::iter::empty(),
+            core::iter::empty(),
         );
         assert_eq!(actions.next(), Some(&Action::<()>::KeyCode(KeyCode::A)));
         assert_eq!(actions.next(), None);
@@ -933,6 +965,7 @@ mod test {
             [].iter().copied(),
             0,
             core::iter::empty(),
+            core::iter::empty(),
         );
         assert_eq!(actions.next(), None);
     }
@@ -948,17 +981,17 @@ mod test {
         let testcfg = SwitchTestCfg::new()
             .opcodes(opcodes)
             .device_history(&[Some(id1)]);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         // Non-matching device ID
         let testcfg = SwitchTestCfg::new()
             .opcodes(opcodes)
             .device_history(&[Some(id2)]);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
 
         // Empty device history
         let testcfg = SwitchTestCfg::new().opcodes(opcodes);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
     }
 
     #[test]
@@ -973,21 +1006,21 @@ mod test {
         let testcfg = SwitchTestCfg::new()
             .opcodes(&[op1, op2])
             .device_history(&history);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         // Check second most recent (recency 2 → how_far_back 1) is id2
         let (op1, op2) = OpCode::new_device_history(id2, 1);
         let testcfg = SwitchTestCfg::new()
             .opcodes(&[op1, op2])
             .device_history(&history);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         // Wrong device at recency 1
         let (op1, op2) = OpCode::new_device_history(id1, 0);
         let testcfg = SwitchTestCfg::new()
             .opcodes(&[op1, op2])
             .device_history(&history);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
     }
 
     #[test]
@@ -1013,14 +1046,38 @@ mod test {
         let testcfg = SwitchTestCfg::new()
             .opcodes(&[op1, op2])
             .device_history(&history);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         // Looking for id1 at position 1 (where None is) should NOT match
         let (op1, op2) = OpCode::new_device_history(id1, 1);
         let testcfg = SwitchTestCfg::new()
             .opcodes(&[op1, op2])
             .device_history(&history);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
+    }
+
+    #[test]
+    fn switch_state_var() {
+        let state_vars = [0, -3, i16::MAX];
+        let (op1, op2) = OpCode::new_state_var(1, -3);
+        let testcfg = SwitchTestCfg::new()
+            .opcodes(&[op1, op2])
+            .state_vars(&state_vars);
+        assert!(testcfg.evaluate());
+
+        let (op1, op2) = OpCode::new_state_var(2, i16::MAX);
+        let (op3, op4) = OpCode::new_state_var(0, 1);
+        let testcfg = SwitchTestCfg::new()
+            .opcodes(&[OpCode::new_bool(And, 5), op1, op2, op3, op4])
+            .state_vars(&state_vars);
+        assert!(!testcfg.evaluate());
+
+        // Unknown variables never match.
+        let (op1, op2) = OpCode::new_state_var(MAX_STATE_VARS - 1, 0);
+        let testcfg = SwitchTestCfg::new()
+            .opcodes(&[op1, op2])
+            .state_vars(&state_vars);
+        assert!(!testcfg.evaluate());
     }
 
     #[test]
@@ -1075,22 +1132,22 @@ mod test {
         let testcfg = SwitchTestCfg::new()
             .opcodes(&opcode_true)
             .historical_keys(&hist_keycodes);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         let testcfg = SwitchTestCfg::new()
             .opcodes(&opcode_true2)
             .historical_keys(&hist_keycodes);
-        assert_eq!(true, testcfg.evaluate());
+        assert!(testcfg.evaluate());
 
         let testcfg = SwitchTestCfg::new()
             .opcodes(&opcode_false)
             .historical_keys(&hist_keycodes);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
 
         let testcfg = SwitchTestCfg::new()
             .opcodes(&opcode_false2)
             .historical_keys(&hist_keycodes);
-        assert_eq!(false, testcfg.evaluate());
+        assert!(!testcfg.evaluate());
     }
 
     #[test]
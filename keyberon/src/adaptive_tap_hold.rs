@@ -0,0 +1,392 @@
+//! Learns the timing of [`HoldTapAction`](crate::action::HoldTapAction)s that have `adaptive`
+//! bounds from likely misfires.
+//!
+//! Two patterns count as misfires:
+//!
+//! - a hold, where the next key pressed after its release types a backspace: the key was
+//!   probably meant as a tap, so its timeout and prior-idle threshold increase;
+//! - a tap, where another key was both pressed and released while it was held: the key was
+//!   probably meant as a hold, so its timeout and prior-idle threshold decrease.
+//!
+//! Learned values are kept per key coordinate and always stay within the bounds of the action.
+//! Adaptive actions on different layers at the same coordinate share their learned values, which
+//! are clamped to the bounds of the action pressed last.
+
+use crate::action::AdaptiveBounds;
+use crate::key_code::KeyCode;
+use crate::layout::{Event, KCoord, REAL_KEY_ROW};
+use std::collections::BTreeMap;
+
+/// Ticks by which a misfire adjusts the timeout and the prior-idle threshold.
+pub const ADAPTIVE_STEP: u16 = 10;
+
+/// Ticks after the release of a hold within which the next key press is checked for a
+/// backspace.
+pub const MISFIRE_WINDOW: u16 = 1000;
+
+/// The learned timing and the statistics of an adaptive key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdaptiveKey {
+    /// Ticks until the key resolves to hold.
+    pub timeout: u16,
+    /// Ticks since the previous key press within which the key resolves to tap immediately.
+    pub prior_idle: u16,
+    pub taps: u32,
+    pub holds: u32,
+    /// Total duration of the taps, in ticks.
+    pub tap_ticks: u64,
+    /// Total duration of the holds, in ticks.
+    pub hold_ticks: u64,
+    /// Holds followed by a backspace.
+    pub false_holds: u32,
+    /// Taps that overlapped another key.
+    pub false_taps: u32,
+}
+
+impl AdaptiveKey {
+    /// Average duration of the taps, in ticks.
+    pub fn avg_tap_ticks(&self) -> u64 {
+        self.tap_ticks.checked_div(self.taps.into()).unwrap_or(0)
+    }
+
+    /// Average duration of the holds, in ticks.
+    pub fn avg_hold_ticks(&self) -> u64 {
+        self.hold_ticks.checked_div(self.holds.into()).unwrap_or(0)
+    }
+
+    fn clamp(&mut self, bounds: AdaptiveBounds) {
+        self.timeout = self.timeout.clamp(bounds.min_timeout, bounds.max_timeout);
+        self.prior_idle = self
+            .prior_idle
+            .clamp(bounds.min_prior_idle, bounds.max_prior_idle);
+    }
+
+    fn adjust_up(&mut self, bounds: AdaptiveBounds) {
+        self.timeout = self.timeout.saturating_add(ADAPTIVE_STEP);
+        self.prior_idle = self.prior_idle.saturating_add(ADAPTIVE_STEP);
+        self.clamp(bounds);
+    }
+
+    fn adjust_down(&mut self, bounds: AdaptiveBounds) {
+        self.timeout = self.timeout.saturating_sub(ADAPTIVE_STEP);
+        self.prior_idle = self.prior_idle.saturating_sub(ADAPTIVE_STEP);
+        self.clamp(bounds);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    Tap,
+    Hold,
+}
+
+/// An adaptive key that is pressed, or released but not yet resolved.
+#[derive(Debug)]
+struct PressedKey {
+    coord: KCoord,
+    bounds: AdaptiveBounds,
+    ticks: u16,
+    released: bool,
+    resolution: Option<Resolution>,
+    /// Keys pressed while this key was held.
+    others: Vec<u16>,
+    /// Whether one of `others` was also released while this key was held.
+    overlapped: bool,
+}
+
+/// A hold that is checked for a backspace after its release.
+#[derive(Debug)]
+struct HoldWatch {
+    coord: KCoord,
+    bounds: AdaptiveBounds,
+    ticks_left: u16,
+    next_pressed: bool,
+}
+
+/// Learned timing of all adaptive keys.
+#[derive(Debug, Default)]
+pub struct AdaptiveTapHold {
+    keys: BTreeMap<KCoord, AdaptiveKey>,
+    pressed: Vec<PressedKey>,
+    watch: Option<HoldWatch>,
+    changed: bool,
+}
+
+impl AdaptiveTapHold {
+    /// The learned keys, ordered by coordinate.
+    pub fn keys(&self) -> impl Iterator<Item = (KCoord, &AdaptiveKey)> {
+        self.keys.iter().map(|(coord, key)| (*coord, key))
+    }
+
+    /// Set the learned values of a key, e.g. when loading them from a file.
+    pub fn set_key(&mut self, coord: KCoord, key: AdaptiveKey) {
+        self.keys.insert(coord, key);
+    }
+
+    /// Whether a learned value changed since the previous call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Register the press of an adaptive key. Returns the timeout and prior-idle threshold to
+    /// use for it, starting from the values of the action if nothing was learned yet.
+    pub(crate) fn press(
+        &mut self,
+        coord: KCoord,
+        bounds: AdaptiveBounds,
+        timeout: u16,
+        prior_idle: u16,
+        delay: u16,
+    ) -> (u16, u16) {
+        let key = self.keys.entry(coord).or_insert(AdaptiveKey {
+            timeout,
+            prior_idle,
+            ..Default::default()
+        });
+        key.clamp(bounds);
+        let learned = (key.timeout, key.prior_idle);
+        self.pressed.retain(|p| p.coord != coord);
+        self.pressed.push(PressedKey {
+            coord,
+            bounds,
+            ticks: delay,
+            released: false,
+            resolution: None,
+            others: vec![],
+            overlapped: false,
+        });
+        learned
+    }
+
+    /// Register that a key resolved to tap or hold.
+    pub(crate) fn resolve(&mut self, coord: KCoord, hold: bool) {
+        let Some(idx) = self
+            .pressed
+            .iter()
+            .position(|p| p.coord == coord && p.resolution.is_none())
+        else {
+            return;
+        };
+        self.pressed[idx].resolution = Some(match hold {
+            true => Resolution::Hold,
+            false => Resolution::Tap,
+        });
+        self.finish(idx);
+    }
+
+    /// Register a physical key event.
+    pub(crate) fn event(&mut self, event: Event) {
+        match event {
+            Event::Press(REAL_KEY_ROW, j) => {
+                for p in self.pressed.iter_mut().filter(|p| !p.released) {
+                    if p.coord.1 != j {
+                        p.others.push(j);
+                    }
+                }
+                if let Some(watch) = self.watch.as_mut() {
+                    if watch.coord.1 != j {
+                        watch.next_pressed = true;
+                    }
+                }
+            }
+            Event::Release(REAL_KEY_ROW, j) => {
+                if let Some(idx) = self
+                    .pressed
+                    .iter()
+                    .position(|p| p.coord == (REAL_KEY_ROW, j) && !p.released)
+                {
+                    self.pressed[idx].released = true;
+                    self.finish(idx);
+                } else {
+                    for p in self.pressed.iter_mut().filter(|p| !p.released) {
+                        p.overlapped |= p.others.contains(&j);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Register a key code that is output by an action.
+    pub(crate) fn key_output(&mut self, keycode: KeyCode) {
+        if !self.watch.as_ref().is_some_and(|w| w.next_pressed) {
+            return;
+        }
+        let Some(watch) = self.watch.take() else {
+            return;
+        };
+        if keycode == KeyCode::BSpace {
+            if let Some(key) = self.keys.get_mut(&watch.coord) {
+                key.false_holds += 1;
+                key.adjust_up(watch.bounds);
+                self.changed = true;
+            }
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        for p in self.pressed.iter_mut().filter(|p| !p.released) {
+            p.ticks = p.ticks.saturating_add(1);
+        }
+        if let Some(watch) = self.watch.as_mut() {
+            watch.ticks_left = watch.ticks_left.saturating_sub(1);
+            if watch.ticks_left == 0 {
+                self.watch = None;
+            }
+        }
+    }
+
+    /// Record the statistics of a pressed key once it is both released and resolved.
+    fn finish(&mut self, idx: usize) {
+        let p = &self.pressed[idx];
+        let (true, Some(resolution)) = (p.released, p.resolution) else {
+            return;
+        };
+        let p = self.pressed.swap_remove(idx);
+        let Some(key) = self.keys.get_mut(&p.coord) else {
+            return;
+        };
+        match resolution {
+            Resolution::Tap => {
+                key.taps += 1;
+                key.tap_ticks += u64::from(p.ticks);
+                if p.overlapped {
+                    key.false_taps += 1;
+                    key.adjust_down(p.bounds);
+                    self.changed = true;
+                }
+            }
+            Resolution::Hold => {
+                key.holds += 1;
+                key.hold_ticks += u64::from(p.ticks);
+                self.watch = Some(HoldWatch {
+                    coord: p.coord,
+                    bounds: p.bounds,
+                    ticks_left: MISFIRE_WINDOW,
+                    next_pressed: false,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const A: KCoord = (REAL_KEY_ROW, 30);
+    const B: KCoord = (REAL_KEY_ROW, 48);
+    const BOUNDS: AdaptiveBounds = AdaptiveBounds {
+        min_timeout: 150,
+        max_timeout: 300,
+        min_prior_idle: 0,
+        max_prior_idle: 150,
+    };
+
+    fn learned(adaptive: &AdaptiveTapHold) -> (u16, u16) {
+        let key = adaptive.keys.get(&A).unwrap();
+        (key.timeout, key.prior_idle)
+    }
+
+    /// Press and release A as a hold, then press B.
+    fn hold_then_press_b(adaptive: &mut AdaptiveTapHold, ticks_before_b: u16) {
+        adaptive.event(Event::Press(A.0, A.1));
+        adaptive.press(A, BOUNDS, 200, 100, 0);
+        adaptive.resolve(A, true);
+        adaptive.key_output(KeyCode::LCtrl);
+        adaptive.event(Event::Release(A.0, A.1));
+        // output while no key was pressed yet is not the next key
+        adaptive.key_output(KeyCode::BSpace);
+        for _ in 0..ticks_before_b {
+            adaptive.tick();
+        }
+        adaptive.event(Event::Press(B.0, B.1));
+    }
+
+    #[test]
+    fn overlapped_tap_decreases_timing() {
+        let mut adaptive = AdaptiveTapHold::default();
+        adaptive.event(Event::Press(A.0, A.1));
+        assert_eq!(adaptive.press(A, BOUNDS, 200, 100, 0), (200, 100));
+        adaptive.event(Event::Press(B.0, B.1));
+        adaptive.event(Event::Release(B.0, B.1));
+        adaptive.event(Event::Release(A.0, A.1));
+        assert!(!adaptive.take_changed());
+        adaptive.resolve(A, false);
+        assert!(adaptive.take_changed());
+        assert_eq!(learned(&adaptive), (190, 90));
+        let key = adaptive.keys.get(&A).unwrap();
+        assert_eq!((key.taps, key.false_taps), (1, 1));
+
+        // a key that is still held when the tap resolves does not overlap
+        adaptive.event(Event::Press(A.0, A.1));
+        assert_eq!(adaptive.press(A, BOUNDS, 200, 100, 0), (190, 90));
+        adaptive.event(Event::Press(B.0, B.1));
+        adaptive.event(Event::Release(A.0, A.1));
+        adaptive.resolve(A, false);
+        adaptive.event(Event::Release(B.0, B.1));
+        assert!(!adaptive.take_changed());
+        assert_eq!(learned(&adaptive), (190, 90));
+    }
+
+    #[test]
+    fn backspace_after_hold_increases_timing() {
+        let mut adaptive = AdaptiveTapHold::default();
+        hold_then_press_b(&mut adaptive, 10);
+        assert!(!adaptive.take_changed());
+        adaptive.key_output(KeyCode::BSpace);
+        assert!(adaptive.take_changed());
+        assert_eq!(learned(&adaptive), (210, 110));
+        let key = adaptive.keys.get(&A).unwrap();
+        assert_eq!((key.holds, key.false_holds), (1, 1));
+
+        // only the first output after the hold counts
+        hold_then_press_b(&mut adaptive, 10);
+        adaptive.key_output(KeyCode::B);
+        adaptive.key_output(KeyCode::BSpace);
+        assert!(!adaptive.take_changed());
+        assert_eq!(learned(&adaptive), (210, 110));
+    }
+
+    #[test]
+    fn backspace_after_misfire_window_is_ignored() {
+        let mut adaptive = AdaptiveTapHold::default();
+        hold_then_press_b(&mut adaptive, MISFIRE_WINDOW - 1);
+        adaptive.key_output(KeyCode::BSpace);
+        assert_eq!(learned(&adaptive), (210, 110));
+
+        adaptive.take_changed();
+        hold_then_press_b(&mut adaptive, MISFIRE_WINDOW);
+        adaptive.key_output(KeyCode::BSpace);
+        assert!(!adaptive.take_changed());
+        assert_eq!(learned(&adaptive), (210, 110));
+    }
+
+    #[test]
+    fn learned_timing_stays_within_bounds() {
+        let mut adaptive = AdaptiveTapHold::default();
+        // the values of the action are clamped too
+        assert_eq!(adaptive.press(A, BOUNDS, 400, 200, 0), (300, 150));
+        adaptive.event(Event::Release(A.0, A.1));
+        adaptive.resolve(A, true);
+        adaptive.event(Event::Press(B.0, B.1));
+        adaptive.key_output(KeyCode::BSpace);
+        assert_eq!(learned(&adaptive), (300, 150));
+
+        adaptive.set_key(
+            A,
+            AdaptiveKey {
+                timeout: 155,
+                prior_idle: 5,
+                ..Default::default()
+            },
+        );
+        adaptive.event(Event::Press(A.0, A.1));
+        assert_eq!(adaptive.press(A, BOUNDS, 200, 100, 0), (155, 5));
+        adaptive.event(Event::Press(B.0, B.1));
+        adaptive.event(Event::Release(B.0, B.1));
+        adaptive.event(Event::Release(A.0, A.1));
+        adaptive.resolve(A, false);
+        assert_eq!(learned(&adaptive), (150, 0));
+    }
+}
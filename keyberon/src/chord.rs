@@ -150,6 +150,8 @@ pub struct ChordsV2<'a, T> {
     prev_queue_len: u8,
     /// Virtual coordinate for use in the layout state.
     next_coord: Cell<u16>,
+    /// Participating keys of the chord that was most recently consumed by the layout.
+    activated: Option<&'a [u16]>,
 }
 
 impl<T> std::fmt::Debug for ChordsV2<'_, T> {
@@ -171,6 +173,7 @@ impl<'a, T> ChordsV2<'a, T> {
             prev_active_layer: u16::MAX,
             prev_queue_len: u8::MAX,
             next_coord: Cell::new(KEY_MAX + 1),
+            activated: None,
         }
     }
 
@@ -190,12 +193,18 @@ impl<'a, T> ChordsV2<'a, T> {
         &self.chords
     }
 
+    /// Take the participating keys of the chord that was most recently activated.
+    pub fn take_activated(&mut self) -> Option<&'a [u16]> {
+        self.activated.take()
+    }
+
     pub(crate) fn get_action_chv2(&mut self) -> QueuedAction<'a, T> {
         self.active_chords
             .iter_mut()
             .find_map(|ach| match ach.status {
                 Unread => {
                     ach.status = Releasable;
+                    self.activated = Some(ach.participating_keys);
                     // Note on LayerStack being default (empty):
                     // A chordv2 is not allowed to use transparency,
                     // so it does not need to handle this case.
@@ -208,6 +217,7 @@ impl<'a, T> ChordsV2<'a, T> {
                 }
                 UnreadReleased => {
                     ach.status = Released;
+                    self.activated = Some(ach.participating_keys);
                     Some(Some((
                         (0, ach.coordinate),
                         ach.delay,
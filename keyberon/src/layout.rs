@@ -136,6 +136,25 @@ where
     /// History of device IDs that sent events, most-recent-first.
     /// Used by `(device-history N recency)` switch conditions.
     pub device_history: ArrayDeque<Option<std::num::NonZeroU8>, 8, arraydeque::behavior::Wrapping>,
+    /// Values of the user-defined state variables, indexed by `(state ...)` switch conditions.
+    pub state_vars: Box<[i16]>,
+    /// Layers that are activated automatically while other layers are held.
+    pub layer_conditions: Box<[LayerCondition]>,
+    /// Layer kept active by [`Action::LayerLock`].
+    pub locked_layer: Option<u16>,
+    /// Layer kept active by the user of the layout until a word ends, e.g. kanata's
+    /// `layer-word`. It sits above `locked_layer` and is not affected by the unlock keys.
+    pub word_layer: Option<u16>,
+    /// Pressing any of these keys, given as the columns of [`REAL_KEY_ROW`], unlocks
+    /// `locked_layer`.
+    pub layer_lock_unlock_keys: Box<[u16]>,
+    /// Pairs of mirrored columns of [`REAL_KEY_ROW`], in both directions, used while
+    /// `swap_hands` is active.
+    pub mirror: Box<[(u16, u16)]>,
+    pub swap_hands: SwapHands,
+    /// Keys pressed while swapped whose release has not arrived yet, with their mirror.
+    swapped_keys: Vec<(u16, u16), 16>,
+    pub auto_shift: Option<AutoShift<'a, T>>,
     rpt_multikey_key_buffer: MultiKeyBuffer<'a, T>,
     trans_resolution_behavior_v2: bool,
     delegate_to_first_layer: bool,
@@ -144,10 +163,50 @@ where
     /// Only stores data when the `tap_hold_tracker` feature is enabled;
     /// otherwise this is a zero-sized no-op.
     pub tap_hold_tracker: crate::tap_hold_tracker::TapHoldTracker,
+    /// Learned timing of the tap-hold actions that have `adaptive` bounds.
+    pub adaptive_tap_hold: crate::adaptive_tap_hold::AdaptiveTapHold,
 }
 
 pub use crate::tap_hold_tracker::{HoldActivatedInfo, TapActivatedInfo};
 
+/// A layer that is active while all of the `if_active` layers are held, e.g. a tri-layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerCondition {
+    pub if_active: Box<[u16]>,
+    pub then: u16,
+}
+
+/// Key codes that are output shifted when their key is held past a timeout.
+#[derive(Debug)]
+pub struct AutoShift<'a, T: 'a> {
+    pub enabled: bool,
+    /// The action, usually a [`HoldTapAction`], that replaces each key code of the layout.
+    pub actions: &'a [(KeyCode, &'a Action<'a, T>)],
+}
+
+impl<'a, T> AutoShift<'a, T> {
+    /// The action that replaces `action` from the layout, if it is an auto-shifted key code.
+    pub fn action_for(&self, action: &Action<'a, T>) -> Option<&'a Action<'a, T>> {
+        match action {
+            Action::KeyCode(kc) => self
+                .actions
+                .iter()
+                .find(|(key, _)| key == kc)
+                .map(|(_, action)| *action),
+            _ => None,
+        }
+    }
+}
+
+/// Whether key presses are resolved through [`Layout::mirror`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SwapHands {
+    Off,
+    On,
+    /// Swap only the next mirrored key press.
+    OneShot,
+}
+
 #[derive(Debug)]
 pub struct History<T> {
     events: ArrayDeque<T, HISTORICAL_EVENT_LEN, arraydeque::behavior::Wrapping>,
@@ -442,6 +501,7 @@ pub(crate) struct TapDanceState<'a, T: 'a> {
     actions: &'a [&'a Action<'a, T>],
     timeout: u16,
     num_taps: u16,
+    config: TapDanceConfig,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -508,6 +568,25 @@ pub enum WaitingAction {
 }
 
 impl<'a, T: std::fmt::Debug> WaitingState<'a, T> {
+    /// Coordinate of the key that started the waiting state.
+    pub fn coord(&self) -> KCoord {
+        self.coord
+    }
+
+    /// Number of ticks remaining until the waiting state times out.
+    pub fn remaining_timeout(&self) -> u16 {
+        self.timeout
+    }
+
+    /// Short description of what is being waited on.
+    pub fn kind(&self) -> &'static str {
+        match self.config {
+            WaitingConfig::HoldTap(_) => "tap-hold",
+            WaitingConfig::TapDance(_) => "tap-dance",
+            WaitingConfig::Chord(_) => "chord",
+        }
+    }
+
     fn tick_wt(
         &mut self,
         queued: &mut Queue,
@@ -519,14 +598,29 @@ impl<'a, T: std::fmt::Debug> WaitingState<'a, T> {
         let (ret, cfg_change) = match self.config {
             WaitingConfig::HoldTap(htc) => (self.handle_hold_tap(htc, queued), None),
             WaitingConfig::TapDance(ref tds) => {
-                let (ret, num_taps) =
-                    self.handle_tap_dance(tds.num_taps, tds.actions.len(), queued);
+                let (ret, num_taps) = match tds.config {
+                    TapDanceConfig::LazyHold { hold_timeout } => self.handle_tap_dance_hold(
+                        tds.timeout,
+                        hold_timeout,
+                        tds.actions.len() / 2,
+                        queued,
+                    ),
+                    _ => self.handle_tap_dance(tds.num_taps, tds.actions.len(), queued),
+                };
                 self.prev_queue_len = queued.len() as u8;
                 // Due to ownership issues, handle_tap_dance can't contain all of the necessary
                 // logic.
                 if ret.is_some() {
-                    let idx = core::cmp::min(num_taps.into(), tds.actions.len()).saturating_sub(1);
-                    self.tap = tds.actions[idx];
+                    if let TapDanceConfig::LazyHold { .. } = tds.config {
+                        let idx = core::cmp::min(num_taps.into(), tds.actions.len() / 2)
+                            .saturating_sub(1);
+                        self.tap = tds.actions[2 * idx];
+                        self.hold = tds.actions[2 * idx + 1];
+                    } else {
+                        let idx =
+                            core::cmp::min(num_taps.into(), tds.actions.len()).saturating_sub(1);
+                        self.tap = tds.actions[idx];
+                    }
                 }
                 if num_taps > tds.num_taps {
                     self.timeout = tds.timeout;
@@ -646,27 +740,8 @@ impl<'a, T: std::fmt::Debug> WaitingState<'a, T> {
             // Fast path: nothing has changed since last tick and we haven't timed out yet.
             return (None, num_taps);
         }
-        // Evict events with the same coordinates except for the final release. E.g. if 3 taps have
-        // occurred, this will remove all `Press` events and 2 `Release` events. This is done so
-        // that the state machine processes the entire tap dance sequence as a single press and
-        // single release regardless of how many taps were actually done.
-        let evict_same_coord_events = |num_taps: u16, queued: &mut Queue| {
-            let mut releases_to_remove = num_taps.saturating_sub(1);
-            queued.retain(|s| {
-                let mut do_retain = true;
-                if self.is_corresponding_release(&s.event) {
-                    if releases_to_remove > 0 {
-                        do_retain = false;
-                        releases_to_remove = releases_to_remove.saturating_sub(1)
-                    }
-                } else if self.is_corresponding_press(&s.event) {
-                    do_retain = false;
-                }
-                do_retain
-            });
-        };
         if self.timeout == 0 {
-            evict_same_coord_events(num_taps, queued);
+            self.evict_same_coord_events(num_taps, queued);
             return (Some(WaitingAction::Tap), num_taps);
         }
         // Get the number of sequential taps for this tap-dance key. If a different key was
@@ -681,17 +756,81 @@ impl<'a, T: std::fmt::Debug> WaitingState<'a, T> {
             }
         }) {
             Ok(num_taps) if usize::from(num_taps) >= max_taps => {
-                evict_same_coord_events(num_taps, queued);
+                self.evict_same_coord_events(num_taps, queued);
                 (Some(WaitingAction::Tap), num_taps)
             }
             Ok(num_taps) => (None, num_taps),
             Err((num_taps, _)) => {
-                evict_same_coord_events(num_taps, queued);
+                self.evict_same_coord_events(num_taps, queued);
                 (Some(WaitingAction::Tap), num_taps)
             }
         }
     }
 
+    /// Like `handle_tap_dance`, but the key being pressed for `hold_timeout` since its latest
+    /// press resolves to `Hold`. The tap-dance `timeout` and presses of other keys only end the
+    /// sequence while the key is released.
+    fn handle_tap_dance_hold(
+        &self,
+        timeout: u16,
+        hold_timeout: u16,
+        max_taps: usize,
+        queued: &mut Queue,
+    ) -> (Option<WaitingAction>, u16) {
+        let mut num_taps: u16 = 1;
+        // Ticks since the latest press of the tap-dance key.
+        let mut pressed_for = self.ticks;
+        let mut released = false;
+        let mut other_press = false;
+        for s in queued.iter() {
+            if self.is_corresponding_press(&s.event) {
+                num_taps += 1;
+                // `since` also counts the tick the press was queued in, unlike `ticks`, which
+                // starts counting on the tick after the first press.
+                pressed_for = s.since.saturating_sub(1);
+                released = false;
+            } else if self.is_corresponding_release(&s.event) {
+                released = true;
+            } else if s.event.is_press() {
+                other_press = true;
+            }
+            if released && other_press {
+                break;
+            }
+        }
+        let ret = if !released {
+            (pressed_for >= hold_timeout).then_some(WaitingAction::Hold)
+        } else if other_press || usize::from(num_taps) >= max_taps || pressed_for >= timeout {
+            Some(WaitingAction::Tap)
+        } else {
+            None
+        };
+        if ret.is_some() {
+            self.evict_same_coord_events(num_taps, queued);
+        }
+        (ret, num_taps)
+    }
+
+    /// Evict events with the same coordinates except for the final release. E.g. if 3 taps have
+    /// occurred, this will remove all `Press` events and 2 `Release` events. This is done so
+    /// that the state machine processes the entire tap dance sequence as a single press and
+    /// single release regardless of how many taps were actually done.
+    fn evict_same_coord_events(&self, num_taps: u16, queued: &mut Queue) {
+        let mut releases_to_remove = num_taps.saturating_sub(1);
+        queued.retain(|s| {
+            let mut do_retain = true;
+            if self.is_corresponding_release(&s.event) {
+                if releases_to_remove > 0 {
+                    do_retain = false;
+                    releases_to_remove = releases_to_remove.saturating_sub(1)
+                }
+            } else if self.is_corresponding_press(&s.event) {
+                do_retain = false;
+            }
+            do_retain
+        });
+    }
+
     fn handle_chord(
         &mut self,
         config: &'a ChordsGroup<'a, T>,
@@ -1236,8 +1375,18 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             delegate_to_first_layer: false,
             chords_v2: None,
             device_history: ArrayDeque::new(),
+            state_vars: Box::default(),
+            layer_conditions: Box::default(),
+            locked_layer: None,
+            word_layer: None,
+            layer_lock_unlock_keys: Box::default(),
+            mirror: Box::default(),
+            swap_hands: SwapHands::Off,
+            swapped_keys: Vec::new(),
+            auto_shift: None,
             contextual_execution: ContextualExecution::new(),
             tap_hold_tracker: Default::default(),
+            adaptive_tap_hold: Default::default(),
         }
     }
     pub fn new_with_trans_action_settings(
@@ -1296,6 +1445,9 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             };
             let layer_stack = w.layer_stack.clone();
             self.tap_hold_tracker.set_hold_activated(coord, &w.config);
+            if matches!(w.config, WaitingConfig::HoldTap(..)) {
+                self.adaptive_tap_hold.resolve(coord, true);
+            }
             if idx < 0 {
                 self.waiting = None;
             } else {
@@ -1336,6 +1488,9 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             };
             let layer_stack = w.layer_stack.clone();
             self.tap_hold_tracker.set_tap_activated(coord, &w.config);
+            if matches!(w.config, WaitingConfig::HoldTap(..)) {
+                self.adaptive_tap_hold.resolve(coord, false);
+            }
             if idx < 0 {
                 self.waiting = None;
             } else {
@@ -1433,6 +1588,9 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             };
             let layer_stack = w.layer_stack.clone();
             self.tap_hold_tracker.set_hold_activated(coord, &w.config);
+            if matches!(w.config, WaitingConfig::HoldTap(..)) {
+                self.adaptive_tap_hold.resolve(coord, true);
+            }
             if idx < 0 {
                 self.waiting = None;
             } else {
@@ -1511,6 +1669,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
         self.historical_keys.tick_hist();
         self.historical_inputs.tick_hist();
         self.historical_inputs_sans_holds_or_timeouts.tick_hist();
+        self.adaptive_tap_hold.tick();
 
         let mut custom = CustomEvent::NoEvent;
         if let Some(released_keys) = self.oneshot.tick_osh() {
@@ -1767,6 +1926,13 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             }
 
             Press(i, j) => {
+                let j = self.swap_hands_press(i, j);
+                if i == REAL_KEY_ROW
+                    && self.locked_layer.is_some()
+                    && self.layer_lock_unlock_keys.contains(&j)
+                {
+                    self.locked_layer = None;
+                }
                 let mut layer_stack = self.trans_resolution_layer_order().into_iter();
                 let mut custom_activation_count = 0;
                 if let Some(tde) = &mut self.tap_dance_eager {
@@ -1788,8 +1954,9 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                         if i == REAL_KEY_ROW {
                             tde.set_expired();
                         }
+                        let action = self.auto_shift_action((i, j), &mut layer_stack.clone());
                         self.do_action(
-                            &Action::Trans,
+                            action,
                             (i, j),
                             queue.since,
                             false,
@@ -1798,8 +1965,9 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                         )
                     }
                 } else {
+                    let action = self.auto_shift_action((i, j), &mut layer_stack.clone());
                     self.do_action(
-                        &Action::Trans,
+                        action,
                         (i, j),
                         queue.since,
                         false,
@@ -1810,8 +1978,72 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             }
         }
     }
+
+    /// The action for a pressed key: the auto-shift replacement of its key code if there is
+    /// one, and otherwise [`Action::Trans`] to resolve it from the layers.
+    fn auto_shift_action(
+        &self,
+        coord: KCoord,
+        layer_stack: &mut (impl Iterator<Item = u16> + Clone),
+    ) -> &'a Action<'a, T> {
+        match &self.auto_shift {
+            Some(auto_shift) if auto_shift.enabled && coord.0 == REAL_KEY_ROW => auto_shift
+                .action_for(self.resolve_coord(coord, layer_stack))
+                .unwrap_or(&Action::Trans),
+            _ => &Action::Trans,
+        }
+    }
+
+    /// Returns the column to use for a pressed key, which is its mirror while hands are swapped.
+    /// The release of a mirrored key is mirrored too, whether it is already queued or not.
+    fn swap_hands_press(&mut self, i: u8, j: u16) -> u16 {
+        if i != REAL_KEY_ROW {
+            return j;
+        }
+        self.swapped_keys.retain(|(from, _)| *from != j);
+        if self.swap_hands == SwapHands::Off {
+            return j;
+        }
+        let Some(&(_, mirrored)) = self.mirror.iter().find(|(from, _)| *from == j) else {
+            return j;
+        };
+        match self
+            .queue
+            .iter_mut()
+            .find(|q| q.event == Event::Release(i, j))
+        {
+            Some(q) => q.event = Event::Release(i, mirrored),
+            None => {
+                if self.swapped_keys.push((j, mirrored)).is_err() {
+                    // The release could not be mirrored and the key would get stuck,
+                    // so this press is not swapped.
+                    return j;
+                }
+            }
+        }
+        if self.swap_hands == SwapHands::OneShot {
+            self.swap_hands = SwapHands::Off;
+        }
+        mirrored
+    }
+
+    /// Mirror the release of a key that was pressed while hands were swapped.
+    fn swap_hands_release(&mut self, event: Event) -> Event {
+        match event {
+            Event::Release(i, j) if i == REAL_KEY_ROW => {
+                match self.swapped_keys.iter().position(|(from, _)| *from == j) {
+                    Some(idx) => Event::Release(i, self.swapped_keys.swap_remove(idx).1),
+                    None => event,
+                }
+            }
+            _ => event,
+        }
+    }
+
     /// Register a key event.
     pub fn event(&mut self, event: Event) {
+        let event = self.swap_hands_release(event);
+        self.adaptive_tap_hold.event(event);
         if let Event::Press(x, y) = event {
             self.historical_inputs.push_front((x, y));
             self.historical_inputs_sans_holds_or_timeouts
@@ -1832,6 +2064,8 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
     /// Put a key event at the front instead of back.
     /// These events will not participate in chordsv2.
     pub fn event_to_front(&mut self, event: Event) {
+        let event = self.swap_hands_release(event);
+        self.adaptive_tap_hold.event(event);
         if let Event::Press(x, y) = event {
             self.historical_inputs.push_front((x, y));
             self.historical_inputs_sans_holds_or_timeouts
@@ -1947,6 +2181,18 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                     custom_activation_count,
                 );
             }
+            LayerLock => {
+                self.last_press_tracker.update_coord(coord);
+                self.locked_layer = match self.locked_layer {
+                    Some(_) => None,
+                    None => Some(self.current_layer() as u16)
+                        .filter(|&layer| usize::from(layer) != self.default_layer),
+                };
+                if !is_oneshot {
+                    self.oneshot
+                        .handle_press(OneShotHandlePressKey::Other(coord));
+                }
+            }
             Trans => {
                 // Transparent action should be resolved to non-transparent one near the top
                 // of `do_action`.
@@ -1985,11 +2231,23 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                 tap_hold_interval,
                 on_press_reset_timeout_to,
                 require_prior_idle,
+                adaptive,
             }) => {
                 // Typing streak detection: if a different physical key was pressed
                 // recently, resolve as tap immediately without entering WaitingState.
                 // Per-action override takes precedence over the global defcfg value.
-                let idle_threshold = require_prior_idle.unwrap_or(self.tap_hold_require_prior_idle);
+                let mut idle_threshold =
+                    require_prior_idle.unwrap_or(self.tap_hold_require_prior_idle);
+                let mut timeout = *timeout;
+                if let Some(bounds) = adaptive {
+                    (timeout, idle_threshold) = self.adaptive_tap_hold.press(
+                        coord,
+                        *bounds,
+                        timeout,
+                        idle_threshold,
+                        delay,
+                    );
+                }
                 if idle_threshold > 0 {
                     let prior_idle_tap = self
                         .historical_inputs_sans_holds_or_timeouts
@@ -2017,6 +2275,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                         })
                         .is_some_and(|prior| prior.ticks_since_occurrence <= idle_threshold);
                     if prior_idle_tap {
+                        self.adaptive_tap_hold.resolve(coord, false);
                         let custom = self.do_action(
                             tap,
                             coord,
@@ -2060,6 +2319,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                     self.last_press_tracker.tap_hold_timeout = *tap_hold_interval;
                 } else {
                     self.last_press_tracker.tap_hold_timeout = 0;
+                    self.adaptive_tap_hold.resolve(coord, false);
                     custom.update(self.do_action(
                         tap,
                         coord,
@@ -2103,7 +2363,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             &TapDance(td) => {
                 self.last_press_tracker.update_coord(coord);
                 match td.config {
-                    TapDanceConfig::Lazy => {
+                    TapDanceConfig::Lazy | TapDanceConfig::LazyHold { .. } => {
                         self.waiting = Some(WaitingState {
                             coord,
                             timeout: td.timeout,
@@ -2117,6 +2377,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                                 actions: td.actions,
                                 timeout: td.timeout,
                                 num_taps: 1,
+                                config: td.config,
                             }),
                             layer_stack: layer_stack.collect(),
                             prev_queue_len: QueueLen::MAX,
@@ -2174,6 +2435,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
             }
             &KeyCode(keycode) => {
                 self.last_press_tracker.update_coord(coord);
+                self.adaptive_tap_hold.key_output(keycode);
                 // Most-recent-first!
                 self.contextual_execution
                     .push_historical_key(&mut self.historical_keys, keycode);
@@ -2448,6 +2710,7 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
                     // assertions.
                     self.default_layer as u16,
                     self.device_history.iter().copied(),
+                    self.state_vars.iter().copied(),
                 ) {
                     action_queue.push_back(Some((coord, delay, ac, layer_stack.clone().collect())));
                 }
@@ -2474,25 +2737,59 @@ impl<'a, const C: usize, const R: usize, T: 'a + Copy + std::fmt::Debug> Layout<
 
     /// Obtain the index of the current active layer
     pub fn current_layer(&self) -> usize {
-        self.states
-            .iter()
-            .rev()
-            .find_map(State::get_layer)
+        self.conditional_layers()
+            .first()
+            .map(|&layer| usize::from(layer))
+            .or_else(|| self.states.iter().rev().find_map(State::get_layer))
+            .or(self.word_layer.map(usize::from))
+            .or(self.locked_layer.map(usize::from))
             .unwrap_or(self.default_layer)
     }
 
+    /// Active layers from most to least recent, excluding the default layer. Layers from
+    /// [`LayerCondition`]s come first, ahead of the held layers that activate them. The word
+    /// layer and then the locked layer come last, below the layers that are held on top of them.
     pub fn active_held_layers(&self) -> impl Iterator<Item = u16> + Clone + '_ {
-        self.states
+        self.conditional_layers()
+            .into_iter()
+            .chain(
+                self.states
+                    .iter()
+                    .filter_map(|s| State::get_layer(s).map(|l| l as u16))
+                    .rev(),
+            )
+            .chain(self.word_layer)
+            .chain(self.locked_layer)
+    }
+
+    /// Target layers of the [`LayerCondition`]s whose layers are all held, locked or the word
+    /// layer, in definition order.
+    fn conditional_layers(&self) -> LayerStack {
+        self.layer_conditions
             .iter()
-            .filter_map(|s| State::get_layer(s).map(|l| l as u16))
-            .rev()
+            .filter(|cond| {
+                cond.if_active.iter().all(|&layer| {
+                    self.locked_layer == Some(layer)
+                        || self.word_layer == Some(layer)
+                        || self
+                            .states
+                            .iter()
+                            .any(|s| s.get_layer() == Some(usize::from(layer)))
+                })
+            })
+            .map(|cond| cond.then)
+            .take(MAX_ACTIVE_LAYERS)
+            .collect()
     }
 
     /// Returns a list indices of layers that should be used for [`Action::Trans`] resolution.
     pub fn trans_resolution_layer_order(&self) -> LayerStack {
         let current_layer = self.current_layer();
         if self.trans_resolution_behavior_v2 {
-            let mut v = self.active_held_layers().collect::<LayerStack>();
+            let mut v = self
+                .active_held_layers()
+                .take(MAX_ACTIVE_LAYERS)
+                .collect::<LayerStack>();
             let _ = v.push(self.default_layer as u16);
             if self.delegate_to_first_layer && current_layer != 0 && self.default_layer != 0 {
                 let _ = v.push(0);
@@ -2541,6 +2838,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 200,
                     hold: l(1),
                     tap: k(Space),
@@ -2551,6 +2849,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 200,
                     hold: k(LCtrl),
                     timeout_action: k(LShift),
@@ -2597,6 +2896,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 200,
                     hold: l(1),
                     tap: k(Space),
@@ -2607,6 +2907,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 200,
                     hold: k(LCtrl),
                     timeout_action: k(LCtrl),
@@ -2652,6 +2953,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -2662,6 +2964,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 20,
                 hold: k(LCtrl),
                 timeout_action: k(LCtrl),
@@ -2705,6 +3008,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -2772,6 +3076,7 @@ mod test {
                 config: HoldTapConfig::Order { buffer: 0 },
                 tap_hold_interval: 0,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(Enter),
         ]]];
@@ -2804,6 +3109,7 @@ mod test {
                 config: HoldTapConfig::Order { buffer: 0 },
                 tap_hold_interval: 0,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(Enter),
         ]]];
@@ -2842,6 +3148,7 @@ mod test {
                 config: HoldTapConfig::Order { buffer: 0 },
                 tap_hold_interval: 0,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(Enter),
         ]]];
@@ -2875,6 +3182,7 @@ mod test {
                 config: HoldTapConfig::Order { buffer: 0 },
                 tap_hold_interval: 0,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(Enter),
             k(Tab),
@@ -2924,6 +3232,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: u16::MAX,
                 hold: k(LAlt),
                 timeout_action: k(Space),
@@ -2966,6 +3275,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3008,6 +3318,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3018,6 +3329,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(RAlt),
                 timeout_action: k(RAlt),
@@ -3028,6 +3340,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LCtrl),
                 timeout_action: k(LCtrl),
@@ -3176,6 +3489,197 @@ mod test {
         assert_keys(&[], layout.keycodes());
     }
 
+    #[test]
+    fn layer_conditions() {
+        static LAYERS: Layers<4, 1> = &[
+            [[l(1), l(2), k(A), k(B)]],
+            [[Trans, Trans, k(C), k(D)]],
+            [[Trans, Trans, k(E), k(F)]],
+            [[Trans, Trans, k(G), Trans]],
+        ];
+        let mut layout = Layout::new(LAYERS);
+        layout.layer_conditions = Box::new([LayerCondition {
+            if_active: Box::new([1, 2]),
+            then: 3,
+        }]);
+
+        // only one of the layers is held
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(1, layout.current_layer());
+        // both are held: the target layer is on top, transparent keys fall through to them
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(3, layout.current_layer());
+        assert_eq!(&[3, 2, 1, 0], &layout.trans_resolution_layer_order()[..]);
+        layout.event(Press(0, 2));
+        layout.event(Press(0, 3));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[G, F], layout.keycodes());
+        layout.event(Release(0, 2));
+        layout.event(Release(0, 3));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        // releasing either layer drops the target layer
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(2, layout.current_layer());
+        layout.event(Release(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(0, layout.current_layer());
+    }
+
+    #[test]
+    fn layer_lock() {
+        static LAYERS: Layers<3, 1> = &[[[l(1), k(A), k(Escape)]], [[Trans, LayerLock, k(B)]]];
+        let mut layout = Layout::new(LAYERS);
+        layout.layer_lock_unlock_keys = Box::new([2]);
+
+        // lock the held layer, it stays active after release
+        layout.event(Press(0, 0));
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(Some(1), layout.locked_layer);
+        layout.event(Release(0, 1));
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(1, layout.current_layer());
+        // pressing it again unlocks
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(None, layout.locked_layer);
+        assert_eq!(0, layout.current_layer());
+        layout.event(Release(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        // lock again
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Release(0, 0));
+        layout.event(Release(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(1, layout.current_layer());
+        // an unlock key unlocks and acts on the layer below
+        layout.event(Press(0, 2));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(0, layout.current_layer());
+        assert_keys(&[Escape], layout.keycodes());
+    }
+
+    #[test]
+    fn swap_hands() {
+        static LAYERS: Layers<3, 1> = &[[[k(A), k(B), k(C)]]];
+        let mut layout = Layout::new(LAYERS);
+        layout.mirror = Box::new([(0, 1), (1, 0)]);
+        layout.swap_hands = SwapHands::On;
+
+        // the mirror of a key is pressed, and released even after swapping ends
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[B], layout.keycodes());
+        layout.swap_hands = SwapHands::Off;
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+        // keys without a mirror are not swapped
+        layout.swap_hands = SwapHands::On;
+        layout.event(Press(0, 2));
+        layout.event(Release(0, 2));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[C], layout.keycodes());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        // one-shot swaps the next mirrored key only, its queued release is mirrored too
+        layout.swap_hands = SwapHands::OneShot;
+        layout.event(Press(0, 1));
+        layout.event(Release(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[A], layout.keycodes());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+        assert_eq!(SwapHands::Off, layout.swap_hands);
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[B], layout.keycodes());
+    }
+
+    #[test]
+    fn swap_hands_too_many_held() {
+        const KEY: Action<core::convert::Infallible> = k(A);
+        static LAYERS: Layers<34, 1> = &[[[KEY; 34]]];
+        let mut layout = Layout::new(LAYERS);
+        layout.mirror = (0..17).map(|j| (j, j + 17)).collect();
+        layout.swap_hands = SwapHands::On;
+
+        // keys held beyond the tracked count are not swapped, so none gets stuck
+        for j in 0..17 {
+            layout.event(Press(0, j));
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+        }
+        assert_eq!(16, layout.swapped_keys.len());
+        for j in 0..17 {
+            layout.event(Release(0, j));
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+        }
+        assert_keys(&[], layout.keycodes());
+    }
+
+    #[test]
+    fn auto_shift() {
+        static LAYERS: Layers<2, 1> = &[[[k(A), k(LCtrl)]]];
+        static SHIFTED_A: Action<core::convert::Infallible> = HoldTap(&HoldTapAction {
+            on_press_reset_timeout_to: None,
+            require_prior_idle: None,
+            adaptive: None,
+            timeout: 10,
+            hold: MultipleKeyCodes(&[LShift, A].as_slice()),
+            timeout_action: MultipleKeyCodes(&[LShift, A].as_slice()),
+            tap: k(A),
+            config: HoldTapConfig::Default,
+            tap_hold_interval: 0,
+        });
+        static AUTO_SHIFT_ACTIONS: &[(KeyCode, &Action<core::convert::Infallible>)] =
+            &[(A, &SHIFTED_A)];
+        let mut layout = Layout::new(LAYERS);
+        layout.auto_shift = Some(AutoShift {
+            enabled: true,
+            actions: AUTO_SHIFT_ACTIONS,
+        });
+
+        // tap
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[A], layout.keycodes());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+        // hold past the timeout
+        layout.event(Press(0, 0));
+        for _ in 0..10 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+            assert_keys(&[], layout.keycodes());
+        }
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LShift, A], layout.keycodes());
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+        // other keys and disabled auto-shift are not affected
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LCtrl], layout.keycodes());
+        layout.auto_shift.as_mut().unwrap().enabled = false;
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LCtrl, A], layout.keycodes());
+    }
+
     #[test]
     fn custom_handler() {
         fn always_tap(_: QueuedIter, _: KCoord) -> (Option<WaitingAction>, bool) {
@@ -3194,6 +3698,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(Kb1),
                 timeout_action: k(Kb1),
@@ -3204,6 +3709,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(Kb3),
                 timeout_action: k(Kb3),
@@ -3214,6 +3720,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(Kb5),
                 timeout_action: k(Kb5),
@@ -3224,6 +3731,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(Kb7),
                 timeout_action: k(Kb7),
@@ -3299,6 +3807,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3356,6 +3865,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3367,6 +3877,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3482,6 +3993,7 @@ mod test {
         static LAYERS: Layers<1, 1> = &[[[HoldTap(&HoldTapAction {
             on_press_reset_timeout_to: None,
             require_prior_idle: None,
+            adaptive: None,
             timeout: 50,
             hold: k(LAlt),
             timeout_action: k(LAlt),
@@ -3527,6 +4039,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 50,
                 hold: k(LAlt),
                 timeout_action: k(LAlt),
@@ -3537,6 +4050,7 @@ mod test {
             HoldTap(&HoldTapAction {
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
                 timeout: 200,
                 hold: k(RAlt),
                 timeout_action: k(RAlt),
@@ -4056,6 +4570,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 100,
                     hold: k(LAlt),
                     timeout_action: k(LAlt),
@@ -4123,6 +4638,7 @@ mod test {
                         &HoldTap(&HoldTapAction {
                             on_press_reset_timeout_to: None,
                             require_prior_idle: None,
+                            adaptive: None,
                             timeout: 100,
                             hold: k(LAlt),
                             timeout_action: k(LAlt),
@@ -4249,6 +4765,89 @@ mod test {
         assert_keys(&[], layout.keycodes());
     }
 
+    #[test]
+    fn tap_dance_lazy_hold() {
+        static LAYERS: Layers<2, 1> = &[[[
+            TapDance(&crate::action::TapDance {
+                timeout: 100,
+                actions: &[&k(A), &k(LShift), &k(B), &k(LCtrl)],
+                config: TapDanceConfig::LazyHold { hold_timeout: 50 },
+            }),
+            k(C),
+        ]]];
+        let mut layout = Layout::new(LAYERS);
+
+        // Test: hold, activated by the hold timeout rather than the tap-dance timeout
+        layout.event(Press(0, 0));
+        for _ in 0..50 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+            assert_keys(&[], layout.keycodes());
+        }
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LShift], layout.keycodes());
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+
+        // Test: tap, then hold measured from the second press
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Release(0, 0));
+        for _ in 0..20 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+        }
+        layout.event(Press(0, 0));
+        for _ in 0..50 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+            assert_keys(&[], layout.keycodes());
+        }
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LCtrl], layout.keycodes());
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+
+        // Test: another key pressed while held does not end the tap dance
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Press(0, 1));
+        for _ in 0..49 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+            assert_keys(&[], layout.keycodes());
+        }
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LShift], layout.keycodes());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[LShift, C], layout.keycodes());
+        layout.event(Release(0, 1));
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+
+        // Test: tap, decided by the tap-dance timeout
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Release(0, 0));
+        for _ in 0..99 {
+            assert_eq!(CustomEvent::NoEvent, layout.tick());
+            assert_keys(&[], layout.keycodes());
+        }
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[A], layout.keycodes());
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[], layout.keycodes());
+
+        // Test: tap, ended by another key
+        layout.event(Press(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Release(0, 0));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        layout.event(Press(0, 1));
+        assert_eq!(CustomEvent::NoEvent, layout.tick());
+        assert_keys(&[A], layout.keycodes());
+    }
+
     #[test]
     fn tap_dance_eager() {
         static LAYERS: Layers<2, 2> = &[[
@@ -4565,6 +5164,7 @@ mod test {
                     &HoldTap(&HoldTapAction {
                         on_press_reset_timeout_to: None,
                         require_prior_idle: None,
+                        adaptive: None,
                         timeout: 100,
                         hold: k(A),
                         timeout_action: k(A),
@@ -4578,6 +5178,7 @@ mod test {
                     &HoldTap(&HoldTapAction {
                         on_press_reset_timeout_to: None,
                         require_prior_idle: None,
+                        adaptive: None,
                         timeout: 100,
                         hold: k(B),
                         timeout_action: k(B),
@@ -4831,6 +5432,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 50,
                     hold: k(Space),
                     timeout_action: k(Space),
@@ -4880,6 +5482,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 50,
                     hold: Trans,
                     timeout_action: Trans,
@@ -5116,6 +5719,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 50,
                     hold: k(B),
                     timeout_action: k(B),
@@ -5131,6 +5735,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 50,
                     hold: k(C),
                     timeout_action: k(C),
@@ -5146,6 +5751,7 @@ mod test {
                 HoldTap(&HoldTapAction {
                     on_press_reset_timeout_to: None,
                     require_prior_idle: None,
+                    adaptive: None,
                     timeout: 50,
                     hold: k(D),
                     timeout_action: k(D),
@@ -5261,6 +5867,7 @@ mod test {
             tap_hold_interval: 0,
             on_press_reset_timeout_to: None,
             require_prior_idle: None,
+            adaptive: None,
         })]]];
         let mut layout = Layout::new(LAYERS);
         // Nothing set initially.
@@ -5295,6 +5902,7 @@ mod test {
                 tap_hold_interval: 0,
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(A),
         ]]];
@@ -5329,6 +5937,7 @@ mod test {
                 tap_hold_interval: 0,
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(A),
         ]]];
@@ -5365,6 +5974,7 @@ mod test {
                 tap_hold_interval: 0,
                 on_press_reset_timeout_to: None,
                 require_prior_idle: None,
+                adaptive: None,
             }),
             k(A),
         ]]];
@@ -2,6 +2,7 @@
 //! Please make contributions to the original project.
 
 pub mod action;
+pub mod adaptive_tap_hold;
 pub mod chord;
 pub mod key_code;
 pub mod layout;
@@ -0,0 +1,48 @@
+//! Completion items for key names, list actions and aliases.
+
+use crate::hover::action_doc;
+use crate::symbols::{SymbolKind, defined_names};
+use kanata_parser::cfg::list_actions::LIST_ACTIONS;
+use kanata_parser::keys::key_names;
+use kanata_parser::lsp_hints::LspHints;
+use lsp_types::{CompletionItem, CompletionItemKind, Documentation, MarkupContent, MarkupKind};
+
+/// Completions for the partially typed atom `word`. `after_paren` is true if the atom is the
+/// first item of a list, where an action name is expected.
+pub(crate) fn complete(hints: &LspHints, word: &str, after_paren: bool) -> Vec<CompletionItem> {
+    if let Some(alias) = word.strip_prefix('@') {
+        return defined_names(hints, SymbolKind::Alias)
+            .filter(|name| name.starts_with(alias))
+            .map(|name| CompletionItem {
+                label: format!("@{name}"),
+                kind: Some(CompletionItemKind::VARIABLE),
+                ..Default::default()
+            })
+            .collect();
+    }
+    if after_paren {
+        return LIST_ACTIONS
+            .iter()
+            .filter(|ac| ac.starts_with(word))
+            .map(|&ac| CompletionItem {
+                label: ac.to_string(),
+                kind: Some(CompletionItemKind::FUNCTION),
+                documentation: action_doc(ac).map(|value| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    })
+                }),
+                ..Default::default()
+            })
+            .collect();
+    }
+    key_names()
+        .filter(|name| name.starts_with(word))
+        .map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::CONSTANT),
+            ..Default::default()
+        })
+        .collect()
+}
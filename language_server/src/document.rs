@@ -0,0 +1,468 @@
+//! An open configuration file and the results of parsing it.
+
+use crate::HashMap;
+use crate::completion::complete;
+use crate::hover::action_doc;
+use crate::symbols::{Symbol, SymbolKind, symbol_at};
+use kanata_parser::cfg::sexpr::Span;
+use kanata_parser::cfg::{FileContentProvider, ParseError, ParseWarning, check_for_lsp};
+use kanata_parser::keys::str_to_oscode;
+use kanata_parser::lsp_hints::LspHints;
+use lsp_types::*;
+use std::path::{Path, PathBuf};
+
+pub(crate) struct Document {
+    uri: Url,
+    path: PathBuf,
+    text: String,
+    /// Hints from the latest parse. If parsing failed, these only cover the part of the
+    /// configuration that was parsed before the error.
+    hints: LspHints,
+    error: Option<ParseError>,
+    warnings: Vec<ParseWarning>,
+    /// Diagnostics to publish, per file. Files that had diagnostics published before but have
+    /// none now are included with an empty list so that the client clears them.
+    diagnostics: Vec<(Url, Vec<Diagnostic>)>,
+}
+
+impl Document {
+    pub(crate) fn new(uri: Url, text: String) -> Self {
+        let path = uri
+            .to_file_path()
+            .unwrap_or_else(|_| PathBuf::from(uri.path()));
+        let mut doc = Self {
+            uri,
+            path,
+            text,
+            hints: LspHints::default(),
+            error: None,
+            warnings: vec![],
+            diagnostics: vec![],
+        };
+        doc.analyze();
+        doc
+    }
+
+    pub(crate) fn update(&mut self, text: String) {
+        self.text = text;
+        self.analyze();
+    }
+
+    pub(crate) fn analyze(&mut self) {
+        let dir = self.dir().to_owned();
+        // Make the include paths relative to the configuration file, as kanata does.
+        let mut get_file_content = |filepath: &Path| {
+            std::fs::read_to_string(dir.join(filepath))
+                .map_err(|e| format!("Failed to include file: {e}"))
+        };
+        let (res, hints, warnings) = check_for_lsp(
+            &self.text,
+            &self.path,
+            &mut FileContentProvider::new(&mut get_file_content),
+        );
+        self.hints = hints;
+        self.error = res.err();
+        self.warnings = warnings;
+        if let Some(e) = &self.error {
+            log::debug!("{}: {}", self.uri, e.msg);
+        }
+        self.update_diagnostics();
+    }
+
+    pub(crate) fn diagnostics(&self) -> Vec<(Url, Vec<Diagnostic>)> {
+        self.diagnostics.clone()
+    }
+
+    fn update_diagnostics(&mut self) {
+        let mut by_file: HashMap<Url, Vec<Diagnostic>> = HashMap::default();
+        let mut main = vec![];
+        for inactive in self.hints.inactive_code.iter() {
+            main.push(Diagnostic {
+                range: span_range(&inactive.span),
+                severity: Some(DiagnosticSeverity::HINT),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                source: Some("kanata".into()),
+                message: inactive.reason.clone(),
+                ..Default::default()
+            });
+        }
+        for e in self.error.iter().flat_map(ParseError::errors) {
+            let error = |range, message| Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("kanata".into()),
+                message,
+                ..Default::default()
+            };
+            match &e.span {
+                Some(span) if !self.is_main_file(span) => {
+                    // Also point at the include in the main file, so that the error is visible
+                    // without opening the included file.
+                    let include = self
+                        .hints
+                        .reference_locations
+                        .include
+                        .0
+                        .iter()
+                        .find(|(name, _)| trim_quotes(name) == &*span.file_name)
+                        .and_then(|(_, spans)| spans.first());
+                    let range = include.map(span_range).unwrap_or_default();
+                    main.push(error(
+                        range,
+                        format!("Error in included file {}: {}", span.file_name, e.msg),
+                    ));
+                    if let Some(uri) = self.uri_of(span) {
+                        by_file
+                            .entry(uri)
+                            .or_default()
+                            .push(error(span_range(span), e.msg.clone()));
+                    }
+                }
+                Some(span) => main.push(error(span_range(span), e.msg.clone())),
+                None => main.push(error(Range::default(), e.msg.clone())),
+            }
+        }
+        for w in self.warnings.iter() {
+            let warning = |range| Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: w
+                    .lint
+                    .map(|lint| NumberOrString::String(lint.name().into())),
+                source: Some("kanata".into()),
+                message: w.msg.clone(),
+                ..Default::default()
+            };
+            match &w.span {
+                Some(span) if !self.is_main_file(span) => {
+                    if let Some(uri) = self.uri_of(span) {
+                        by_file
+                            .entry(uri)
+                            .or_default()
+                            .push(warning(span_range(span)));
+                    }
+                }
+                Some(span) => main.push(warning(span_range(span))),
+                None => main.push(warning(Range::default())),
+            }
+        }
+        by_file.insert(self.uri.clone(), main);
+        for (uri, diags) in self.diagnostics.iter() {
+            if !diags.is_empty() {
+                by_file.entry(uri.clone()).or_default();
+            }
+        }
+        self.diagnostics = by_file.into_iter().collect();
+    }
+
+    pub(crate) fn definition(&self, pos: Position) -> Option<Location> {
+        let sym = self.symbol_at(pos)?;
+        if sym.kind == SymbolKind::Include {
+            let path = self.dir().join(trim_quotes(sym.name));
+            return Some(Location {
+                uri: Url::from_file_path(path).ok()?,
+                range: Range::default(),
+            });
+        }
+        let span = sym.kind.definition(&self.hints, sym.name)?;
+        self.location(span)
+    }
+
+    pub(crate) fn references(&self, pos: Position, include_declaration: bool) -> Vec<Location> {
+        let Some(sym) = self.symbol_at(pos) else {
+            return vec![];
+        };
+        let def = sym
+            .kind
+            .definition(&self.hints, sym.name)
+            .filter(|_| include_declaration);
+        def.into_iter()
+            .chain(sym.kind.references(&self.hints, sym.name))
+            .filter_map(|span| self.location(span))
+            .collect()
+    }
+
+    pub(crate) fn prepare_rename(&self, pos: Position) -> Option<Range> {
+        let sym = self.symbol_at(pos)?;
+        if !sym.kind.is_renamable() {
+            return None;
+        }
+        name_range(sym.span, sym.name)
+    }
+
+    pub(crate) fn rename(&self, pos: Position, new_name: &str) -> Option<WorkspaceEdit> {
+        let sym = self.symbol_at(pos)?;
+        if !sym.kind.is_renamable() || !is_valid_name(new_name) {
+            return None;
+        }
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::default();
+        let def = sym.kind.definition(&self.hints, sym.name);
+        for span in def
+            .into_iter()
+            .chain(sym.kind.references(&self.hints, sym.name))
+        {
+            let (Some(uri), Some(range)) = (self.uri_of(span), name_range(span, sym.name)) else {
+                continue;
+            };
+            changes.entry(uri).or_default().push(TextEdit {
+                range,
+                new_text: new_name.to_string(),
+            });
+        }
+        Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn hover(&self, pos: Position) -> Option<Hover> {
+        let offset = position_to_offset(&self.text, pos);
+        let markdown = match self.symbol_at(pos) {
+            Some(sym) => self.symbol_doc(sym),
+            None => {
+                let (word, range) = word_at(&self.text, offset);
+                let after_paren = self.text[..range.start].ends_with('(');
+                match action_doc(word) {
+                    Some(doc) if after_paren => doc,
+                    _ if str_to_oscode(word).is_some() => format!("key `{word}`"),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: markdown,
+            }),
+            range: None,
+        })
+    }
+
+    fn symbol_doc(&self, sym: Symbol) -> String {
+        let kind = sym.kind.name();
+        let name = sym.name;
+        match sym.kind.definition(&self.hints, name) {
+            Some(def) => {
+                let line = def.start.line + 1;
+                format!("{kind} `{name}`, defined in {} line {line}", def.file_name)
+            }
+            None if sym.kind == SymbolKind::Include => format!("{kind} {name}"),
+            None => format!("{kind} `{name}` (undefined)"),
+        }
+    }
+
+    pub(crate) fn completion(&self, pos: Position) -> Vec<CompletionItem> {
+        let offset = position_to_offset(&self.text, pos);
+        let (word, range) = word_at(&self.text, offset);
+        // Complete only the part of the atom before the cursor.
+        let prefix = &word[..offset - range.start];
+        let after_paren = self.text[..range.start].ends_with('(');
+        complete(&self.hints, prefix, after_paren)
+    }
+
+    fn symbol_at(&self, pos: Position) -> Option<Symbol<'_>> {
+        let offset = position_to_offset(&self.text, pos);
+        symbol_at(&self.hints, &self.path.to_string_lossy(), offset)
+    }
+
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+
+    fn is_main_file(&self, span: &Span) -> bool {
+        *span.file_name == *self.path.to_string_lossy()
+    }
+
+    fn uri_of(&self, span: &Span) -> Option<Url> {
+        if self.is_main_file(span) {
+            return Some(self.uri.clone());
+        }
+        Url::from_file_path(self.dir().join(&*span.file_name)).ok()
+    }
+
+    fn location(&self, span: &Span) -> Option<Location> {
+        Some(Location {
+            uri: self.uri_of(span)?,
+            range: span_range(span),
+        })
+    }
+}
+
+fn trim_quotes(s: &str) -> &str {
+    s.trim_start_matches('"').trim_end_matches('"')
+}
+
+/// Names can be used as atoms anywhere, so they must not contain characters that end an atom.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | ';'))
+}
+
+/// The range of `name` within the span. Reference spans include a prefix such as `@`, which is
+/// excluded.
+fn name_range(span: &Span, name: &str) -> Option<Range> {
+    let text = span.file_content.get(span.start()..span.end())?;
+    if !text.ends_with(name) {
+        return None;
+    }
+    let start = span.end() - name.len();
+    Some(Range {
+        start: offset_to_position(&span.file_content, start),
+        end: offset_to_position(&span.file_content, span.end()),
+    })
+}
+
+/// The atom containing, or ending at, `offset` and its byte range.
+fn word_at(text: &str, offset: usize) -> (&str, std::ops::Range<usize>) {
+    let is_delim = |c: char| c.is_whitespace() || c == '(' || c == ')';
+    let offset = offset.min(text.len());
+    let start = text[..offset]
+        .rfind(is_delim)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let end = text[offset..]
+        .find(is_delim)
+        .map(|i| i + offset)
+        .unwrap_or(text.len());
+    (&text[start..end], start..end)
+}
+
+fn span_range(span: &Span) -> Range {
+    Range {
+        start: offset_to_position(&span.file_content, span.start()),
+        end: offset_to_position(&span.file_content, span.end()),
+    }
+}
+
+/// Convert a byte offset to an LSP position, whose character offset counts UTF-16 code units.
+fn offset_to_position(text: &str, offset: usize) -> Position {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+fn position_to_offset(text: &str, pos: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..pos.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= pos.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CFG: &str = "\
+(defsrc a b)
+(defalias x (layer-while-held other))
+(deflayer base @x b)
+(deflayer other a @x)
+(defcfg process-unmapped-keys no)
+";
+
+    fn doc() -> Document {
+        let uri = Url::from_file_path(std::env::temp_dir().join("kanata-lsp-test.kbd")).unwrap();
+        Document::new(uri, CFG.to_string())
+    }
+
+    fn pos(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn positions_count_utf16() {
+        let text = "ab\n🔣x\n";
+        assert_eq!(offset_to_position(text, 7), pos(1, 2));
+        assert_eq!(position_to_offset(text, pos(1, 2)), 7);
+        assert_eq!(position_to_offset(text, pos(5, 0)), text.len());
+    }
+
+    #[test]
+    fn parse_error_is_published() {
+        let mut doc = doc();
+        assert!(doc.diagnostics().iter().all(|(_, d)| d.is_empty()));
+        doc.update("(defcfg process-unmapped-keys no)\n(defsrc a b)\n(deflayer base a)\n".into());
+        let diags = doc.diagnostics();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].1.len(), 1);
+        assert_eq!(diags[0].1[0].severity, Some(DiagnosticSeverity::ERROR));
+    }
+
+    #[test]
+    fn lint_warnings_are_published() {
+        let mut doc = doc();
+        doc.update(CFG.replace("(deflayer base @x b)", "(deflayer base a b)"));
+        let diags = doc.diagnostics();
+        assert_eq!(diags[0].1.len(), 1);
+        let diag = &diags[0].1[0];
+        assert_eq!(diag.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(
+            diag.code,
+            Some(NumberOrString::String("unreachable-layer".into()))
+        );
+        assert_eq!(diag.range.start, pos(3, 10));
+    }
+
+    #[test]
+    fn definition_and_references_of_alias() {
+        let doc = doc();
+        let def = doc.definition(pos(2, 16)).unwrap();
+        assert_eq!(def.range.start, pos(1, 10));
+        let refs = doc.references(pos(1, 10), true);
+        assert_eq!(refs.len(), 3);
+    }
+
+    #[test]
+    fn rename_keeps_alias_prefix() {
+        let doc = doc();
+        let edit = doc.rename(pos(3, 19), "y").unwrap();
+        let edits = &edit.changes.unwrap()[&doc.uri];
+        assert_eq!(edits.len(), 3);
+        for e in edits.iter() {
+            assert_eq!(CFG.as_bytes()[position_to_offset(CFG, e.range.start)], b'x');
+            assert_eq!(e.range.end.character - e.range.start.character, 1);
+        }
+        assert!(doc.rename(pos(3, 19), "bad name").is_none());
+    }
+
+    #[test]
+    fn rename_layer() {
+        let doc = doc();
+        let edit = doc.rename(pos(1, 32), "nav").unwrap();
+        assert_eq!(edit.changes.unwrap()[&doc.uri].len(), 2);
+    }
+
+    #[test]
+    fn hover_and_completion() {
+        let doc = doc();
+        let hover = doc.hover(pos(1, 14)).unwrap();
+        let HoverContents::Markup(content) = hover.contents else {
+            panic!("expected markdown");
+        };
+        assert!(content.value.contains("layer-while-held"));
+        let items = doc.completion(pos(1, 15));
+        assert!(items.iter().any(|i| i.label == "layer-while-held"));
+        let items = doc.completion(pos(0, 9));
+        assert!(items.iter().any(|i| i.label == "a"));
+    }
+}
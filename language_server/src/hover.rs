@@ -0,0 +1,353 @@
+//! Hover documentation for list actions.
+
+use kanata_parser::cfg::list_actions::*;
+
+const CONFIG_GUIDE: &str = "https://github.com/jtroo/kanata/blob/main/docs/config.adoc";
+
+/// Short descriptions of the list actions. Unicode alternative names share the description of
+/// the action they alias, so they are listed together.
+const ACTION_DOCS: &[(&[&str], &str)] = &[
+    (
+        &[LAYER_SWITCH],
+        "`(layer-switch $layer)`: change the base layer.",
+    ),
+    (
+        &[LAYER_TOGGLE, LAYER_WHILE_HELD],
+        "`(layer-while-held $layer)`: activate the layer while the key is held.",
+    ),
+    (
+        &[TAP_HOLD],
+        "`(tap-hold $tap-repress-timeout $hold-timeout $tap-action $hold-action)`: \
+        do the tap action if the key is released before the hold timeout, \
+        otherwise do the hold action.",
+    ),
+    (
+        &[TAP_HOLD_PRESS, TAP_HOLD_PRESS_A],
+        "`(tap-hold-press $tap-repress-timeout $hold-timeout $tap-action $hold-action)`: \
+        like `tap-hold`, but also activates the hold action when another key is pressed.",
+    ),
+    (
+        &[TAP_HOLD_RELEASE, TAP_HOLD_RELEASE_A],
+        "`(tap-hold-release $tap-repress-timeout $hold-timeout $tap-action $hold-action)`: \
+        like `tap-hold`, but also activates the hold action when another key is pressed \
+        and released.",
+    ),
+    (
+        &[
+            TAP_HOLD_PRESS_TIMEOUT,
+            TAP_HOLD_PRESS_TIMEOUT_A,
+            TAP_HOLD_RELEASE_TIMEOUT,
+            TAP_HOLD_RELEASE_TIMEOUT_A,
+        ],
+        "Like `tap-hold-press`/`tap-hold-release`, \
+        with an extra parameter for the action to do when the hold timeout expires.",
+    ),
+    (
+        &[
+            TAP_HOLD_RELEASE_KEYS,
+            TAP_HOLD_RELEASE_KEYS_A,
+            TAP_HOLD_RELEASE_KEYS_TAP_RELEASE,
+        ],
+        "Like `tap-hold-release`, \
+        with an extra parameter listing keys that activate the tap action early when pressed.",
+    ),
+    (
+        &[TAP_HOLD_EXCEPT_KEYS, TAP_HOLD_EXCEPT_KEYS_A],
+        "Like `tap-hold`, \
+        with an extra parameter listing keys that activate the tap action when pressed.",
+    ),
+    (
+        &[TAP_HOLD_TAP_KEYS, TAP_HOLD_TAP_KEYS_A, TAP_HOLD_KEYS],
+        "Like `tap-hold`, with extra parameters listing keys that resolve the action.",
+    ),
+    (
+        &[TAP_HOLD_ORDER],
+        "Tap-hold variant that decides based on the release order of overlapping keys.",
+    ),
+    (
+        &[TAP_HOLD_OPPOSITE_HAND, TAP_HOLD_OPPOSITE_HAND_RELEASE],
+        "Tap-hold variant that activates the hold action when a key on the opposite hand, \
+        as defined by `defhands`, is pressed.",
+    ),
+    (
+        &[MULTI],
+        "`(multi $action1 $action2 ...)`: do all of the actions at the same time.",
+    ),
+    (
+        &[MACRO, MACRO_RELEASE_CANCEL, MACRO_RELEASE_CANCEL_A],
+        "`(macro $item1 $item2 ...)`: type a sequence of keys, with optional delays \
+        in milliseconds between them.",
+    ),
+    (
+        &[
+            MACRO_REPEAT,
+            MACRO_REPEAT_A,
+            MACRO_REPEAT_RELEASE_CANCEL,
+            MACRO_REPEAT_RELEASE_CANCEL_A,
+        ],
+        "`(macro-repeat $item1 $item2 ...)`: repeat the macro while the key is held.",
+    ),
+    (
+        &[
+            MACRO_CANCEL_ON_NEXT_PRESS,
+            MACRO_REPEAT_CANCEL_ON_NEXT_PRESS,
+            MACRO_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
+            MACRO_REPEAT_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
+        ],
+        "Macro variant that is cancelled when another key is pressed.",
+    ),
+    (
+        &[UNICODE, SYM],
+        "`(unicode $char)`: type a unicode character.",
+    ),
+    (
+        &[
+            ONE_SHOT,
+            ONE_SHOT_PRESS,
+            ONE_SHOT_PRESS_A,
+            ONE_SHOT_RELEASE,
+            ONE_SHOT_RELEASE_A,
+            ONE_SHOT_PRESS_PCANCEL,
+            ONE_SHOT_PRESS_PCANCEL_A,
+            ONE_SHOT_RELEASE_PCANCEL,
+            ONE_SHOT_RELEASE_PCANCEL_A,
+        ],
+        "`(one-shot $timeout $action)`: keep the action active until the next key press \
+        or until the timeout expires.",
+    ),
+    (
+        &[ONE_SHOT_PAUSE_PROCESSING],
+        "`(one-shot-pause-processing $ms)`: pause one-shot processing for a duration.",
+    ),
+    (
+        &[TAP_DANCE, TAP_DANCE_EAGER],
+        "`(tap-dance $timeout $action-list)`: do the Nth action of the list \
+        when the key is tapped N times.",
+    ),
+    (
+        &[TAP_DANCE_HOLD],
+        "`(tap-dance-hold $timeout $hold-timeout (($tap1 $hold1) ...))`: like `tap-dance` \
+        with a tap and a hold action for each tap count.",
+    ),
+    (
+        &[CHORD],
+        "`(chord $group $key)`: a key that is part of a `defchords` group.",
+    ),
+    (
+        &[RELEASE_KEY, RELEASE_KEY_A],
+        "`(release-key $key)`: release a held key.",
+    ),
+    (
+        &[RELEASE_LAYER, RELEASE_LAYER_A],
+        "`(release-layer $layer)`: release a held layer.",
+    ),
+    (
+        &[
+            ON_PRESS_FAKEKEY,
+            ON_PRESS_FAKEKEY_A,
+            ON_RELEASE_FAKEKEY,
+            ON_RELEASE_FAKEKEY_A,
+            ON_PRESS,
+            ON_PRESS_A,
+            ON_RELEASE,
+            ON_RELEASE_A,
+        ],
+        "`(on-press $action $virtual-key)`: act on a virtual key \
+        (press, release, tap or toggle) when the key is pressed or released.",
+    ),
+    (
+        &[
+            ON_PRESS_FAKEKEY_DELAY,
+            ON_PRESS_FAKEKEY_DELAY_A,
+            ON_RELEASE_FAKEKEY_DELAY,
+            ON_RELEASE_FAKEKEY_DELAY_A,
+            ON_PRESS_DELAY,
+            ON_RELEASE_DELAY,
+        ],
+        "Delay processing by a number of milliseconds when the key is pressed or released.",
+    ),
+    (
+        &[ON_IDLE_FAKEKEY, ON_IDLE, ON_PHYSICAL_IDLE],
+        "`(on-idle $ms $action $virtual-key)`: act on a virtual key after no keys \
+        have been pressed for a duration.",
+    ),
+    (
+        &[HOLD_FOR_DURATION],
+        "`(hold-for-duration $ms $virtual-key)`: press a virtual key and release it \
+        after a duration.",
+    ),
+    (
+        &[
+            MWHEEL_UP,
+            MWHEEL_UP_A,
+            MWHEEL_DOWN,
+            MWHEEL_DOWN_A,
+            MWHEEL_LEFT,
+            MWHEEL_LEFT_A,
+            MWHEEL_RIGHT,
+            MWHEEL_RIGHT_A,
+            MWHEEL_ACCEL_UP,
+            MWHEEL_ACCEL_DOWN,
+            MWHEEL_ACCEL_LEFT,
+            MWHEEL_ACCEL_RIGHT,
+        ],
+        "`(mwheel-up $interval $distance)`: scroll the mouse wheel while the key is held.",
+    ),
+    (
+        &[
+            MOVEMOUSE_UP,
+            MOVEMOUSE_UP_A,
+            MOVEMOUSE_DOWN,
+            MOVEMOUSE_DOWN_A,
+            MOVEMOUSE_LEFT,
+            MOVEMOUSE_LEFT_A,
+            MOVEMOUSE_RIGHT,
+            MOVEMOUSE_RIGHT_A,
+        ],
+        "`(movemouse-up $interval $distance)`: move the mouse cursor while the key is held.",
+    ),
+    (
+        &[
+            MOVEMOUSE_ACCEL_UP,
+            MOVEMOUSE_ACCEL_UP_A,
+            MOVEMOUSE_ACCEL_DOWN,
+            MOVEMOUSE_ACCEL_DOWN_A,
+            MOVEMOUSE_ACCEL_LEFT,
+            MOVEMOUSE_ACCEL_LEFT_A,
+            MOVEMOUSE_ACCEL_RIGHT,
+            MOVEMOUSE_ACCEL_RIGHT_A,
+        ],
+        "`(movemouse-accel-up $interval $accel-time $min-distance $max-distance)`: \
+        move the mouse cursor with acceleration while the key is held.",
+    ),
+    (
+        &[MOVEMOUSE_SPEED, MOVEMOUSE_SPEED_A],
+        "`(movemouse-speed $percentage)`: scale mouse movement speed while the key is held.",
+    ),
+    (
+        &[SETMOUSE, SETMOUSE_A],
+        "`(setmouse $x $y)`: move the mouse cursor to an absolute position.",
+    ),
+    (
+        &[DYNAMIC_MACRO_RECORD, DYNAMIC_MACRO_RECORD_STOP_TRUNCATE],
+        "`(dynamic-macro-record $id)`: start recording a dynamic macro.",
+    ),
+    (
+        &[DYNAMIC_MACRO_PLAY],
+        "`(dynamic-macro-play $id)`: play back a recorded dynamic macro.",
+    ),
+    (
+        &[ARBITRARY_CODE],
+        "`(arbitrary-code $code)`: send an arbitrary key code.",
+    ),
+    (
+        &[CMD, CMD_LOG],
+        "`(cmd $program $args...)`: run a program. Requires `danger-enable-cmd`.",
+    ),
+    (
+        &[CMD_OUTPUT_KEYS],
+        "`(cmd-output-keys $program $args...)`: run a program and type its output \
+        as a macro. Requires `danger-enable-cmd`.",
+    ),
+    (
+        &[PUSH_MESSAGE],
+        "`(push-msg $message)`: send a message to connected TCP clients.",
+    ),
+    (
+        &[FORK],
+        "`(fork $left-action $right-action $right-trigger-keys)`: \
+        do the right action if any of the trigger keys are held, otherwise the left action.",
+    ),
+    (
+        &[
+            CAPS_WORD,
+            CAPS_WORD_A,
+            CAPS_WORD_TOGGLE,
+            CAPS_WORD_TOGGLE_A,
+            CAPS_WORD_CUSTOM,
+            CAPS_WORD_CUSTOM_A,
+            CAPS_WORD_CUSTOM_TOGGLE,
+            CAPS_WORD_CUSTOM_TOGGLE_A,
+        ],
+        "`(caps-word $timeout)`: shift letters until a non-word key is pressed \
+        or the timeout expires.",
+    ),
+    (
+        &[
+            CASE_WORD,
+            CASE_WORD_TOGGLE,
+            CASE_WORD_CUSTOM,
+            CASE_WORD_CUSTOM_TOGGLE,
+        ],
+        "`(case-word $timeout $mode)`: type a word in snake_case, kebab-case, camelCase \
+        and similar, with space as the separator.",
+    ),
+    (
+        &[LAYER_WORD],
+        "`(layer-word $layer $timeout $non-terminal-keys)`: activate a layer until a key \
+        not in the list is pressed or the timeout expires.",
+    ),
+    (
+        &[TYPE_TEXT],
+        "`(type-text $text)`: type text with the OS layout from `defoslayout`, \
+        falling back to `unicode`.",
+    ),
+    (
+        &[SWITCH],
+        "`(switch $cond1 $action1 break|fallthrough ...)`: \
+        do the actions whose conditions are satisfied.",
+    ),
+    (
+        &[SEQUENCE, SEQUENCE_NOERASE],
+        "`(sequence $timeout $input-mode)`: start a key sequence defined in `defseq`.",
+    ),
+    (
+        &[UNMOD, UNSHIFT, UNSHIFT_A],
+        "`(unmod $key1 $key2 ...)`: press keys with modifiers temporarily released.",
+    ),
+    (
+        &[LIVE_RELOAD_NUM, LIVE_RELOAD_FILE],
+        "Live reload a specific configuration file, by index or by path.",
+    ),
+    (
+        &[
+            CLIPBOARD_SET,
+            CLIPBOARD_CMD_SET,
+            CLIPBOARD_SAVE,
+            CLIPBOARD_RESTORE,
+            CLIPBOARD_SAVE_SET,
+            CLIPBOARD_SAVE_CMD_SET,
+            CLIPBOARD_SAVE_SWAP,
+        ],
+        "Set, save or restore the contents of the clipboard.",
+    ),
+    (
+        &[STATE_SET, STATE_TOGGLE, STATE_INC, STATE_DEC, STATE_RESET],
+        "Change a state variable declared in `defstate`.",
+    ),
+];
+
+/// Markdown documentation for the list action named `name`, if it is one.
+pub(crate) fn action_doc(name: &str) -> Option<String> {
+    if !is_list_action(name) {
+        return None;
+    }
+    let desc = ACTION_DOCS
+        .iter()
+        .find(|(names, _)| names.contains(&name))
+        .map(|(_, desc)| *desc)
+        .unwrap_or("List action.");
+    Some(format!(
+        "{desc}\n\nSee the [configuration guide]({CONFIG_GUIDE})."
+    ))
+}
+
+#[test]
+fn all_list_actions_are_documented() {
+    for ac in LIST_ACTIONS {
+        assert!(
+            ACTION_DOCS.iter().any(|(names, _)| names.contains(ac)),
+            "{ac} has no hover documentation"
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Language server for kanata configuration files, speaking LSP over stdio.
+//!
+//! The analysis is done by `kanata-parser` with its `lsp` feature enabled, which collects the
+//! definition and reference locations of aliases, variables, virtual keys, layers and templates
+//! while parsing.
+
+use anyhow::Result;
+use lsp_server::{Connection, ExtractError, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, GotoDefinition, HoverRequest, PrepareRenameRequest, References, Rename,
+    Request as _,
+};
+use lsp_types::*;
+use simplelog::*;
+
+type HashMap<K, V> = std::collections::HashMap<K, V>;
+
+mod completion;
+mod document;
+mod hover;
+mod symbols;
+
+use document::Document;
+
+fn log_init() {
+    // stdout is used by the protocol, so logs must go to stderr.
+    let _ = CombinedLogger::init(vec![TermLogger::new(
+        LevelFilter::Info,
+        ConfigBuilder::new().build(),
+        TerminalMode::Stderr,
+        ColorChoice::Never,
+    )]);
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["(".into(), "@".into()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn main() -> Result<()> {
+    log_init();
+    log::info!("kanata-lsp v{} starting", env!("CARGO_PKG_VERSION"));
+    let (connection, io_threads) = Connection::stdio();
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::default().run(&connection)?;
+    // The writer thread exits only once the connection is dropped.
+    drop(connection);
+    io_threads.join()?;
+    log::info!("kanata-lsp exiting");
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    fn run(&mut self, connection: &Connection) -> Result<()> {
+        for msg in &connection.receiver {
+            match msg {
+                Message::Request(req) => {
+                    if connection.handle_shutdown(&req)? {
+                        return Ok(());
+                    }
+                    let resp = self.handle_request(req);
+                    connection.sender.send(Message::Response(resp))?;
+                }
+                Message::Notification(not) => {
+                    for not in self.handle_notification(not) {
+                        connection.sender.send(Message::Notification(not))?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, req: Request) -> Response {
+        let id = req.id.clone();
+        let result = match req.method.as_str() {
+            GotoDefinition::METHOD => extract::<GotoDefinition>(req).map(|(id, params)| {
+                let pos = params.text_document_position_params;
+                let res = self
+                    .document(&pos.text_document.uri)
+                    .and_then(|doc| doc.definition(pos.position))
+                    .map(GotoDefinitionResponse::Scalar);
+                (id, serde_json::to_value(res))
+            }),
+            References::METHOD => extract::<References>(req).map(|(id, params)| {
+                let pos = params.text_document_position;
+                let res = self
+                    .document(&pos.text_document.uri)
+                    .map(|doc| doc.references(pos.position, params.context.include_declaration));
+                (id, serde_json::to_value(res))
+            }),
+            PrepareRenameRequest::METHOD => {
+                extract::<PrepareRenameRequest>(req).map(|(id, params)| {
+                    let res = self
+                        .document(&params.text_document.uri)
+                        .and_then(|doc| doc.prepare_rename(params.position))
+                        .map(PrepareRenameResponse::Range);
+                    (id, serde_json::to_value(res))
+                })
+            }
+            Rename::METHOD => extract::<Rename>(req).map(|(id, params)| {
+                let pos = params.text_document_position;
+                let res = self
+                    .document(&pos.text_document.uri)
+                    .and_then(|doc| doc.rename(pos.position, &params.new_name));
+                (id, serde_json::to_value(res))
+            }),
+            HoverRequest::METHOD => extract::<HoverRequest>(req).map(|(id, params)| {
+                let pos = params.text_document_position_params;
+                let res = self
+                    .document(&pos.text_document.uri)
+                    .and_then(|doc| doc.hover(pos.position));
+                (id, serde_json::to_value(res))
+            }),
+            Completion::METHOD => extract::<Completion>(req).map(|(id, params)| {
+                let pos = params.text_document_position;
+                let res = self
+                    .document(&pos.text_document.uri)
+                    .map(|doc| CompletionResponse::Array(doc.completion(pos.position)));
+                (id, serde_json::to_value(res))
+            }),
+            _ => Err(format!("unsupported request: {}", req.method)),
+        };
+        match result {
+            Ok((id, Ok(value))) => Response::new_ok(id, value),
+            Ok((id, Err(e))) => Response::new_err(
+                id,
+                lsp_server::ErrorCode::InternalError as i32,
+                e.to_string(),
+            ),
+            Err(e) => Response::new_err(id, lsp_server::ErrorCode::MethodNotFound as i32, e),
+        }
+    }
+
+    /// Returns the notifications to send back to the client.
+    fn handle_notification(&mut self, not: Notification) -> Vec<Notification> {
+        let uri = match not.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) =
+                    not.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                let uri = params.text_document.uri;
+                let doc = Document::new(uri.clone(), params.text_document.text);
+                self.documents.insert(uri.clone(), doc);
+                uri
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) =
+                    not.extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                let uri = params.text_document.uri;
+                // Only full document sync is advertised, so the last change is the whole text.
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return vec![];
+                };
+                match self.documents.get_mut(&uri) {
+                    Some(doc) => doc.update(change.text),
+                    None => {
+                        let doc = Document::new(uri.clone(), change.text);
+                        self.documents.insert(uri.clone(), doc);
+                    }
+                }
+                uri
+            }
+            DidSaveTextDocument::METHOD => {
+                let Ok(params) =
+                    not.extract::<DidSaveTextDocumentParams>(DidSaveTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                // Included files may have changed on disk, so check again.
+                let uri = params.text_document.uri;
+                let Some(doc) = self.documents.get_mut(&uri) else {
+                    return vec![];
+                };
+                doc.analyze();
+                uri
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) =
+                    not.extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                else {
+                    return vec![];
+                };
+                let Some(doc) = self.documents.remove(&params.text_document.uri) else {
+                    return vec![];
+                };
+                // Clear everything the document reported so it does not linger in the client.
+                let cleared = doc
+                    .diagnostics()
+                    .into_iter()
+                    .map(|(uri, _)| (uri, vec![]))
+                    .collect();
+                return publish_diagnostics(cleared);
+            }
+            _ => return vec![],
+        };
+        let Some(doc) = self.documents.get(&uri) else {
+            return vec![];
+        };
+        publish_diagnostics(doc.diagnostics())
+    }
+
+    fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+}
+
+fn publish_diagnostics(diagnostics: Vec<(Url, Vec<Diagnostic>)>) -> Vec<Notification> {
+    diagnostics
+        .into_iter()
+        .map(|(uri, diagnostics)| {
+            Notification::new(
+                PublishDiagnostics::METHOD.to_string(),
+                PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                },
+            )
+        })
+        .collect()
+}
+
+fn extract<R>(req: Request) -> std::result::Result<(RequestId, R::Params), String>
+where
+    R: lsp_types::request::Request,
+    R::Params: serde::de::DeserializeOwned,
+{
+    req.extract(R::METHOD).map_err(|e| match e {
+        ExtractError::MethodMismatch(req) => format!("unexpected method: {}", req.method),
+        ExtractError::JsonError { method, error } => format!("invalid {method} params: {error}"),
+    })
+}
+
+#[test]
+fn close_clears_diagnostics() {
+    let uri = Url::from_file_path(std::env::temp_dir().join("kanata-lsp-close.kbd")).unwrap();
+    let mut server = Server::default();
+    let opened = server.handle_notification(Notification::new(
+        DidOpenTextDocument::METHOD.to_string(),
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "kanata".into(),
+                0,
+                "(defsrc a".into(),
+            ),
+        },
+    ));
+    let reported = |nots: &[Notification]| {
+        nots.iter()
+            .map(|n| {
+                let params: PublishDiagnosticsParams =
+                    serde_json::from_value(n.params.clone()).unwrap();
+                (params.uri, params.diagnostics.len())
+            })
+            .collect::<Vec<_>>()
+    };
+    assert!(reported(&opened).iter().any(|(u, n)| u == &uri && *n > 0));
+    let closed = server.handle_notification(Notification::new(
+        DidCloseTextDocument::METHOD.to_string(),
+        DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        },
+    ));
+    assert_eq!(reported(&closed), vec![(uri, 0)]);
+    assert!(server.documents.is_empty());
+}
@@ -0,0 +1,127 @@
+//! Lookup of the symbols recorded in the parser's LSP hints.
+
+use kanata_parser::cfg::sexpr::Span;
+use kanata_parser::lsp_hints::{LspHints, ReferencesMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SymbolKind {
+    Alias,
+    Variable,
+    VirtualKey,
+    Layer,
+    Template,
+    Include,
+}
+
+impl SymbolKind {
+    const ALL: [SymbolKind; 6] = [
+        SymbolKind::Alias,
+        SymbolKind::Variable,
+        SymbolKind::VirtualKey,
+        SymbolKind::Layer,
+        SymbolKind::Template,
+        SymbolKind::Include,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SymbolKind::Alias => "alias",
+            SymbolKind::Variable => "variable",
+            SymbolKind::VirtualKey => "virtual key",
+            SymbolKind::Layer => "layer",
+            SymbolKind::Template => "template",
+            SymbolKind::Include => "include",
+        }
+    }
+
+    /// Renaming is supported for the symbols where every use is known to the parser.
+    pub(crate) fn is_renamable(self) -> bool {
+        matches!(self, SymbolKind::Alias | SymbolKind::Layer)
+    }
+
+    /// Included files have no definition span; the definition is the file itself.
+    pub(crate) fn definition<'a>(self, hints: &'a LspHints, name: &str) -> Option<&'a Span> {
+        let defs = &hints.definition_locations;
+        match self {
+            SymbolKind::Alias => defs.alias.get(name),
+            SymbolKind::Variable => defs.variable.get(name),
+            SymbolKind::VirtualKey => defs.virtual_key.get(name),
+            SymbolKind::Layer => defs.layer.get(name),
+            SymbolKind::Template => defs.template.get(name),
+            SymbolKind::Include => None,
+        }
+    }
+
+    pub(crate) fn references<'a>(self, hints: &'a LspHints, name: &str) -> &'a [Span] {
+        self.references_map(hints)
+            .0
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn references_map(self, hints: &LspHints) -> &ReferencesMap {
+        let refs = &hints.reference_locations;
+        match self {
+            SymbolKind::Alias => &refs.alias,
+            SymbolKind::Variable => &refs.variable,
+            SymbolKind::VirtualKey => &refs.virtual_key,
+            SymbolKind::Layer => &refs.layer,
+            SymbolKind::Template => &refs.template,
+            SymbolKind::Include => &refs.include,
+        }
+    }
+
+    fn definitions(self, hints: &LspHints) -> Box<dyn Iterator<Item = (&String, &Span)> + '_> {
+        let defs = &hints.definition_locations;
+        match self {
+            SymbolKind::Alias => Box::new(defs.alias.iter()),
+            SymbolKind::Variable => Box::new(defs.variable.iter()),
+            SymbolKind::VirtualKey => Box::new(defs.virtual_key.iter()),
+            SymbolKind::Layer => Box::new(defs.layer.iter()),
+            SymbolKind::Template => Box::new(defs.template.iter()),
+            SymbolKind::Include => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Symbol<'a> {
+    pub(crate) kind: SymbolKind,
+    pub(crate) name: &'a str,
+    pub(crate) span: &'a Span,
+}
+
+/// Find the symbol, either a definition or a reference, whose span in the file `file_name`
+/// contains the byte offset `offset`.
+pub(crate) fn symbol_at<'a>(
+    hints: &'a LspHints,
+    file_name: &str,
+    offset: usize,
+) -> Option<Symbol<'a>> {
+    let contains = |span: &Span| {
+        span.start() <= offset && offset <= span.end() && &*span.file_name == file_name
+    };
+    SymbolKind::ALL.into_iter().find_map(|kind| {
+        let def = kind
+            .definitions(hints)
+            .find(|(_, span)| contains(span))
+            .map(|(name, span)| Symbol { kind, name, span });
+        def.or_else(|| {
+            kind.references_map(hints)
+                .0
+                .iter()
+                .find_map(|(name, spans)| {
+                    spans
+                        .iter()
+                        .find(|span| contains(span))
+                        .map(|span| Symbol { kind, name, span })
+                })
+        })
+    })
+}
+
+/// Names of all symbols of `kind` with a definition.
+pub(crate) fn defined_names(hints: &LspHints, kind: SymbolKind) -> impl Iterator<Item = &str> {
+    kind.definitions(hints).map(|(name, _)| name.as_str())
+}
@@ -1,6 +1,65 @@
 use super::*;
 
-use crate::bail;
+use crate::{anyhow_expr, bail, bail_expr};
+
+const DEFAULT_KEYS_TO_CAPITALIZE: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Minus,
+];
+
+const DEFAULT_KEYS_NONTERMINAL: &[KeyCode] = &[
+    KeyCode::Kb0,
+    KeyCode::Kb1,
+    KeyCode::Kb2,
+    KeyCode::Kb3,
+    KeyCode::Kb4,
+    KeyCode::Kb5,
+    KeyCode::Kb6,
+    KeyCode::Kb7,
+    KeyCode::Kb8,
+    KeyCode::Kb9,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::BSpace,
+    KeyCode::Delete,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+];
 
 pub(crate) fn parse_caps_word(
     ac_params: &[SExpr],
@@ -15,64 +74,11 @@ pub(crate) fn parse_caps_word(
     custom(
         CustomAction::CapsWord(CapsWordCfg {
             repress_behaviour,
-            keys_to_capitalize: &[
-                KeyCode::A,
-                KeyCode::B,
-                KeyCode::C,
-                KeyCode::D,
-                KeyCode::E,
-                KeyCode::F,
-                KeyCode::G,
-                KeyCode::H,
-                KeyCode::I,
-                KeyCode::J,
-                KeyCode::K,
-                KeyCode::L,
-                KeyCode::M,
-                KeyCode::N,
-                KeyCode::O,
-                KeyCode::P,
-                KeyCode::Q,
-                KeyCode::R,
-                KeyCode::S,
-                KeyCode::T,
-                KeyCode::U,
-                KeyCode::V,
-                KeyCode::W,
-                KeyCode::X,
-                KeyCode::Y,
-                KeyCode::Z,
-                KeyCode::Minus,
-            ],
-            keys_nonterminal: &[
-                KeyCode::Kb0,
-                KeyCode::Kb1,
-                KeyCode::Kb2,
-                KeyCode::Kb3,
-                KeyCode::Kb4,
-                KeyCode::Kb5,
-                KeyCode::Kb6,
-                KeyCode::Kb7,
-                KeyCode::Kb8,
-                KeyCode::Kb9,
-                KeyCode::Kp0,
-                KeyCode::Kp1,
-                KeyCode::Kp2,
-                KeyCode::Kp3,
-                KeyCode::Kp4,
-                KeyCode::Kp5,
-                KeyCode::Kp6,
-                KeyCode::Kp7,
-                KeyCode::Kp8,
-                KeyCode::Kp9,
-                KeyCode::BSpace,
-                KeyCode::Delete,
-                KeyCode::Up,
-                KeyCode::Down,
-                KeyCode::Left,
-                KeyCode::Right,
-            ],
+            keys_to_capitalize: DEFAULT_KEYS_TO_CAPITALIZE,
+            keys_nonterminal: DEFAULT_KEYS_NONTERMINAL,
             timeout,
+            case: CapsWordCase::Upper,
+            separator: None,
         }),
         &s.a,
     )
@@ -104,6 +110,138 @@ pub(crate) fn parse_caps_word_custom(
                     .collect(),
             ),
             timeout,
+            case: CapsWordCase::Upper,
+            separator: None,
+        }),
+        &s.a,
+    )
+}
+
+pub(crate) fn parse_case_word(
+    ac_params: &[SExpr],
+    repress_behaviour: CapsWordRepressBehaviour,
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_STR: &str = "case-word expects 2 params: <timeout> <mode>";
+    if ac_params.len() != 2 {
+        bail!("{ERR_STR}\nFound {} params instead of 2", ac_params.len());
+    }
+    let timeout = parse_non_zero_u16(&ac_params[0], s, "timeout")?;
+    let (case, separator) = parse_case_word_mode(&ac_params[1], s)?;
+    custom(
+        CustomAction::CapsWord(CapsWordCfg {
+            repress_behaviour,
+            keys_to_capitalize: DEFAULT_KEYS_TO_CAPITALIZE,
+            keys_nonterminal: DEFAULT_KEYS_NONTERMINAL,
+            timeout,
+            case,
+            separator: Some(separator),
+        }),
+        &s.a,
+    )
+}
+
+pub(crate) fn parse_case_word_custom(
+    ac_params: &[SExpr],
+    repress_behaviour: CapsWordRepressBehaviour,
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_STR: &str =
+        "case-word-custom expects 4 params: <timeout> <mode> <word-keys> <extra-non-terminal-keys>";
+    if ac_params.len() != 4 {
+        bail!("{ERR_STR}\nFound {} params instead of 4", ac_params.len());
+    }
+    let timeout = parse_non_zero_u16(&ac_params[0], s, "timeout")?;
+    let (case, separator) = parse_case_word_mode(&ac_params[1], s)?;
+    custom(
+        CustomAction::CapsWord(CapsWordCfg {
+            repress_behaviour,
+            keys_to_capitalize: s.a.sref_vec(
+                parse_key_list(&ac_params[2], s, "word-keys")?
+                    .into_iter()
+                    .map(KeyCode::from)
+                    .collect(),
+            ),
+            keys_nonterminal: s.a.sref_vec(
+                parse_key_list(&ac_params[3], s, "extra-non-terminal-keys")?
+                    .into_iter()
+                    .map(KeyCode::from)
+                    .collect(),
+            ),
+            timeout,
+            case,
+            separator: Some(separator),
+        }),
+        &s.a,
+    )
+}
+
+/// Parse the mode of `case-word` into the case and the keys that replace space.
+fn parse_case_word_mode(
+    expr: &SExpr,
+    s: &ParserState,
+) -> Result<(CapsWordCase, &'static [KeyCode])> {
+    const ERR_STR: &str = "mode must be one of: snake, screaming-snake, kebab, screaming-kebab, \
+        camel, pascal, (separator $key), (screaming-separator $key)";
+    const UNDERSCORE: &[KeyCode] = &[KeyCode::LShift, KeyCode::Minus];
+    const HYPHEN: &[KeyCode] = &[KeyCode::Minus];
+    if let Some(mode) = expr.atom(s.vars()) {
+        return Ok(match mode {
+            "snake" => (CapsWordCase::Lower, UNDERSCORE),
+            "screaming-snake" => (CapsWordCase::Upper, UNDERSCORE),
+            "kebab" => (CapsWordCase::Lower, HYPHEN),
+            "screaming-kebab" => (CapsWordCase::Upper, HYPHEN),
+            "camel" => (CapsWordCase::Camel, &[]),
+            "pascal" => (CapsWordCase::Pascal, &[]),
+            _ => bail_expr!(expr, "{ERR_STR}"),
+        });
+    }
+    let Some((mode, params)) = parse_list_with_first_atom(expr, s) else {
+        bail_expr!(expr, "{ERR_STR}");
+    };
+    let case = match mode {
+        "separator" => CapsWordCase::Lower,
+        "screaming-separator" => CapsWordCase::Upper,
+        _ => bail_expr!(expr, "{ERR_STR}"),
+    };
+    let [separator] = params else {
+        bail_expr!(expr, "{mode} expects 1 param: <key>");
+    };
+    let key_name = separator
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(separator, "expected a key name, found list"))?;
+    let (mut keys, unmodded) =
+        parse_mod_prefix(key_name).map_err(|e| anyhow_expr!(separator, "{}", e.msg))?;
+    if keys.contains(&KEY_OVERLAP) {
+        bail_expr!(separator, "O- is not allowed in a separator");
+    }
+    let key = str_to_oscode(unmodded)
+        .ok_or_else(|| anyhow_expr!(separator, "unknown key '{unmodded}'"))?;
+    keys.push(key.into());
+    Ok((case, s.a.sref_vec(keys)))
+}
+
+pub(crate) fn parse_layer_word(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_STR: &str = "layer-word expects 3 params: <layer-name> <timeout> <non-terminal-keys>";
+    if ac_params.len() != 3 {
+        bail!("{ERR_STR}\nFound {} params instead of 3", ac_params.len());
+    }
+    let layer = layer_idx(&ac_params[..1], &s.layer_idxs, s)?;
+    set_layer_change_lsp_hint(&ac_params[0], &mut s.lsp_hints.borrow_mut());
+    let timeout = parse_non_zero_u16(&ac_params[1], s, "timeout")?;
+    custom(
+        CustomAction::LayerWord(LayerWordCfg {
+            layer: layer as u16,
+            keys_nonterminal: s.a.sref_vec(
+                parse_key_list(&ac_params[2], s, "non-terminal-keys")?
+                    .into_iter()
+                    .map(KeyCode::from)
+                    .collect(),
+            ),
+            timeout,
         }),
         &s.a,
     )
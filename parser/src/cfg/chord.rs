@@ -12,9 +12,10 @@ pub(crate) fn parse_defchordv2(
     s: &ParserState,
 ) -> Result<ChordsForKeys<'static, KanataCustom>> {
     if exprs[0].atom(None).expect("should be atom") == "defchordsv2-experimental" {
-        log::warn!(
+        s.warn(
+            Some(exprs[0].span()),
             "You should replace defchordsv2-experimental with defchordsv2.\n\
-             Using -experimental will be invalid in the future."
+             Using -experimental will be invalid in the future.",
         );
     }
 
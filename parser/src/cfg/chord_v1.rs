@@ -192,6 +192,7 @@ pub(crate) fn find_chords_coords(
         | Action::Trans
         | Action::Src
         | Action::Repeat
+        | Action::LayerLock
         | Action::KeyCode(_)
         | Action::MultipleKeyCodes(_)
         | Action::Layer(_)
@@ -247,6 +248,7 @@ pub(crate) fn fill_chords(
         Action::NoOp
         | Action::Trans
         | Action::Repeat
+        | Action::LayerLock
         | Action::Src
         | Action::KeyCode(_)
         | Action::MultipleKeyCodes(_)
@@ -0,0 +1,64 @@
+//! Counterparts of keys for `alt-repeat`.
+//!
+//! Syntax is pairs of keys, where the second key is output by `alt-repeat` after the first key
+//! was pressed. Keys may have modifier prefixes:
+//!
+//! `(defaltrepeat S-9 S-0 C-z C-y up down ...)`
+
+use super::sexpr::*;
+use super::*;
+use crate::sequences::*;
+use crate::{anyhow_expr, bail_expr};
+
+pub(crate) const DEFALTREPEAT: &str = "defaltrepeat";
+
+/// The output of `alt-repeat` for a key pressed with some modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AltRepeat {
+    /// The key code of the last pressed key, combined with the mod mask of the modifiers held
+    /// with it, in the same form as in sequences.
+    pub from: u16,
+    /// Mod mask of the modifiers to press together with the output.
+    pub mods: u16,
+    /// The key to output.
+    pub to: OsCode,
+}
+
+/// Parse a `defaltrepeat` block.
+pub(super) fn parse_defaltrepeat(expr: &[SExpr], s: &ParserState) -> Result<Box<[AltRepeat]>> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFALTREPEAT)?;
+    let mut alt_repeats: Vec<AltRepeat> = vec![];
+    while let Some(from_expr) = subexprs.next() {
+        let Some(to_expr) = subexprs.next() else {
+            bail_expr!(
+                from_expr,
+                "key must be followed by the key that alt-repeat outputs"
+            );
+        };
+        let (from_mods, from) = alt_repeat_key(from_expr, s)?;
+        let from = u16::from(from) | from_mods;
+        if alt_repeats.iter().any(|r| r.from == from) {
+            bail_expr!(from_expr, "key already has an alt-repeat output");
+        }
+        let (mods, to) = alt_repeat_key(to_expr, s)?;
+        alt_repeats.push(AltRepeat { from, mods, to });
+    }
+    Ok(alt_repeats.into_boxed_slice())
+}
+
+fn alt_repeat_key(expr: &SExpr, s: &ParserState) -> Result<(u16, OsCode)> {
+    let key_name = expr
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(expr, "expected a key name, found list"))?;
+    let (mods, unmodded) =
+        parse_mod_prefix(key_name).map_err(|e| anyhow_expr!(expr, "{}", e.msg))?;
+    if mods.contains(&KEY_OVERLAP) {
+        bail_expr!(expr, "O- is not allowed in defaltrepeat");
+    }
+    let key =
+        str_to_oscode(unmodded).ok_or_else(|| anyhow_expr!(expr, "unknown key '{unmodded}'"))?;
+    let mods = mods
+        .into_iter()
+        .fold(0, |mask, m| mask | mod_mask_for_keycode(m));
+    Ok((mods, key))
+}
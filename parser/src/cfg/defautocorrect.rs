@@ -0,0 +1,115 @@
+//! Autocorrect: a typo followed by a boundary key is replaced by its correction.
+//!
+//! Syntax is a file name followed by pairs of option names and values:
+//!
+//! `(defautocorrect autocorrect.txt boundary-keys (spc ret tab . ,))`
+//!
+//! Each line of the file is `typo -> correction`. Empty lines and lines beginning with `#` are
+//! ignored. Leading and trailing `:` of typos, which mark word boundaries in QMK dictionaries,
+//! are ignored since typos always match whole words.
+
+use super::defexpand::{char_to_oscode, default_boundary_keys, parse_expansion};
+use super::sexpr::*;
+use super::*;
+use crate::trie::GetOrDescendentExistsResult;
+use crate::{anyhow_expr, bail_expr};
+
+pub(crate) const DEFAUTOCORRECT: &str = "defautocorrect";
+
+#[derive(Debug, Clone, Default)]
+pub struct Autocorrect {
+    /// The keys of typos, with the index of their correction in `corrections` as the value.
+    pub typos: Trie<u16>,
+    pub corrections: Box<[Box<[ExpandChar]>]>,
+    /// Keys that end a word and correct a typo typed before them.
+    pub boundary_keys: Box<[OsCode]>,
+}
+
+impl Autocorrect {
+    pub fn is_empty(&self) -> bool {
+        self.corrections.is_empty()
+    }
+}
+
+pub(super) fn parse_defautocorrect(
+    expr: &[SExpr],
+    s: &ParserState,
+    f: &mut FileContentProvider,
+) -> Result<Autocorrect> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFAUTOCORRECT)?;
+    let Some(file_expr) = subexprs.next() else {
+        bail_expr!(&expr[0], "defautocorrect expects a file name");
+    };
+    let file_name = file_expr
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(file_expr, "Filename must be a string, not a list."))?;
+    let mut boundary_keys = None;
+    while let Some(option) = subexprs.next() {
+        let label = option
+            .atom(s.vars())
+            .ok_or_else(|| anyhow_expr!(option, "expected an option name, found a list"))?;
+        let Some(val) = subexprs.next() else {
+            bail_expr!(option, "option {label} must be followed by a value");
+        };
+        match label {
+            "boundary-keys" => {
+                boundary_keys = Some(parse_key_list(val, s, label)?.into_boxed_slice());
+            }
+            _ => bail_expr!(
+                option,
+                "Unknown defautocorrect option {label}. Valid options are: boundary-keys"
+            ),
+        }
+    }
+
+    let content = f
+        .get_file_content(file_name.trim_atom_quotes().as_ref())
+        .map_err(|e| anyhow_expr!(file_expr, "Failed to read file:\n{e}"))?;
+    let mut typos = Trie::new();
+    let mut corrections = vec![];
+    for (line_number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.trim().starts_with('#') {
+            continue;
+        }
+        let line_error = |msg: &str| anyhow_expr!(file_expr, "{msg}:\n{}: {line}", line_number + 1);
+        let Some((typo, correction)) = line.split_once("->") else {
+            return Err(line_error(
+                "Typo and correction are separated by ->, but found no ->",
+            ));
+        };
+        let typo = typo.trim().trim_matches(':');
+        if typo.is_empty() {
+            return Err(line_error("typo must not be empty"));
+        }
+        let typo_keys = typo
+            .chars()
+            .map(|c| {
+                char_to_oscode(c.to_lowercase().next().unwrap_or(c))
+                    .filter(|osc| *osc != OsCode::KEY_SPACE)
+                    .map(u16::from)
+                    .ok_or_else(|| line_error(&format!("Unknown typo key name '{c}'")))
+            })
+            .collect::<Result<Vec<u16>>>()?;
+        if matches!(
+            typos.get_or_descendant_exists(&typo_keys),
+            GetOrDescendentExistsResult::HasValue(_)
+        ) {
+            return Err(line_error("duplicate typo"));
+        }
+        let correction =
+            parse_expansion(correction.trim(), &s.os_layout).map_err(|e| line_error(&e))?;
+        if correction.is_empty() {
+            return Err(line_error("correction must not be empty"));
+        }
+        typos.insert(&typo_keys, corrections.len() as u16);
+        corrections.push(correction);
+    }
+    if corrections.is_empty() {
+        bail_expr!(file_expr, "The file must define at least one typo");
+    }
+    Ok(Autocorrect {
+        typos,
+        corrections: corrections.into_boxed_slice(),
+        boundary_keys: boundary_keys.unwrap_or_else(default_boundary_keys),
+    })
+}
@@ -0,0 +1,161 @@
+//! Auto-shift: letters, numbers and symbols are output shifted when held past a timeout.
+//!
+//! Syntax is pairs of option names and values:
+//!
+//! `(defautoshift timeout 175 numbers-symbols-timeout 200 exclude (q z) ...)`
+
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail_expr};
+
+pub(crate) const DEFAUTOSHIFT: &str = "defautoshift";
+
+const LETTERS: [KeyCode; 26] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+];
+
+const NUMBERS_SYMBOLS: [KeyCode; 22] = [
+    KeyCode::Kb1,
+    KeyCode::Kb2,
+    KeyCode::Kb3,
+    KeyCode::Kb4,
+    KeyCode::Kb5,
+    KeyCode::Kb6,
+    KeyCode::Kb7,
+    KeyCode::Kb8,
+    KeyCode::Kb9,
+    KeyCode::Kb0,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::LBracket,
+    KeyCode::RBracket,
+    KeyCode::Bslash,
+    KeyCode::SColon,
+    KeyCode::Quote,
+    KeyCode::Grave,
+    KeyCode::Comma,
+    KeyCode::Dot,
+    KeyCode::Slash,
+    KeyCode::NonUsBslash,
+];
+
+/// Parse the `defautoshift` block into the replacement hold-tap action of every auto-shifted key.
+pub(super) fn parse_defautoshift(
+    expr: &[SExpr],
+    s: &ParserState,
+) -> Result<AutoShift<'static, KanataCustom>> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFAUTOSHIFT)?;
+    let mut timeout = 175;
+    let mut numbers_symbols_timeout = None;
+    let mut exclude: Vec<KeyCode> = vec![];
+    let mut repeat_after_hold = false;
+    let mut enabled = true;
+    while let Some(option) = subexprs.next() {
+        let label = option
+            .atom(s.vars())
+            .ok_or_else(|| anyhow_expr!(option, "expected an option name, found a list"))?;
+        let Some(val) = subexprs.next() else {
+            bail_expr!(option, "option {label} must be followed by a value");
+        };
+        match label {
+            "timeout" => timeout = parse_non_zero_u16(val, s, label)?,
+            "numbers-symbols-timeout" => {
+                numbers_symbols_timeout = Some(parse_non_zero_u16(val, s, label)?)
+            }
+            "exclude" => {
+                let keys = val.list(s.vars()).ok_or_else(|| {
+                    anyhow_expr!(val, "The value for {label} must be a list of key names")
+                })?;
+                for key_expr in keys {
+                    let key = key_expr
+                        .atom(s.vars())
+                        .and_then(str_to_oscode)
+                        .ok_or_else(|| anyhow_expr!(key_expr, "Expected a known key name."))?;
+                    exclude.push(key.into());
+                }
+            }
+            "repeat-after-hold" => repeat_after_hold = parse_defcfg_val_bool(val, label)?,
+            "enabled" => enabled = parse_defcfg_val_bool(val, label)?,
+            _ => bail_expr!(
+                option,
+                "Unknown defautoshift option {label}. Valid options are: timeout, \
+                 numbers-symbols-timeout, exclude, repeat-after-hold, enabled"
+            ),
+        }
+    }
+    let numbers_symbols_timeout = numbers_symbols_timeout.unwrap_or(timeout);
+
+    let actions = LETTERS
+        .iter()
+        .map(|&kc| (kc, timeout))
+        .chain(
+            NUMBERS_SYMBOLS
+                .iter()
+                .map(|&kc| (kc, numbers_symbols_timeout)),
+        )
+        .filter(|(kc, _)| !exclude.contains(kc))
+        .map(|(kc, timeout)| (kc, auto_shift_action(kc, timeout, repeat_after_hold, s)))
+        .collect();
+    Ok(AutoShift {
+        enabled,
+        actions: s.a.sref_vec(actions),
+    })
+}
+
+/// A key that is tapped when released before `timeout`, and otherwise output with shift, which
+/// stays held for the OS to repeat if `repeat_after_hold` is set.
+fn auto_shift_action(
+    kc: KeyCode,
+    timeout: u16,
+    repeat_after_hold: bool,
+    s: &ParserState,
+) -> &'static KanataAction {
+    let hold = if repeat_after_hold {
+        Action::MultipleKeyCodes(s.a.sref(s.a.sref_vec(vec![KeyCode::LShift, kc])))
+    } else {
+        Action::Sequence {
+            events: s.a.sref(s.a.sref_vec(vec![
+                SequenceEvent::Press(KeyCode::LShift),
+                SequenceEvent::Press(kc),
+                SequenceEvent::Release(kc),
+                SequenceEvent::Release(KeyCode::LShift),
+            ])),
+        }
+    };
+    s.a.sref(Action::HoldTap(s.a.sref(HoldTapAction {
+        config: HoldTapConfig::Default,
+        tap_hold_interval: 0,
+        timeout,
+        tap: Action::KeyCode(kc),
+        hold,
+        timeout_action: hold,
+        on_press_reset_timeout_to: None,
+        require_prior_idle: None,
+        adaptive: None,
+    })))
+}
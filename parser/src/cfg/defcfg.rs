@@ -1,4 +1,5 @@
 use super::HashSet;
+use super::Lint;
 use super::sexpr::SExpr;
 use super::{TrimAtomQuotes, error::*};
 use crate::cfg::check_first_expr;
@@ -157,6 +158,14 @@ pub struct CfgOptions {
     pub trans_resolution_behavior_v2: bool,
     pub chords_v2_min_idle: u16,
     pub tap_hold_require_prior_idle: u16,
+    pub adaptive_tap_hold_file: Option<String>,
+    pub stats: bool,
+    pub stats_file: Option<String>,
+    pub stats_save_interval: u16,
+    pub stats_exclude_letters: bool,
+    pub stats_exclude_bigrams: bool,
+    pub allowed_lints: Vec<Lint>,
+    pub layer_lock_unlock_keys: Vec<OsCode>,
     #[cfg(any(
         all(target_os = "windows", feature = "interception_driver"),
         target_os = "linux",
@@ -205,6 +214,14 @@ impl Default for CfgOptions {
             trans_resolution_behavior_v2: true,
             chords_v2_min_idle: 5,
             tap_hold_require_prior_idle: 0,
+            adaptive_tap_hold_file: None,
+            stats: false,
+            stats_file: None,
+            stats_save_interval: 60,
+            stats_exclude_letters: false,
+            stats_exclude_bigrams: false,
+            allowed_lints: vec![],
+            layer_lock_unlock_keys: vec![],
             #[cfg(any(
                 all(target_os = "windows", feature = "interception_driver"),
                 target_os = "linux",
@@ -232,27 +249,30 @@ impl Default for CfgOptions {
 
 /// Parse configuration entries from an expression starting with defcfg.
 pub fn parse_defcfg(expr: &[SExpr]) -> Result<CfgOptions> {
+    let (cfg, errors, _) = parse_defcfg_recovering(expr);
+    match ParseError::from_errors(errors) {
+        Some(e) => Err(e),
+        None => Ok(cfg),
+    }
+}
+
+/// Parse configuration entries from an expression starting with defcfg, continuing past invalid
+/// options so that all of their errors are returned. Invalid options keep their default values.
+pub(crate) fn parse_defcfg_recovering<'a>(
+    expr: &'a [SExpr],
+) -> (CfgOptions, Vec<ParseError>, Vec<ParseWarning>) {
     let mut seen_keys = HashSet::default();
     let mut cfg = CfgOptions::default();
-    let mut exprs = check_first_expr(expr.iter(), "defcfg")?;
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    let mut exprs = match check_first_expr(expr.iter(), "defcfg") {
+        Ok(exprs) => exprs,
+        Err(e) => return (cfg, vec![e], warnings),
+    };
     let mut is_process_unmapped_keys_defined = false;
-    // Read k-v pairs from the configuration
-    loop {
-        let key = match exprs.next() {
-            Some(k) => k,
-            None => {
-                if !is_process_unmapped_keys_defined {
-                    log::warn!(
-                        "The item process-unmapped-keys is not defined in defcfg. Consider whether process-unmapped-keys should be yes vs. no."
-                    );
-                }
-                return Ok(cfg);
-            }
-        };
-        let val = match exprs.next() {
-            Some(v) => v,
-            None => bail_expr!(key, "Found a defcfg option missing a value"),
-        };
+    // An invalid option keeps its default value, and its error is reported together with the
+    // errors of the other options.
+    let mut parse_option = |key: &'a SExpr, val: &'a SExpr| -> Result<()> {
         match key {
             SExpr::Atom(k) => {
                 let label = k.t.as_str();
@@ -931,6 +951,67 @@ pub fn parse_defcfg(expr: &[SExpr]) -> Result<CfgOptions> {
                     "tap-hold-require-prior-idle" => {
                         cfg.tap_hold_require_prior_idle = parse_cfg_val_u16(val, label, false)?;
                     }
+                    "adaptive-tap-hold-file" => {
+                        let file = sexpr_to_str_or_err(val, label)?;
+                        if file.is_empty() {
+                            bail_expr!(val, "The value for {label} must not be empty");
+                        }
+                        cfg.adaptive_tap_hold_file = Some(file.to_owned());
+                    }
+                    "allow-lints" => {
+                        let lints = val.list(None).ok_or_else(|| {
+                            anyhow_expr!(val, "The value for {label} must be a list of lint names")
+                        })?;
+                        for lint in lints {
+                            let lint =
+                                lint.atom(None).and_then(Lint::from_name).ok_or_else(|| {
+                                    anyhow_expr!(
+                                        lint,
+                                        "Unknown lint name. Valid names are:\n{}",
+                                        Lint::ALL.map(Lint::name).join(" ")
+                                    )
+                                })?;
+                            cfg.allowed_lints.push(lint);
+                        }
+                    }
+                    "stats" => cfg.stats = parse_defcfg_val_bool(val, label)?,
+                    "stats-file" => {
+                        let file = sexpr_to_str_or_err(val, label)?;
+                        if file.is_empty() {
+                            bail_expr!(val, "The value for {label} must not be empty");
+                        }
+                        cfg.stats_file = Some(file.to_owned());
+                    }
+                    "stats-save-interval" => {
+                        cfg.stats_save_interval = parse_cfg_val_u16(val, label, true)?;
+                    }
+                    "stats-exclude" => {
+                        let items = val.list(None).ok_or_else(|| {
+                            anyhow_expr!(
+                                val,
+                                "The value for {label} must be a list containing letters and/or bigrams"
+                            )
+                        })?;
+                        for item in items {
+                            match item.atom(None) {
+                                Some("letters") => cfg.stats_exclude_letters = true,
+                                Some("bigrams") => cfg.stats_exclude_bigrams = true,
+                                _ => bail_expr!(item, "Expected letters or bigrams"),
+                            }
+                        }
+                    }
+                    "layer-lock-unlock-keys" => {
+                        let keys = val.list(None).ok_or_else(|| {
+                            anyhow_expr!(val, "The value for {label} must be a list of key names")
+                        })?;
+                        for key_expr in keys {
+                            let key =
+                                key_expr.atom(None).and_then(str_to_oscode).ok_or_else(|| {
+                                    anyhow_expr!(key_expr, "Expected a known key name.")
+                                })?;
+                            cfg.layer_lock_unlock_keys.push(key);
+                        }
+                    }
                     "mouse-movement-key" => {
                         #[cfg(any(
                             all(target_os = "windows", feature = "interception_driver"),
@@ -958,6 +1039,35 @@ pub fn parse_defcfg(expr: &[SExpr]) -> Result<CfgOptions> {
                 bail_expr!(key, "Lists are not allowed in as keys in defcfg");
             }
         }
+        Ok(())
+    };
+    // Read k-v pairs from the configuration
+    loop {
+        let key = match exprs.next() {
+            Some(k) => k,
+            None => {
+                if !is_process_unmapped_keys_defined {
+                    let msg = "The item process-unmapped-keys is not defined in defcfg. Consider whether process-unmapped-keys should be yes vs. no.";
+                    log::warn!("{msg}");
+                    warnings.push(ParseWarning {
+                        msg: msg.into(),
+                        span: expr.first().map(SExpr::span),
+                        lint: None,
+                    });
+                }
+                return (cfg, errors, warnings);
+            }
+        };
+        let val = match exprs.next() {
+            Some(v) => v,
+            None => {
+                errors.push(anyhow_expr!(key, "Found a defcfg option missing a value"));
+                return (cfg, errors, warnings);
+            }
+        };
+        if let Err(e) = parse_option(key, val) {
+            errors.push(e);
+        }
     }
 }
 
@@ -972,7 +1082,7 @@ pub const FALSE_VALUES: [&str; 3] = ["no", "false", "0"];
 pub const TRUE_VALUES: [&str; 3] = ["yes", "true", "1"];
 pub const BOOLEAN_VALUES: [&str; 6] = ["yes", "true", "1", "no", "false", "0"];
 
-fn parse_defcfg_val_bool(expr: &SExpr, label: &str) -> Result<bool> {
+pub(super) fn parse_defcfg_val_bool(expr: &SExpr, label: &str) -> Result<bool> {
     match &expr {
         SExpr::Atom(v) => {
             let val = v.t.trim_atom_quotes().to_ascii_lowercase();
@@ -0,0 +1,181 @@
+//! Text expansion: a trigger word followed by a boundary key is replaced by its expansion.
+//!
+//! Syntax is pairs of option names and values:
+//!
+//! `(defexpand expansions (btw "by the way") file expansions.tsv boundary-keys (spc ret))`
+//!
+//! Each line of the file is a trigger and its expansion separated by a tab. Empty lines and
+//! lines beginning with `//` are ignored.
+//!
+//! Expansions are typed with the same character table as `type-text`, so they can contain
+//! shifted symbols and characters from `defoslayout`.
+
+use super::defoslayout::{LayoutKey, layout_key};
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail_expr};
+
+pub(crate) const DEFEXPAND: &str = "defexpand";
+
+/// A character of an expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpandChar {
+    pub osc: OsCode,
+    /// Modifiers to hold while pressing the key, e.g. shift for uppercase letters.
+    pub mods: &'static [KeyCode],
+    /// Whether this is a letter, which can be capitalized to carry over the case of the trigger.
+    pub letter: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Expansions {
+    /// Expansions by the keys of their trigger.
+    pub expansions: HashMap<Box<[OsCode]>, Box<[ExpandChar]>>,
+    /// Keys that end a word and expand a trigger typed before them.
+    pub boundary_keys: Box<[OsCode]>,
+    /// The length of the longest trigger.
+    pub max_trigger_len: usize,
+}
+
+impl Expansions {
+    pub fn is_empty(&self) -> bool {
+        self.expansions.is_empty()
+    }
+}
+
+pub(super) fn parse_defexpand(
+    expr: &[SExpr],
+    s: &ParserState,
+    f: &mut FileContentProvider,
+) -> Result<Expansions> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFEXPAND)?;
+    let mut expansions: HashMap<Box<[OsCode]>, Box<[ExpandChar]>> = HashMap::default();
+    let mut boundary_keys = None;
+    while let Some(option) = subexprs.next() {
+        let label = option
+            .atom(s.vars())
+            .ok_or_else(|| anyhow_expr!(option, "expected an option name, found a list"))?;
+        let Some(val) = subexprs.next() else {
+            bail_expr!(option, "option {label} must be followed by a value");
+        };
+        match label {
+            "expansions" => {
+                let mut pairs = val
+                    .list(s.vars())
+                    .ok_or_else(|| {
+                        anyhow_expr!(val, "{label} must be a list of triggers and expansions")
+                    })?
+                    .chunks_exact(2);
+                for pair in pairs.by_ref() {
+                    let trigger = expansion_text(&pair[0], s)?;
+                    let expansion = expansion_text(&pair[1], s)?;
+                    let trigger =
+                        parse_trigger(trigger).map_err(|e| anyhow_expr!(&pair[0], "{e}"))?;
+                    let expansion = parse_expansion(expansion, &s.os_layout)
+                        .map_err(|e| anyhow_expr!(&pair[1], "{e}"))?;
+                    if expansions.insert(trigger, expansion).is_some() {
+                        bail_expr!(&pair[0], "duplicate trigger");
+                    }
+                }
+                if let [trigger] = pairs.remainder() {
+                    bail_expr!(trigger, "trigger must be followed by its expansion");
+                }
+            }
+            "file" => {
+                let file_name = val
+                    .atom(s.vars())
+                    .ok_or_else(|| anyhow_expr!(val, "Filename must be a string, not a list."))?;
+                let content = f
+                    .get_file_content(file_name.trim_atom_quotes().as_ref())
+                    .map_err(|e| anyhow_expr!(val, "Failed to read file:\n{e}"))?;
+                for (line_number, line) in content.lines().enumerate() {
+                    if line.trim().is_empty() || line.trim().starts_with("//") {
+                        continue;
+                    }
+                    let line_error =
+                        |msg: &str| anyhow_expr!(val, "{msg}:\n{}: {line}", line_number + 1);
+                    let Some((trigger, expansion)) = line.split_once('\t') else {
+                        return Err(line_error(
+                            "Trigger and expansion are separated by a tab, but found no tab",
+                        ));
+                    };
+                    let trigger = parse_trigger(trigger).map_err(|e| line_error(&e))?;
+                    let expansion =
+                        parse_expansion(expansion, &s.os_layout).map_err(|e| line_error(&e))?;
+                    if expansions.insert(trigger, expansion).is_some() {
+                        return Err(line_error("duplicate trigger"));
+                    }
+                }
+            }
+            "boundary-keys" => {
+                boundary_keys = Some(parse_key_list(val, s, label)?.into_boxed_slice());
+            }
+            _ => bail_expr!(
+                option,
+                "Unknown defexpand option {label}. Valid options are: expansions, file, \
+                 boundary-keys"
+            ),
+        }
+    }
+    if expansions.is_empty() {
+        bail_expr!(&expr[0], "defexpand must define at least one expansion");
+    }
+    let max_trigger_len = expansions.keys().map(|t| t.len()).max().unwrap_or(0);
+    Ok(Expansions {
+        expansions,
+        boundary_keys: boundary_keys.unwrap_or_else(default_boundary_keys),
+        max_trigger_len,
+    })
+}
+
+pub(super) fn default_boundary_keys() -> Box<[OsCode]> {
+    Box::new([OsCode::KEY_SPACE, OsCode::KEY_ENTER, OsCode::KEY_TAB])
+}
+
+fn expansion_text<'a>(expr: &'a SExpr, s: &'a ParserState) -> Result<&'a str> {
+    expr.atom(s.vars())
+        .map(|text| text.trim_atom_quotes())
+        .ok_or_else(|| anyhow_expr!(expr, "expected a string, found a list"))
+}
+
+/// The keys of a trigger. Case is ignored because it carries over from typing the trigger.
+fn parse_trigger(trigger: &str) -> std::result::Result<Box<[OsCode]>, String> {
+    if trigger.is_empty() {
+        return Err("trigger must not be empty".into());
+    }
+    trigger
+        .chars()
+        .map(|c| {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            char_to_oscode(lower)
+                .filter(|osc| *osc != OsCode::KEY_SPACE)
+                .ok_or_else(|| format!("Unknown trigger key name '{c}'"))
+        })
+        .collect()
+}
+
+/// The keys that type an expansion, looked up like the characters of `type-text`.
+pub(super) fn parse_expansion(
+    expansion: &str,
+    os_layout: &HashMap<char, LayoutKey>,
+) -> std::result::Result<Box<[ExpandChar]>, String> {
+    expansion
+        .chars()
+        .map(|c| {
+            let LayoutKey { mods, key } =
+                layout_key(c, os_layout).ok_or_else(|| format!("Unknown output key name '{c}'"))?;
+            Ok(ExpandChar {
+                osc: key.into(),
+                mods,
+                letter: c.is_alphabetic(),
+            })
+        })
+        .collect()
+}
+
+pub(super) fn char_to_oscode(c: char) -> Option<OsCode> {
+    match c {
+        ' ' => Some(OsCode::KEY_SPACE),
+        _ => str_to_oscode(c.encode_utf8(&mut [0; 4])),
+    }
+}
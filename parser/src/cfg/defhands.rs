@@ -108,6 +108,7 @@ pub(super) fn parse_tap_hold_opposite_hand(
     let mut unknown_hand = DecisionBehavior::Ignore;
     let mut neutral_keys: Vec<OsCode> = Vec::new();
     let mut require_prior_idle: Option<u16> = None;
+    let mut adaptive: Option<AdaptiveBounds> = None;
     let mut tap_repress_timeout: u16 = 0;
     let mut seen_options: HashSet<&str> = HashSet::default();
 
@@ -184,6 +185,9 @@ pub(super) fn parse_tap_hold_opposite_hand(
                     s,
                 )?);
             }
+            "adaptive" => {
+                adaptive = Some(tap_hold::parse_adaptive_option(option, option_expr, s)?);
+            }
             "tap-repress-timeout" => {
                 tap_repress_timeout =
                     tap_hold::parse_tap_repress_timeout_option(option, option_expr, s)?;
@@ -192,7 +196,7 @@ pub(super) fn parse_tap_hold_opposite_hand(
                 &option[0],
                 "unknown option '{}' for tap-hold-opposite-hand. \
                 Valid options: timeout, same-hand, neutral, unknown-hand, neutral-keys, \
-                require-prior-idle, tap-repress-timeout",
+                require-prior-idle, adaptive, tap-repress-timeout",
                 kw
             ),
         }
@@ -222,6 +226,7 @@ pub(super) fn parse_tap_hold_opposite_hand(
         timeout_action: *timeout_action,
         on_press_reset_timeout_to: None,
         require_prior_idle,
+        adaptive,
     }))))
 }
 
@@ -262,6 +267,7 @@ pub(super) fn parse_tap_hold_opposite_hand_release(
     let mut unknown_hand = DecisionBehavior::Ignore;
     let mut neutral_keys: Vec<OsCode> = Vec::new();
     let mut require_prior_idle: Option<u16> = None;
+    let mut adaptive: Option<AdaptiveBounds> = None;
     let mut tap_repress_timeout: u16 = 0;
     let mut seen_options: HashSet<&str> = HashSet::default();
 
@@ -338,6 +344,9 @@ pub(super) fn parse_tap_hold_opposite_hand_release(
                     s,
                 )?);
             }
+            "adaptive" => {
+                adaptive = Some(tap_hold::parse_adaptive_option(option, option_expr, s)?);
+            }
             "tap-repress-timeout" => {
                 tap_repress_timeout =
                     tap_hold::parse_tap_repress_timeout_option(option, option_expr, s)?;
@@ -346,7 +355,7 @@ pub(super) fn parse_tap_hold_opposite_hand_release(
                 &option[0],
                 "unknown option '{}' for tap-hold-opposite-hand-release. \
                 Valid options: timeout, same-hand, neutral, unknown-hand, neutral-keys, \
-                require-prior-idle, tap-repress-timeout",
+                require-prior-idle, adaptive, tap-repress-timeout",
                 kw
             ),
         }
@@ -376,6 +385,7 @@ pub(super) fn parse_tap_hold_opposite_hand_release(
         timeout_action: *timeout_action,
         on_press_reset_timeout_to: None,
         require_prior_idle,
+        adaptive,
     }))))
 }
 
@@ -130,102 +130,15 @@ pub(crate) fn parse_layers(
     }
     let mut defsrc_layer = s.defsrc_layer;
     for (layer_level, layer) in s.layer_exprs.iter().enumerate() {
-        match layer {
-            // The skip is done to skip the `deflayer` and layer name tokens.
-            LayerExprs::DefsrcMapping(layer) => {
-                // Parse actions in the layer and place them appropriately according
-                // to defsrc mapping order.
-                for (i, ac) in layer.iter().skip(2).enumerate() {
-                    let ac = parse_action(ac, s)?;
-                    layers_cfg[layer_level][0][s.mapping_order[i]] = *ac;
-                }
-            }
-            LayerExprs::CustomMapping(layer) => {
-                // Parse actions as input output pairs
-                let mut pairs = layer[2..].chunks_exact(2);
-                let mut layer_mapped_keys = HashSet::default();
-                let mut defsrc_anykey_used = false;
-                let mut unmapped_anykey_used = false;
-                let mut both_anykey_used = false;
-                for pair in pairs.by_ref() {
-                    let input = &pair[0];
-                    let action = &pair[1];
-
-                    let action = parse_action(action, s)?;
-                    if input.atom(s.vars()).is_some_and(|x| x == "_") {
-                        if defsrc_anykey_used {
-                            bail_expr!(input, "must have only one use of _ within a layer")
-                        }
-                        if both_anykey_used {
-                            bail_expr!(input, "must either use _ or ___ within a layer, not both")
-                        }
-                        for i in 0..s.mapping_order.len() {
-                            if layers_cfg[layer_level][0][s.mapping_order[i]] == DEFAULT_ACTION {
-                                layers_cfg[layer_level][0][s.mapping_order[i]] = *action;
-                            }
-                        }
-                        defsrc_anykey_used = true;
-                    } else if input.atom(s.vars()).is_some_and(|x| x == "__") {
-                        if unmapped_anykey_used {
-                            bail_expr!(input, "must have only one use of __ within a layer")
-                        }
-                        if !defcfg.process_unmapped_keys {
-                            bail_expr!(
-                                input,
-                                "must set process-unmapped-keys to yes to use __ to map unmapped keys"
-                            );
-                        }
-                        if both_anykey_used {
-                            bail_expr!(input, "must either use __ or ___ within a layer, not both")
-                        }
-                        for i in 0..layers_cfg[0][0].len() {
-                            if layers_cfg[layer_level][0][i] == DEFAULT_ACTION
-                                && !s.mapping_order.contains(&i)
-                            {
-                                layers_cfg[layer_level][0][i] = *action;
-                            }
-                        }
-                        unmapped_anykey_used = true;
-                    } else if input.atom(s.vars()).is_some_and(|x| x == "___") {
-                        if both_anykey_used {
-                            bail_expr!(input, "must have only one use of ___ within a layer")
-                        }
-                        if defsrc_anykey_used {
-                            bail_expr!(input, "must either use _ or ___ within a layer, not both")
-                        }
-                        if unmapped_anykey_used {
-                            bail_expr!(input, "must either use __ or ___ within a layer, not both")
-                        }
-                        if !defcfg.process_unmapped_keys {
-                            bail_expr!(
-                                input,
-                                "must set process-unmapped-keys to yes to use ___ to also map unmapped keys"
-                            );
-                        }
-                        for i in 0..layers_cfg[0][0].len() {
-                            if layers_cfg[layer_level][0][i] == DEFAULT_ACTION {
-                                layers_cfg[layer_level][0][i] = *action;
-                            }
-                        }
-                        both_anykey_used = true;
-                    } else {
-                        let input_key = input
-                            .atom(s.vars())
-                            .and_then(str_to_oscode)
-                            .ok_or_else(|| anyhow_expr!(input, "input must be a key name"))?;
-                        mapped_keys.insert(input_key);
-                        if !layer_mapped_keys.insert(input_key) {
-                            bail_expr!(input, "input key must not be repeated within a layer")
-                        }
-                        layers_cfg[layer_level][0][usize::from(input_key)] = *action;
-                    }
-                }
-                let rem = pairs.remainder();
-                if !rem.is_empty() {
-                    bail_expr!(&rem[0], "input must by followed by an action");
-                }
-            }
-        }
+        // A layer with an error is kept, with the actions that failed to parse left as defaults,
+        // so that the remaining layers can still be checked.
+        s.recover(parse_layer_actions(
+            layer,
+            &mut layers_cfg[layer_level],
+            s,
+            mapped_keys,
+            defcfg,
+        ));
         for (osc, layer_action) in layers_cfg[layer_level][0].iter_mut().enumerate() {
             if *layer_action == DEFAULT_ACTION {
                 *layer_action = match s.block_unmapped_keys && !is_a_button(osc as u16) {
@@ -259,6 +172,111 @@ pub(crate) fn parse_layers(
     Ok(layers_cfg)
 }
 
+/// Parse the actions of one layer into `layer_cfg`. Actions after an error are not parsed.
+fn parse_layer_actions(
+    layer: &LayerExprs,
+    layer_cfg: &mut [Row; LAYER_ROWS],
+    s: &ParserState,
+    mapped_keys: &mut MappedKeys,
+    defcfg: &CfgOptions,
+) -> Result<()> {
+    match layer {
+        // The skip is done to skip the `deflayer` and layer name tokens.
+        LayerExprs::DefsrcMapping(layer) => {
+            // Parse actions in the layer and place them appropriately according
+            // to defsrc mapping order.
+            for (i, ac) in layer.iter().skip(2).enumerate() {
+                let ac = parse_action(ac, s)?;
+                layer_cfg[0][s.mapping_order[i]] = *ac;
+            }
+        }
+        LayerExprs::CustomMapping(layer) => {
+            // Parse actions as input output pairs
+            let mut pairs = layer[2..].chunks_exact(2);
+            let mut layer_mapped_keys = HashSet::default();
+            let mut defsrc_anykey_used = false;
+            let mut unmapped_anykey_used = false;
+            let mut both_anykey_used = false;
+            for pair in pairs.by_ref() {
+                let input = &pair[0];
+                let action = &pair[1];
+
+                let action = parse_action(action, s)?;
+                if input.atom(s.vars()).is_some_and(|x| x == "_") {
+                    if defsrc_anykey_used {
+                        bail_expr!(input, "must have only one use of _ within a layer")
+                    }
+                    if both_anykey_used {
+                        bail_expr!(input, "must either use _ or ___ within a layer, not both")
+                    }
+                    for i in 0..s.mapping_order.len() {
+                        if layer_cfg[0][s.mapping_order[i]] == DEFAULT_ACTION {
+                            layer_cfg[0][s.mapping_order[i]] = *action;
+                        }
+                    }
+                    defsrc_anykey_used = true;
+                } else if input.atom(s.vars()).is_some_and(|x| x == "__") {
+                    if unmapped_anykey_used {
+                        bail_expr!(input, "must have only one use of __ within a layer")
+                    }
+                    if !defcfg.process_unmapped_keys {
+                        bail_expr!(
+                            input,
+                            "must set process-unmapped-keys to yes to use __ to map unmapped keys"
+                        );
+                    }
+                    if both_anykey_used {
+                        bail_expr!(input, "must either use __ or ___ within a layer, not both")
+                    }
+                    for i in 0..layer_cfg[0].len() {
+                        if layer_cfg[0][i] == DEFAULT_ACTION && !s.mapping_order.contains(&i) {
+                            layer_cfg[0][i] = *action;
+                        }
+                    }
+                    unmapped_anykey_used = true;
+                } else if input.atom(s.vars()).is_some_and(|x| x == "___") {
+                    if both_anykey_used {
+                        bail_expr!(input, "must have only one use of ___ within a layer")
+                    }
+                    if defsrc_anykey_used {
+                        bail_expr!(input, "must either use _ or ___ within a layer, not both")
+                    }
+                    if unmapped_anykey_used {
+                        bail_expr!(input, "must either use __ or ___ within a layer, not both")
+                    }
+                    if !defcfg.process_unmapped_keys {
+                        bail_expr!(
+                            input,
+                            "must set process-unmapped-keys to yes to use ___ to also map unmapped keys"
+                        );
+                    }
+                    for i in 0..layer_cfg[0].len() {
+                        if layer_cfg[0][i] == DEFAULT_ACTION {
+                            layer_cfg[0][i] = *action;
+                        }
+                    }
+                    both_anykey_used = true;
+                } else {
+                    let input_key = input
+                        .atom(s.vars())
+                        .and_then(str_to_oscode)
+                        .ok_or_else(|| anyhow_expr!(input, "input must be a key name"))?;
+                    mapped_keys.insert(input_key);
+                    if !layer_mapped_keys.insert(input_key) {
+                        bail_expr!(input, "input key must not be repeated within a layer")
+                    }
+                    layer_cfg[0][usize::from(input_key)] = *action;
+                }
+            }
+            let rem = pairs.remainder();
+            if !rem.is_empty() {
+                bail_expr!(&rem[0], "input must by followed by an action");
+            }
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn parse_layer_base(
     ac_params: &[SExpr],
     s: &ParserState,
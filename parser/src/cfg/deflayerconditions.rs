@@ -0,0 +1,67 @@
+//! Layers that are activated automatically while other layers are active, e.g. a tri-layer.
+//!
+//! Syntax is a list of layers, an arrow and the layer to activate:
+//!
+//! `(deflayerconditions (<layer> ...) -> <layer> ...)`
+
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail_expr};
+
+use kanata_keyberon::layout::LayerCondition;
+
+pub(crate) const DEFLAYERCONDITIONS: &str = "deflayerconditions";
+
+/// Parse all `deflayerconditions` blocks. Earlier conditions take priority when several are
+/// met at once.
+pub(super) fn parse_deflayerconditions(
+    exprs: &[&Vec<SExpr>],
+    s: &ParserState,
+) -> Result<Box<[LayerCondition]>> {
+    let mut conditions = vec![];
+    for expr in exprs {
+        let mut subexprs = check_first_expr(expr.iter(), DEFLAYERCONDITIONS)?;
+        while let Some(layers_expr) = subexprs.next() {
+            let layers = layers_expr.list(s.vars()).ok_or_else(|| {
+                anyhow_expr!(
+                    layers_expr,
+                    "expected a list of layers that must be active, e.g. (nav sym)"
+                )
+            })?;
+            if layers.is_empty() {
+                bail_expr!(layers_expr, "the list of layers must not be empty");
+            }
+            let if_active = layers
+                .iter()
+                .map(|layer| layer_idx(layer, s))
+                .collect::<Result<Box<[u16]>>>()?;
+            match subexprs.next() {
+                Some(arrow) if arrow.atom(s.vars()) == Some("->") => {}
+                Some(other) => bail_expr!(other, "expected -> after the list of layers"),
+                None => bail_expr!(
+                    layers_expr,
+                    "the list of layers must be followed by -> and the layer to activate"
+                ),
+            }
+            let Some(then_expr) = subexprs.next() else {
+                bail_expr!(layers_expr, "expected the layer to activate after ->");
+            };
+            let then = layer_idx(then_expr, s)?;
+            if if_active.contains(&then) {
+                bail_expr!(
+                    then_expr,
+                    "the layer to activate must not be one of the layers it depends on"
+                );
+            }
+            conditions.push(LayerCondition { if_active, then });
+        }
+    }
+    Ok(conditions.into_boxed_slice())
+}
+
+fn layer_idx(expr: &SExpr, s: &ParserState) -> Result<u16> {
+    expr.atom(s.vars())
+        .and_then(|atom| s.layer_idxs.get(atom))
+        .map(|idx| *idx as u16)
+        .ok_or_else(|| anyhow_expr!(expr, "not a known layer name"))
+}
@@ -0,0 +1,46 @@
+//! Mirrored key positions for `swap-hands`.
+//!
+//! Syntax is pairs of keys, each of which is the mirror of the other:
+//!
+//! `(defmirror <key> <key> ...)`
+
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail_expr};
+
+pub(crate) const DEFMIRROR: &str = "defmirror";
+
+/// Parse a `defmirror` block into pairs of key codes, in both directions.
+pub(super) fn parse_defmirror(expr: &[SExpr], s: &ParserState) -> Result<Box<[(u16, u16)]>> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFMIRROR)?;
+    let mut mirror: Vec<(u16, u16)> = vec![];
+    while let Some(left_expr) = subexprs.next() {
+        let Some(right_expr) = subexprs.next() else {
+            bail_expr!(left_expr, "key must be followed by the key that mirrors it");
+        };
+        let left = mirror_key(left_expr, &mirror, s)?;
+        let right = mirror_key(right_expr, &mirror, s)?;
+        if left == right {
+            bail_expr!(right_expr, "a key cannot mirror itself");
+        }
+        mirror.push((left, right));
+        mirror.push((right, left));
+    }
+    Ok(mirror.into_boxed_slice())
+}
+
+fn mirror_key(expr: &SExpr, mirror: &[(u16, u16)], s: &ParserState) -> Result<u16> {
+    let key_name = expr
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(expr, "expected a key name, found list"))?;
+    let key = str_to_oscode(key_name)
+        .map(u16::from)
+        .ok_or_else(|| anyhow_expr!(expr, "unknown key '{key_name}'"))?;
+    if mirror.iter().any(|(from, _)| *from == key) {
+        bail_expr!(
+            expr,
+            "key is already mirrored, a key can only have one mirror"
+        );
+    }
+    Ok(key)
+}
@@ -0,0 +1,191 @@
+//! The characters produced by keys of the OS layout, for `type-text`.
+//!
+//! Syntax is pairs of a character and the key that types it with the OS layout. Keys may have
+//! modifier prefixes for shifted and AltGr levels:
+//!
+//! `(defoslayout y z  z y  ü [  Ü S-[  @ AG-q  "(" S-8)`
+//!
+//! Characters not in `defoslayout` are typed as on the US layout, then by key names such as the
+//! ones from `deflocalkeys`. Any other character is typed with `unicode`.
+
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail, bail_expr};
+
+pub(crate) const DEFOSLAYOUT: &str = "defoslayout";
+
+/// A key with the modifiers to hold while pressing it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LayoutKey {
+    pub(super) mods: &'static [KeyCode],
+    pub(super) key: KeyCode,
+}
+
+const SHIFT: &[KeyCode] = &[KeyCode::LShift];
+
+/// Parse a `defoslayout` block.
+pub(super) fn parse_defoslayout(
+    expr: &[SExpr],
+    s: &ParserState,
+) -> Result<HashMap<char, LayoutKey>> {
+    let mut subexprs = check_first_expr(expr.iter(), DEFOSLAYOUT)?;
+    let mut layout = HashMap::default();
+    while let Some(char_expr) = subexprs.next() {
+        let Some(key_expr) = subexprs.next() else {
+            bail_expr!(
+                char_expr,
+                "character must be followed by the key that types it"
+            );
+        };
+        let chars = char_expr
+            .atom(s.vars())
+            .ok_or_else(|| anyhow_expr!(char_expr, "expected a character, found a list"))?
+            .trim_atom_quotes();
+        let mut chars_iter = chars.chars();
+        let (Some(c), None) = (chars_iter.next(), chars_iter.next()) else {
+            bail_expr!(char_expr, "expected a single character, found '{chars}'");
+        };
+        let key_name = key_expr
+            .atom(s.vars())
+            .ok_or_else(|| anyhow_expr!(key_expr, "expected a key name, found a list"))?;
+        let (mods, unmodded) =
+            parse_mod_prefix(key_name).map_err(|e| anyhow_expr!(key_expr, "{}", e.msg))?;
+        if mods.contains(&KEY_OVERLAP) {
+            bail_expr!(key_expr, "O- is not allowed in defoslayout");
+        }
+        let key = str_to_oscode(unmodded)
+            .ok_or_else(|| anyhow_expr!(key_expr, "unknown key '{unmodded}'"))?;
+        let key = LayoutKey {
+            mods: s.a.sref_vec(mods),
+            key: key.into(),
+        };
+        if layout.insert(c, key).is_some() {
+            bail_expr!(char_expr, "duplicate character '{c}'");
+        }
+    }
+    Ok(layout)
+}
+
+/// Parse `(type-text $text)` into a macro that types the text using the OS layout.
+pub(super) fn parse_type_text(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "type-text expects 1 parameter: <text>";
+    if ac_params.len() != 1 {
+        bail!("{ERR_MSG}, found {}", ac_params.len());
+    }
+    let text = ac_params[0]
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(&ac_params[0], "{ERR_MSG}, found a list"))?
+        .trim_atom_quotes();
+    if text.is_empty() {
+        bail_expr!(&ac_params[0], "{ERR_MSG}, found empty text");
+    }
+    let mut events: Vec<SequenceEvent<KanataCustom>> = vec![];
+    for c in text.chars() {
+        match layout_key(c, &s.os_layout) {
+            Some(LayoutKey { mods, key }) => {
+                events.extend(mods.iter().copied().map(SequenceEvent::Press));
+                events.push(SequenceEvent::Press(key));
+                events.push(SequenceEvent::Release(key));
+                events.extend(mods.iter().rev().copied().map(SequenceEvent::Release));
+            }
+            None => {
+                log::debug!("type-text: typing {c:?} with unicode");
+                events.push(SequenceEvent::Custom(
+                    s.a.sref(s.a.sref(CustomAction::Unicode(c))),
+                ));
+            }
+        }
+    }
+    events.push(SequenceEvent::Complete);
+    Ok(s.a.sref(Action::Sequence {
+        events: s.a.sref(s.a.sref(s.a.sref_vec(events))),
+    }))
+}
+
+/// The key that types a character: from `defoslayout`, then as on the US layout, then by key
+/// name, with shift for uppercase characters.
+pub(super) fn layout_key(c: char, os_layout: &HashMap<char, LayoutKey>) -> Option<LayoutKey> {
+    if let Some(key) = os_layout.get(&c) {
+        return Some(*key);
+    }
+    // An uppercase letter is typed with shift on the unmodified key of its lowercase letter.
+    let mut lowercase = c.to_lowercase();
+    if let (Some(lower), None) = (lowercase.next(), lowercase.next()) {
+        match os_layout.get(&lower) {
+            Some(LayoutKey { mods: [], key }) if lower != c => {
+                return Some(LayoutKey {
+                    mods: SHIFT,
+                    key: *key,
+                });
+            }
+            _ => {}
+        }
+    }
+    let unshifted = |osc: OsCode| LayoutKey {
+        mods: &[],
+        key: osc.into(),
+    };
+    let shifted = |osc: OsCode| LayoutKey {
+        mods: SHIFT,
+        key: osc.into(),
+    };
+    let key = match c {
+        'a'..='z' | '0'..='9' => str_to_oscode(c.encode_utf8(&mut [0; 4])).map(unshifted)?,
+        'A'..='Z' => shifted(str_to_oscode(
+            c.to_ascii_lowercase().encode_utf8(&mut [0; 4]),
+        )?),
+        ' ' => unshifted(OsCode::KEY_SPACE),
+        '\n' => unshifted(OsCode::KEY_ENTER),
+        '\t' => unshifted(OsCode::KEY_TAB),
+        '`' => unshifted(OsCode::KEY_GRAVE),
+        '-' => unshifted(OsCode::KEY_MINUS),
+        '=' => unshifted(OsCode::KEY_EQUAL),
+        '[' => unshifted(OsCode::KEY_LEFTBRACE),
+        ']' => unshifted(OsCode::KEY_RIGHTBRACE),
+        '\\' => unshifted(OsCode::KEY_BACKSLASH),
+        ';' => unshifted(OsCode::KEY_SEMICOLON),
+        '\'' => unshifted(OsCode::KEY_APOSTROPHE),
+        ',' => unshifted(OsCode::KEY_COMMA),
+        '.' => unshifted(OsCode::KEY_DOT),
+        '/' => unshifted(OsCode::KEY_SLASH),
+        '~' => shifted(OsCode::KEY_GRAVE),
+        '!' => shifted(OsCode::KEY_1),
+        '@' => shifted(OsCode::KEY_2),
+        '#' => shifted(OsCode::KEY_3),
+        '$' => shifted(OsCode::KEY_4),
+        '%' => shifted(OsCode::KEY_5),
+        '^' => shifted(OsCode::KEY_6),
+        '&' => shifted(OsCode::KEY_7),
+        '*' => shifted(OsCode::KEY_8),
+        '(' => shifted(OsCode::KEY_9),
+        ')' => shifted(OsCode::KEY_0),
+        '_' => shifted(OsCode::KEY_MINUS),
+        '+' => shifted(OsCode::KEY_EQUAL),
+        '{' => shifted(OsCode::KEY_LEFTBRACE),
+        '}' => shifted(OsCode::KEY_RIGHTBRACE),
+        '|' => shifted(OsCode::KEY_BACKSLASH),
+        ':' => shifted(OsCode::KEY_SEMICOLON),
+        '"' => shifted(OsCode::KEY_APOSTROPHE),
+        '<' => shifted(OsCode::KEY_COMMA),
+        '>' => shifted(OsCode::KEY_DOT),
+        '?' => shifted(OsCode::KEY_SLASH),
+        _ if c.is_ascii() => return None,
+        _ => {
+            let mut lowercase = c.to_lowercase();
+            let lower = match (lowercase.next(), lowercase.next()) {
+                (Some(lower), None) => lower,
+                _ => return None,
+            };
+            let osc = str_to_oscode(lower.encode_utf8(&mut [0; 4]))?;
+            if c.is_uppercase() {
+                shifted(osc)
+            } else {
+                unshifted(osc)
+            }
+        }
+    };
+    Some(key)
+}
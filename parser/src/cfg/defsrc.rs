@@ -76,6 +76,35 @@ pub(crate) fn parse_defsrc(
     Ok((mkeys, ordered_codes))
 }
 
+/// Where a key is written in `defsrc`. Used to draw the layers in the shape of the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefsrcKey {
+    pub osc: OsCode,
+    /// Zero-based line in the file containing `defsrc`.
+    pub line: usize,
+    /// Zero-based column, in characters.
+    pub column: usize,
+}
+
+/// Get the position of each key in an already-parsed `defsrc`, in order.
+pub(crate) fn parse_defsrc_keys(expr: &[SExpr]) -> Vec<DefsrcKey> {
+    expr.iter()
+        .skip(1)
+        .filter_map(|expr| {
+            let SExpr::Atom(a) = expr else {
+                return None;
+            };
+            let start = a.span.start;
+            let line_text = &a.span.file_content[start.line_beginning..start.absolute];
+            Some(DefsrcKey {
+                osc: str_to_oscode(&a.t)?,
+                line: start.line,
+                column: line_text.chars().count(),
+            })
+        })
+        .collect()
+}
+
 pub(crate) fn create_defsrc_layer() -> [KanataAction; KEYS_IN_ROW] {
     let mut layer = [KanataAction::NoOp; KEYS_IN_ROW];
 
@@ -0,0 +1,173 @@
+//! User-defined state variables.
+//!
+//! Variables are declared in `defstate` with their initial value, changed by the `state-*`
+//! actions and checked by the `(state <name> <value>)` condition in `switch`.
+
+use super::sexpr::*;
+use super::*;
+use crate::{anyhow_expr, bail, bail_expr};
+
+use kanata_keyberon::action::switch::MAX_STATE_VARS;
+
+pub(crate) const DEFSTATE: &str = "defstate";
+
+/// A state variable declared in `defstate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateVar {
+    pub name: String,
+    pub initial: i16,
+}
+
+/// Parse all `defstate` blocks. The index of a variable is its position in the returned list.
+///
+/// Syntax is pairs of name and initial value:
+///
+/// `(defstate <name> <integer|true|false> ...)`
+pub(super) fn parse_defstate(exprs: &[&Vec<SExpr>], s: &ParserState) -> Result<Vec<StateVar>> {
+    let mut state_vars: Vec<StateVar> = vec![];
+    for expr in exprs {
+        let mut subexprs = check_first_expr(expr.iter(), DEFSTATE)?;
+        while let Some(name_expr) = subexprs.next() {
+            let name = name_expr
+                .atom(s.vars())
+                .ok_or_else(|| anyhow_expr!(name_expr, "state variable name must not be a list"))?;
+            if state_vars.iter().any(|v| v.name == name) {
+                bail_expr!(name_expr, "duplicate state variable name: {name}");
+            }
+            let Some(initial_expr) = subexprs.next() else {
+                bail_expr!(
+                    name_expr,
+                    "state variable name must have a subsequent initial value"
+                );
+            };
+            if state_vars.len() >= usize::from(MAX_STATE_VARS) {
+                bail_expr!(
+                    name_expr,
+                    "too many state variables, the maximum is {MAX_STATE_VARS}"
+                );
+            }
+            state_vars.push(StateVar {
+                name: name.to_owned(),
+                initial: parse_state_value(initial_expr, s)?,
+            });
+        }
+    }
+    Ok(state_vars)
+}
+
+/// Parse a state variable value: an integer from -32768 to 32767, or `true`/`false` which are
+/// 1 and 0.
+pub(super) fn parse_state_value(expr: &SExpr, s: &ParserState) -> Result<i16> {
+    match expr.atom(s.vars()) {
+        Some("true") => Ok(1),
+        Some("false") => Ok(0),
+        Some(a) => a.parse::<i16>().map_err(|_| {
+            anyhow_expr!(
+                expr,
+                "state value must be true, false or an integer from -32768 to 32767"
+            )
+        }),
+        None => bail_expr!(expr, "state value must not be a list"),
+    }
+}
+
+/// The index of a variable declared in `defstate`.
+pub(super) fn state_var_idx(expr: &SExpr, s: &ParserState) -> Result<u16> {
+    let name = expr
+        .atom(s.vars())
+        .ok_or_else(|| anyhow_expr!(expr, "state variable name must not be a list"))?;
+    s.state_vars
+        .iter()
+        .position(|v| v.name == name)
+        .map(|idx| idx as u16)
+        .ok_or_else(|| {
+            anyhow_expr!(
+                expr,
+                "state variable is not declared in any defstate: {name}"
+            )
+        })
+}
+
+pub(super) fn parse_state_set(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "expects 2 parameters: <state variable> <value>";
+    if ac_params.len() != 2 {
+        bail!("{STATE_SET} {ERR_MSG}, found {}", ac_params.len());
+    }
+    let var = state_var_idx(&ac_params[0], s)?;
+    let value = parse_state_value(&ac_params[1], s)?;
+    custom(
+        CustomAction::StateVar {
+            var,
+            op: StateVarOp::Set(value),
+        },
+        &s.a,
+    )
+}
+
+pub(super) fn parse_state_toggle(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "expects 1 parameter: <state variable>";
+    if ac_params.len() != 1 {
+        bail!("{STATE_TOGGLE} {ERR_MSG}, found {}", ac_params.len());
+    }
+    let var = state_var_idx(&ac_params[0], s)?;
+    custom(
+        CustomAction::StateVar {
+            var,
+            op: StateVarOp::Toggle,
+        },
+        &s.a,
+    )
+}
+
+pub(super) fn parse_state_reset(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "expects 1 parameter: <state variable>";
+    if ac_params.len() != 1 {
+        bail!("{STATE_RESET} {ERR_MSG}, found {}", ac_params.len());
+    }
+    let var = state_var_idx(&ac_params[0], s)?;
+    custom(
+        CustomAction::StateVar {
+            var,
+            op: StateVarOp::Set(s.state_vars[usize::from(var)].initial),
+        },
+        &s.a,
+    )
+}
+
+/// Parse `state-inc` and `state-dec`, which take an optional amount that defaults to 1.
+pub(super) fn parse_state_add(
+    ac_params: &[SExpr],
+    s: &ParserState,
+    action_name: &str,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "expects 1 or 2 parameters: <state variable> [amount]";
+    if !(1..=2).contains(&ac_params.len()) {
+        bail!("{action_name} {ERR_MSG}, found {}", ac_params.len());
+    }
+    let var = state_var_idx(&ac_params[0], s)?;
+    let amount = match ac_params.get(1) {
+        Some(expr) => i16::try_from(parse_u16(expr, s, "amount")?)
+            .map_err(|_| anyhow_expr!(expr, "amount must be 0-32767"))?,
+        None => 1,
+    };
+    let amount = match action_name {
+        STATE_DEC => -amount,
+        _ => amount,
+    };
+    custom(
+        CustomAction::StateVar {
+            var,
+            op: StateVarOp::Add(amount),
+        },
+        &s.a,
+    )
+}
@@ -9,7 +9,7 @@
 //!   Instead the code could pre-compute the paths to access every variable
 //!   that needs substition. (perf_1)
 //!
-//! - Replacing the `template-expand|if-equal` items with the appropriate values
+//! - Replacing the `template-expand|template-for|if-equal` items with the appropriate values
 //!   recreates the Vec for every replacement that happens at that recursion depth.
 //!   Instead the code could do recreate the vec only once
 //!   and insert SExprs at the proper places. (perf_2)
@@ -213,12 +213,21 @@ fn expand(exprs: &mut Vec<SExpr>, templates: &[Template], _lsp_hints: &mut LspHi
             match expr {
                 SExpr::Atom(_) => continue,
                 SExpr::List(l) => {
-                    if !matches!(
-                        l.t.first().and_then(|expr| expr.atom(None)),
-                        Some("template-expand") | Some("t!")
-                    ) {
-                        expand(&mut l.t, templates, _lsp_hints)?;
-                        continue;
+                    match l.t.first().and_then(|expr| expr.atom(None)) {
+                        Some("template-expand") | Some("t!") => {}
+                        // Template content was already saved and its variables are not known yet.
+                        Some("deftemplate") => continue,
+                        Some("template-for") => {
+                            replacements.push(Replacement {
+                                insert_index: expr_index,
+                                exprs: expand_for(l)?,
+                            });
+                            continue;
+                        }
+                        _ => {
+                            expand(&mut l.t, templates, _lsp_hints)?;
+                            continue;
+                        }
                     }
 
                     // found expand, now parse
@@ -285,19 +294,7 @@ fn expand(exprs: &mut Vec<SExpr>, templates: &[Template], _lsp_hints: &mut LspHi
                         }
                     });
 
-                    visit_mut_all_lists(&mut expanded_template, &mut |expr: &mut SExpr| {
-                        *expr = match expr {
-                            // Below should not be reached because only lists should be visited
-                            SExpr::Atom(_) => unreachable!(),
-                            SExpr::List(l) => parse_list_var(l, &HashMap::default()),
-                        };
-                        match expr {
-                            SExpr::Atom(_) => true,
-                            SExpr::List(_) => false,
-                        }
-                    });
-
-                    while evaluate_conditionals(&mut expanded_template)? {}
+                    evaluate_content(&mut expanded_template)?;
 
                     replacements.push(Replacement {
                         insert_index: expr_index,
@@ -332,6 +329,156 @@ fn expand(exprs: &mut Vec<SExpr>, templates: &[Template], _lsp_hints: &mut LspHi
     Ok(())
 }
 
+/// Expand `(template-for <var> <values> <content>...)` into a copy of the content for every
+/// value, with `$<var>` substituted by the value. The values are either a list or
+/// `(range <start> <end>)`, which counts from start to end inclusive.
+fn expand_for(l: &Spanned<Vec<SExpr>>) -> Result<Vec<SExpr>> {
+    let var =
+        l.t.get(1)
+            .ok_or_else(|| {
+                anyhow_span!(
+                    l,
+                    "template-for must have a variable name as the first parameter"
+                )
+            })
+            .and_then(|var| {
+                var.atom(None)
+                    .ok_or_else(|| anyhow_expr!(var, "template-for variable name must be a string"))
+            })?;
+    let var = format!("${var}");
+    let mut values_expr =
+        l.t.get(2)
+            .ok_or_else(|| {
+                anyhow_span!(
+                    l,
+                    "template-for must have a list of values as the second parameter"
+                )
+            })?
+            .clone();
+    evaluate_arithmetic(std::slice::from_mut(&mut values_expr))?;
+    let values = values_expr.list(None).ok_or_else(|| {
+        anyhow_expr!(
+            &values_expr,
+            "template-for values must be a list or (range <start> <end>)"
+        )
+    })?;
+    let values = match values.first().and_then(|v| v.atom(None)) {
+        Some("range") => range_values(&values_expr, values)?,
+        _ => values.to_vec(),
+    };
+
+    let mut expanded = vec![];
+    for value in values {
+        let mut content: Vec<SExpr> = l.t.iter().skip(3).cloned().collect();
+        visit_mut_all_atoms(&mut content, &mut |expr: &mut SExpr| {
+            if expr.atom(None) == Some(var.as_str()) {
+                *expr = value.clone();
+            }
+        });
+        evaluate_content(&mut content)?;
+        expanded.extend(content);
+    }
+    Ok(expanded)
+}
+
+fn range_values(range_expr: &SExpr, range: &[SExpr]) -> Result<Vec<SExpr>> {
+    const ERR_MSG: &str = "range expects two integers: (range <start> <end>)";
+    if range.len() != 3 {
+        bail_expr!(range_expr, "{ERR_MSG}");
+    }
+    let bound = |expr: &SExpr| -> Result<i64> {
+        expr.atom(None)
+            .and_then(|a| a.parse().ok())
+            .ok_or_else(|| anyhow_expr!(expr, "{ERR_MSG}"))
+    };
+    let (start, end) = (bound(&range[1])?, bound(&range[2])?);
+    let span = range_expr.span();
+    Ok((start..=end)
+        .map(|n| {
+            SExpr::Atom(Spanned {
+                t: n.to_string(),
+                span: span.clone(),
+            })
+        })
+        .collect())
+}
+
+/// Evaluate arithmetic, string functions and conditionals in content that has had its variables
+/// substituted. Nested `template-for` content is left alone until its own variable is known.
+fn evaluate_content(content: &mut Vec<SExpr>) -> Result<()> {
+    evaluate_arithmetic(content)?;
+    visit_mut_all_lists(content, &mut |expr: &mut SExpr| {
+        *expr = match expr {
+            // Below should not be reached because only lists should be visited
+            SExpr::Atom(_) => unreachable!(),
+            SExpr::List(l) => parse_list_var(l, &HashMap::default()),
+        };
+        match expr {
+            SExpr::Atom(_) => true,
+            SExpr::List(_) => false,
+        }
+    });
+    while evaluate_conditionals(content)? {}
+    Ok(())
+}
+
+fn is_template_for(expr: &SExpr) -> bool {
+    matches!(
+        expr.list(None)
+            .and_then(|l| l.first())
+            .and_then(|first| first.atom(None)),
+        Some("template-for")
+    )
+}
+
+/// Replace `(+ ...)`, `(- ...)` and `(* ...)` lists with their result, innermost first.
+/// Lists whose operands are not all integers are left as-is
+/// because they could be something else, e.g. a chord of the `-` key.
+fn evaluate_arithmetic(exprs: &mut [SExpr]) -> Result<()> {
+    for expr in exprs.iter_mut() {
+        if is_template_for(expr) {
+            continue;
+        }
+        let SExpr::List(l) = expr else {
+            continue;
+        };
+        evaluate_arithmetic(&mut l.t)?;
+        if let Some(result) = arithmetic_result(l)? {
+            let span = l.span.clone();
+            *expr = SExpr::Atom(Spanned {
+                t: result.to_string(),
+                span,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn arithmetic_result(l: &Spanned<Vec<SExpr>>) -> Result<Option<i64>> {
+    let op = match l.t.first().and_then(|op| op.atom(None)) {
+        Some(op @ ("+" | "-" | "*")) if l.t.len() >= 3 => op,
+        _ => return Ok(None),
+    };
+    let Some(operands) =
+        l.t.iter()
+            .skip(1)
+            .map(|n| n.atom(None).and_then(|n| n.parse::<i64>().ok()))
+            .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(None);
+    };
+    operands
+        .iter()
+        .skip(1)
+        .try_fold(operands[0], |acc, &n| match op {
+            "+" => acc.checked_add(n),
+            "-" => acc.checked_sub(n),
+            _ => acc.checked_mul(n),
+        })
+        .map(Some)
+        .ok_or_else(|| anyhow_span!(l, "the result of {op} is too large"))
+}
+
 fn visit_validate_all_atoms(
     exprs: &[SExpr],
     visit: &mut dyn FnMut(&Spanned<String>) -> Result<()>,
@@ -371,7 +518,7 @@ fn visit_mut_all_atoms(exprs: &mut [SExpr], visit: &mut dyn FnMut(&mut SExpr)) {
 fn visit_mut_all_lists(exprs: &mut [SExpr], visit: &mut dyn FnMut(&mut SExpr) -> ChangeOccurred) {
     for expr in exprs {
         loop {
-            if let SExpr::Atom(_) = expr {
+            if matches!(expr, SExpr::Atom(_)) || is_template_for(expr) {
                 break;
             }
             // revisit until change did not happen to the list
@@ -391,7 +538,7 @@ fn evaluate_conditionals(exprs: &mut Vec<SExpr>) -> Result<ChangeOccurred> {
     let mut replacements: Vec<Replacement> = vec![];
     let mut expand_happened = false;
     for (index, expr) in exprs.iter_mut().enumerate() {
-        if matches!(expr, SExpr::Atom(_)) {
+        if matches!(expr, SExpr::Atom(_)) || is_template_for(expr) {
             continue;
         }
         // expr must be a list, visit it
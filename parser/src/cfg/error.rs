@@ -4,7 +4,7 @@
 
 #![allow(unused_assignments)]
 
-use miette::{Diagnostic, NamedSource, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode, SourceSpan};
 use thiserror::Error;
 
 use super::{sexpr::Span, *};
@@ -16,6 +16,9 @@ pub type Result<T> = std::result::Result<T, ParseError>;
 pub struct ParseError {
     pub msg: String,
     pub span: Option<Span>,
+    /// Other independent errors found in the same configuration, after the parser recovered
+    /// from this one.
+    pub related: Box<[ParseError]>,
 }
 
 impl ParseError {
@@ -23,6 +26,7 @@ impl ParseError {
         Self {
             msg: err_msg.as_ref().to_string(),
             span: Some(span),
+            related: Box::default(),
         }
     }
 
@@ -30,6 +34,7 @@ impl ParseError {
         Self {
             msg: err_msg.as_ref().to_string(),
             span: None,
+            related: Box::default(),
         }
     }
 
@@ -40,6 +45,41 @@ impl ParseError {
     pub fn from_spanned<T>(spanned: &Spanned<T>, err_msg: impl AsRef<str>) -> Self {
         Self::new(spanned.span.clone(), err_msg)
     }
+
+    /// Combine errors into one, in order. Returns `None` if there are no errors.
+    pub fn from_errors(errors: impl IntoIterator<Item = ParseError>) -> Option<Self> {
+        let mut errors = errors.into_iter().flat_map(ParseError::into_errors);
+        let mut first = errors.next()?;
+        first.related = errors.collect();
+        Some(first)
+    }
+
+    /// Iterate over this error and all of its related errors.
+    pub fn errors(&self) -> impl Iterator<Item = &ParseError> {
+        std::iter::once(self).chain(self.related.iter())
+    }
+
+    /// Split this error into itself and its related errors, each without related errors.
+    pub fn into_errors(mut self) -> Vec<ParseError> {
+        let related = std::mem::take(&mut self.related);
+        let mut errors = vec![self];
+        errors.extend(
+            Vec::from(related)
+                .into_iter()
+                .flat_map(ParseError::into_errors),
+        );
+        errors
+    }
+}
+
+/// A problem in the configuration that does not prevent it from being used.
+#[derive(Debug, Clone)]
+pub struct ParseWarning {
+    pub msg: String,
+    pub span: Option<Span>,
+    /// The lint that produced this warning, if any. Lints can be silenced with the
+    /// `allow-lints` defcfg option.
+    pub lint: Option<Lint>,
 }
 
 impl From<anyhow::Error> for ParseError {
@@ -50,36 +90,64 @@ impl From<anyhow::Error> for ParseError {
 
 impl From<ParseError> for miette::Error {
     fn from(val: ParseError) -> Self {
-        let diagnostic = CfgError {
+        CfgError::from(val).into()
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Error in configuration")]
+struct CfgError {
+    err_span: Option<SourceSpan>,
+    help_msg: String,
+    source_code: Option<NamedSource>,
+    related: Vec<CfgError>,
+}
+
+impl From<ParseError> for CfgError {
+    fn from(val: ParseError) -> Self {
+        CfgError {
             err_span: val
                 .span
                 .as_ref()
                 .map(|s| SourceSpan::new(s.start().into(), (s.end() - s.start()).into())),
             help_msg: help(val.msg),
-            file_name: val.span.as_ref().map(|s| s.file_name()),
-            file_content: val.span.as_ref().map(|s| s.file_content()),
-        };
-
-        let report: miette::Error = diagnostic.into();
-
-        if let Some(span) = val.span {
-            report.with_source_code(NamedSource::new(span.file_name(), span.file_content()))
-        } else {
-            report
+            source_code: val
+                .span
+                .as_ref()
+                .map(|s| NamedSource::new(s.file_name(), s.file_content())),
+            related: Vec::from(val.related)
+                .into_iter()
+                .map(CfgError::from)
+                .collect(),
         }
     }
 }
 
-#[derive(Error, Debug, Diagnostic, Clone)]
-#[error("Error in configuration")]
-#[diagnostic()]
-struct CfgError {
-    #[label("Error here")]
-    err_span: Option<SourceSpan>,
-    #[help]
-    help_msg: String,
-    file_name: Option<String>,
-    file_content: Option<String>,
+// Implemented by hand because each related error can be in a different file, so each needs its
+// own optional source code, which the derive macro does not support.
+impl Diagnostic for CfgError {
+    fn help<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        Some(Box::new(&self.help_msg))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let span = self.err_span?;
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some("Error here".into()),
+            span,
+        ))))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source_code.as_ref().map(|s| s as &dyn SourceCode)
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        if self.related.is_empty() {
+            return None;
+        }
+        Some(Box::new(self.related.iter().map(|e| e as &dyn Diagnostic)))
+    }
 }
 
 pub(super) fn help(err_msg: impl AsRef<str>) -> String {
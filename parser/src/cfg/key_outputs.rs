@@ -10,6 +10,7 @@ pub(crate) fn create_key_outputs(
     layers: &KLayers,
     overrides: &Overrides,
     chords_v2: &Option<ChordsV2<'static, KanataCustom>>,
+    auto_shift: &Option<AutoShift<'static, KanataCustom>>,
 ) -> KeyOutputs {
     let mut outs = KeyOutputs::new();
     for (layer_idx, layer) in layers.iter().enumerate() {
@@ -20,6 +21,16 @@ pub(crate) fn create_key_outputs(
                 Err(_) => continue,
             };
             add_key_output_from_action_to_key_pos(osc_slot, action, &mut layer_outputs, overrides);
+            if auto_shift
+                .as_ref()
+                .is_some_and(|auto_shift| auto_shift.action_for(action).is_some())
+            {
+                // Shift goes first so that the key itself is checked first for repeat.
+                let outputs: &mut Vec<OsCode> = layer_outputs.entry(osc_slot).or_default();
+                if !outputs.contains(&OsCode::KEY_LEFTSHIFT) {
+                    outputs.insert(0, OsCode::KEY_LEFTSHIFT);
+                }
+            }
             add_chordsv2_output_for_key_pos(
                 osc_slot,
                 layer_idx,
@@ -127,6 +138,7 @@ pub(crate) fn add_key_output_from_action_to_key_pos(
         Action::NoOp
         | Action::Trans
         | Action::Repeat
+        | Action::LayerLock
         | Action::Layer(_)
         | Action::DefaultLayer(_)
         | Action::Sequence { .. }
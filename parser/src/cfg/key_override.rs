@@ -216,6 +216,20 @@ impl Override {
         Ok(override_cfg)
     }
 
+    /// Returns true if this override applies whenever `other` does. Since the last matching
+    /// override is used, `other` has no effect if it comes before this one.
+    pub(crate) fn covers(&self, other: &Override) -> bool {
+        let excluded_layers_covered = match (&self.excluded_layers, &other.excluded_layers) {
+            (None, _) => true,
+            (Some(layers), Some(other_layers)) => layers.iter().all(|l| other_layers.contains(l)),
+            (Some(layers), None) => layers.is_empty(),
+        };
+        self.in_non_mod_osc == other.in_non_mod_osc
+            && self.get_mod_mask() & other.get_mod_mask() == self.get_mod_mask()
+            && self.get_excluded_mod_mask() & !other.get_excluded_mod_mask() == 0
+            && excluded_layers_covered
+    }
+
     fn get_mod_mask(&self) -> u8 {
         let mut mask = 0;
         for osc in self.in_mod_oscs.iter().copied() {
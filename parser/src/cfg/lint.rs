@@ -0,0 +1,469 @@
+//! Lints: warnings for configuration that is valid but probably not what was intended.
+//!
+//! The lints for unused definitions only check whether a name is written anywhere else in the
+//! configuration. Definitions that are only used from outside of it, e.g. virtual keys that are
+//! pressed over TCP, are reported too. Lints can be silenced with the `allow-lints` defcfg option.
+
+use super::*;
+
+/// A kind of lint warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    UnusedAlias,
+    UnusedVirtualKey,
+    UnusedTemplate,
+    UnusedVariable,
+    UnreachableLayer,
+    TransparentLayer,
+    UnmappedChordKey,
+    HomeRowTimeouts,
+    ShadowedOverride,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 9] = [
+        Lint::UnusedAlias,
+        Lint::UnusedVirtualKey,
+        Lint::UnusedTemplate,
+        Lint::UnusedVariable,
+        Lint::UnreachableLayer,
+        Lint::TransparentLayer,
+        Lint::UnmappedChordKey,
+        Lint::HomeRowTimeouts,
+        Lint::ShadowedOverride,
+    ];
+
+    /// The name of the lint, as used in `allow-lints`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedAlias => "unused-alias",
+            Lint::UnusedVirtualKey => "unused-virtual-key",
+            Lint::UnusedTemplate => "unused-template",
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnreachableLayer => "unreachable-layer",
+            Lint::TransparentLayer => "transparent-layer",
+            Lint::UnmappedChordKey => "unmapped-chord-key",
+            Lint::HomeRowTimeouts => "home-row-timeouts",
+            Lint::ShadowedOverride => "shadowed-override",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+/// Count how often each atom is written in the configuration. This should be done before the
+/// platform and environment specific parts are filtered out, so that names used only in those
+/// parts still count as used.
+pub(crate) fn count_atoms(exprs: &[TopLevel]) -> HashMap<String, usize> {
+    fn count(expr: &SExpr, counts: &mut HashMap<String, usize>) {
+        match expr {
+            SExpr::Atom(a) => *counts.entry(a.t.clone()).or_default() += 1,
+            SExpr::List(l) => l.t.iter().for_each(|expr| count(expr, counts)),
+        }
+    }
+    let mut counts = HashMap::default();
+    for expr in exprs.iter().flat_map(|tl| tl.t.iter()) {
+        count(expr, &mut counts);
+    }
+    counts
+}
+
+/// Check a successfully parsed configuration for likely mistakes. Lints that are allowed in
+/// defcfg are skipped.
+pub(crate) fn lint_cfg(
+    exprs: &[TopLevel],
+    atom_counts: &HashMap<String, usize>,
+    cfg: &CfgOptions,
+    mapped_keys: &MappedKeys,
+    overrides: &[Spanned<Override>],
+    s: &ParserState,
+) -> Vec<ParseWarning> {
+    let mut linter = Linter {
+        allowed: &cfg.allowed_lints,
+        warnings: vec![],
+    };
+    unused_definitions(&mut linter, exprs, atom_counts, cfg);
+    unreachable_layers(&mut linter, exprs, cfg, s);
+    transparent_layers(&mut linter, exprs, s);
+    unmapped_chord_keys(&mut linter, exprs, mapped_keys, s);
+    home_row_timeouts(&mut linter, exprs, s);
+    shadowed_overrides(&mut linter, overrides);
+    linter.warnings
+}
+
+struct Linter<'a> {
+    allowed: &'a [Lint],
+    warnings: Vec<ParseWarning>,
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, lint: Lint, span: Span, msg: String) {
+        if self.allowed.contains(&lint) {
+            return;
+        }
+        log::warn!(
+            "{}:{}: {msg} [{}]",
+            span.file_name,
+            span.start.line + 1,
+            lint.name()
+        );
+        self.warnings.push(ParseWarning {
+            msg,
+            span: Some(span),
+            lint: Some(lint),
+        });
+    }
+}
+
+fn top_levels<'a>(
+    exprs: &'a [TopLevel],
+    names: &'a [&str],
+) -> impl Iterator<Item = &'a TopLevel> + 'a {
+    exprs.iter().filter(move |tl| {
+        tl.t.first()
+            .and_then(|expr| expr.atom(None))
+            .is_some_and(|name| names.contains(&name))
+    })
+}
+
+/// The `(name value)` pairs of lists like `defalias`, which start after `skip` items.
+fn definitions<'a>(
+    exprs: &'a [TopLevel],
+    def: &'a str,
+    skip: usize,
+) -> impl Iterator<Item = (&'a Spanned<String>, &'a SExpr)> + 'a {
+    exprs
+        .iter()
+        .filter(move |tl| tl.t.first().and_then(|expr| expr.atom(None)) == Some(def))
+        .flat_map(move |tl| {
+            tl.t.get(skip..)
+                .unwrap_or_default()
+                .chunks_exact(2)
+                .filter_map(|pair| match &pair[0] {
+                    SExpr::Atom(name) => Some((name, &pair[1])),
+                    SExpr::List(_) => None,
+                })
+        })
+}
+
+fn unused_definitions(
+    linter: &mut Linter,
+    exprs: &[TopLevel],
+    atom_counts: &HashMap<String, usize>,
+    cfg: &CfgOptions,
+) {
+    // Names that are given as template parameters are only seen where they are used once the
+    // templates are expanded.
+    let expanded_counts = count_atoms(exprs);
+    let count = |atom: &str| {
+        std::cmp::max(
+            atom_counts.get(atom).copied().unwrap_or(0),
+            expanded_counts.get(atom).copied().unwrap_or(0),
+        )
+    };
+    for (def, skip) in [("defalias", 1), ("defaliasenvcond", 2)] {
+        for (name, _) in definitions(exprs, def, skip) {
+            if count(&format!("@{}", name.t)) == 0
+                && cfg.start_alias.as_deref() != Some(name.t.as_str())
+            {
+                linter.warn(
+                    Lint::UnusedAlias,
+                    name.span.clone(),
+                    format!("Alias {} is never used.", name.t),
+                );
+            }
+        }
+    }
+    for (name, _) in definitions(exprs, "defvar", 1) {
+        if count(&format!("${}", name.t)) == 0 {
+            linter.warn(
+                Lint::UnusedVariable,
+                name.span.clone(),
+                format!("Variable {} is never used.", name.t),
+            );
+        }
+    }
+    for def in ["defvirtualkeys", "deffakekeys"] {
+        for (name, _) in definitions(exprs, def, 1) {
+            // The definition itself is one occurrence of the name.
+            if count(&name.t) <= 1 {
+                linter.warn(
+                    Lint::UnusedVirtualKey,
+                    name.span.clone(),
+                    format!("Virtual key {} is never used.", name.t),
+                );
+            }
+        }
+    }
+    for tl in top_levels(exprs, &["deftemplate"]) {
+        if let Some(SExpr::Atom(name)) = tl.t.get(1) {
+            if count(&name.t) <= 1 {
+                linter.warn(
+                    Lint::UnusedTemplate,
+                    name.span.clone(),
+                    format!("Template {} is never used.", name.t),
+                );
+            }
+        }
+    }
+}
+
+fn layer_name<'a>(layer: &'a TopLevel, s: &'a ParserState) -> Option<&'a str> {
+    let name = layer.t.get(1)?;
+    name.atom(s.vars())
+        .or_else(|| name.list(s.vars())?.first()?.atom(s.vars()))
+}
+
+/// Layers are reachable from the first layer, and from top-level items other than layers, e.g.
+/// chords and virtual keys, which are assumed to be usable from any layer.
+fn unreachable_layers<'a>(
+    linter: &mut Linter,
+    exprs: &'a [TopLevel],
+    cfg: &'a CfgOptions,
+    s: &'a ParserState,
+) {
+    const NOT_ROOTS: &[&str] = &[
+        DEFLAYER,
+        DEFLAYER_MAPPED,
+        "defalias",
+        "defaliasenvcond",
+        "defvar",
+        "deftemplate",
+        DEFLAYERCONDITIONS,
+    ];
+    let layers = top_levels(exprs, &[DEFLAYER, DEFLAYER_MAPPED])
+        .filter_map(|tl| Some((layer_name(tl, s)?, tl)))
+        .collect::<Vec<_>>();
+    let Some((base_layer, _)) = layers.first() else {
+        return;
+    };
+    let aliases: HashMap<&str, &SExpr> = definitions(exprs, "defalias", 1)
+        .chain(definitions(exprs, "defaliasenvcond", 2))
+        .map(|(name, action)| (name.t.as_str(), action))
+        .collect();
+    let layer_refs = |items: &'a [SExpr]| {
+        let mut found = vec![];
+        let mut followed = HashSet::default();
+        for item in items {
+            activated_layers(item, &aliases, s, &mut followed, &mut found);
+        }
+        found
+    };
+
+    let mut queue = vec![*base_layer];
+    for tl in exprs.iter().filter(|tl| {
+        !tl.t
+            .first()
+            .and_then(|expr| expr.atom(None))
+            .is_some_and(|name| NOT_ROOTS.contains(&name))
+    }) {
+        queue.extend(layer_refs(&tl.t));
+    }
+    if let Some(action) = cfg.start_alias.as_deref().and_then(|a| aliases.get(a)) {
+        queue.extend(layer_refs(std::slice::from_ref(*action)));
+    }
+    // A conditional layer is reachable once all of the layers it depends on are.
+    let conditions = top_levels(exprs, &[DEFLAYERCONDITIONS])
+        .flat_map(|tl| tl.t[1..].chunks_exact(3))
+        .filter_map(|cond| {
+            let if_active = cond[0]
+                .list(s.vars())?
+                .iter()
+                .map(|layer| layer.atom(s.vars()))
+                .collect::<Option<Vec<_>>>()?;
+            Some((if_active, cond[2].atom(s.vars())?))
+        })
+        .collect::<Vec<_>>();
+    let mut reachable = HashSet::default();
+    while !queue.is_empty() {
+        while let Some(name) = queue.pop() {
+            if !reachable.insert(name) {
+                continue;
+            }
+            if let Some((_, layer)) = layers.iter().find(|(layer_name, _)| *layer_name == name) {
+                queue.extend(layer_refs(&layer.t[2..]));
+            }
+        }
+        queue.extend(
+            conditions
+                .iter()
+                .filter(|(if_active, then)| {
+                    !reachable.contains(then) && if_active.iter().all(|l| reachable.contains(l))
+                })
+                .map(|(_, then)| *then),
+        );
+    }
+
+    for (name, layer) in layers.iter() {
+        if !reachable.contains(name) {
+            linter.warn(
+                Lint::UnreachableLayer,
+                layer.t[1].span(),
+                format!(
+                    "Layer {name} is never activated: no action reachable from the first layer \
+                     switches to it."
+                ),
+            );
+        }
+    }
+}
+
+/// Collect the names of layers that `expr` activates, following aliases and variables.
+fn activated_layers<'a>(
+    expr: &'a SExpr,
+    aliases: &HashMap<&str, &'a SExpr>,
+    s: &'a ParserState,
+    followed: &mut HashSet<&'a str>,
+    found: &mut Vec<&'a str>,
+) {
+    match expr {
+        SExpr::Atom(a) => {
+            let target = if let Some(alias) = a.t.strip_prefix('@') {
+                aliases.get(alias).copied()
+            } else if let Some(var) = a.t.strip_prefix('$') {
+                s.vars.get(var)
+            } else {
+                None
+            };
+            if let Some(target) = target {
+                if followed.insert(&a.t) {
+                    activated_layers(target, aliases, s, followed, found);
+                }
+            }
+        }
+        SExpr::List(l) => {
+            if let [SExpr::Atom(action), layer, ..] = l.t.as_slice() {
+                if [LAYER_SWITCH, LAYER_TOGGLE, LAYER_WHILE_HELD, LAYER_WORD]
+                    .contains(&action.t.as_str())
+                {
+                    if let Some(layer) = layer.atom(s.vars()) {
+                        found.push(layer);
+                    }
+                }
+            }
+            for expr in l.t.iter() {
+                activated_layers(expr, aliases, s, followed, found);
+            }
+        }
+    }
+}
+
+fn transparent_layers(linter: &mut Linter, exprs: &[TopLevel], s: &ParserState) {
+    for layer in top_levels(exprs, &[DEFLAYER]) {
+        let keys = layer.t.get(2..).unwrap_or_default();
+        let all_transparent = !keys.is_empty()
+            && keys.iter().all(|key| {
+                key.atom(s.vars())
+                    .is_some_and(|key| matches!(key, "_" | "‗" | "≝"))
+            });
+        if all_transparent {
+            let name = layer_name(layer, s).unwrap_or_default();
+            linter.warn(
+                Lint::TransparentLayer,
+                layer.t[1].span(),
+                format!("Every key in layer {name} is transparent, so activating it does nothing."),
+            );
+        }
+    }
+}
+
+fn unmapped_chord_keys(
+    linter: &mut Linter,
+    exprs: &[TopLevel],
+    mapped_keys: &MappedKeys,
+    s: &ParserState,
+) {
+    for chords in top_levels(exprs, &["defchordsv2", "defchordsv2-experimental"]) {
+        for chord in chords.t[1..].chunks_exact(5) {
+            let Some(keys) = chord[0].list(s.vars()) else {
+                continue;
+            };
+            if keys.first().and_then(|key| key.atom(None)) == Some("include") {
+                continue;
+            }
+            for key in keys {
+                let Some(name) = key.atom(s.vars()) else {
+                    continue;
+                };
+                if str_to_oscode(name).is_some_and(|osc| !mapped_keys.contains(&osc)) {
+                    linter.warn(
+                        Lint::UnmappedChordKey,
+                        key.span(),
+                        format!(
+                            "Chord key {name} is not in defsrc, so kanata does not process it \
+                             and the chord can never activate."
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Home row keys with tap-hold actions are usually meant to behave the same, so a timeout that is
+/// more than twice another one in the same layer is likely a typo.
+fn home_row_timeouts(linter: &mut Linter, exprs: &[TopLevel], s: &ParserState) {
+    const HOME_ROW: [(&str, OsCode); 8] = [
+        ("a", OsCode::KEY_A),
+        ("s", OsCode::KEY_S),
+        ("d", OsCode::KEY_D),
+        ("f", OsCode::KEY_F),
+        ("j", OsCode::KEY_J),
+        ("k", OsCode::KEY_K),
+        ("l", OsCode::KEY_L),
+        (";", OsCode::KEY_SEMICOLON),
+    ];
+    let layers = top_levels(exprs, &[DEFLAYER, DEFLAYER_MAPPED]);
+    for (actions, layer) in s.layers.iter().zip(layers) {
+        let timeouts = HOME_ROW
+            .iter()
+            .filter_map(|(key, osc)| match actions[0][osc.as_u16() as usize] {
+                Action::HoldTap(ht) => Some((*key, ht.timeout)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let (Some(min), Some(max)) = (
+            timeouts.iter().map(|(_, t)| *t).min(),
+            timeouts.iter().map(|(_, t)| *t).max(),
+        ) else {
+            continue;
+        };
+        if u32::from(max) > 2 * u32::from(min) {
+            let name = layer_name(layer, s).unwrap_or_default();
+            let timeouts = timeouts
+                .iter()
+                .map(|(key, t)| format!("{key}: {t}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            linter.warn(
+                Lint::HomeRowTimeouts,
+                layer.t[1].span(),
+                format!(
+                    "Tap-hold timeouts of home row keys in layer {name} differ widely.\n\
+                     Timeouts in ms: {timeouts}"
+                ),
+            );
+        }
+    }
+}
+
+fn shadowed_overrides(linter: &mut Linter, overrides: &[Spanned<Override>]) {
+    for (i, earlier) in overrides.iter().enumerate() {
+        if let Some(later) = overrides[i + 1..]
+            .iter()
+            .find(|later| later.t.covers(&earlier.t))
+        {
+            linter.warn(
+                Lint::ShadowedOverride,
+                earlier.span.clone(),
+                format!(
+                    "This override is never used, because the override on line {} \
+                     applies whenever it would and takes precedence.",
+                    later.span.start.line + 1
+                ),
+            );
+        }
+    }
+}
@@ -50,6 +50,7 @@ pub const ONE_SHOT_RELEASE_PCANCEL_A: &str = "one-shot↑⤫";
 pub const ONE_SHOT_PAUSE_PROCESSING: &str = "one-shot-pause-processing";
 pub const TAP_DANCE: &str = "tap-dance";
 pub const TAP_DANCE_EAGER: &str = "tap-dance-eager";
+pub const TAP_DANCE_HOLD: &str = "tap-dance-hold";
 pub const CHORD: &str = "chord";
 pub const RELEASE_KEY: &str = "release-key";
 pub const RELEASE_KEY_A: &str = "key↑";
@@ -114,6 +115,11 @@ pub const CAPS_WORD_TOGGLE: &str = "caps-word-toggle";
 pub const CAPS_WORD_TOGGLE_A: &str = "word⇪toggle";
 pub const CAPS_WORD_CUSTOM_TOGGLE: &str = "caps-word-custom-toggle";
 pub const CAPS_WORD_CUSTOM_TOGGLE_A: &str = "word⇪custom-toggle";
+pub const CASE_WORD: &str = "case-word";
+pub const CASE_WORD_CUSTOM: &str = "case-word-custom";
+pub const CASE_WORD_TOGGLE: &str = "case-word-toggle";
+pub const CASE_WORD_CUSTOM_TOGGLE: &str = "case-word-custom-toggle";
+pub const LAYER_WORD: &str = "layer-word";
 pub const DYNAMIC_MACRO_RECORD_STOP_TRUNCATE: &str = "dynamic-macro-record-stop-truncate";
 pub const SWITCH: &str = "switch";
 pub const SEQUENCE: &str = "sequence";
@@ -130,6 +136,7 @@ pub const ON_RELEASE_A: &str = "on↑";
 pub const ON_IDLE: &str = "on-idle";
 pub const ON_PHYSICAL_IDLE: &str = "on-physical-idle";
 pub const HOLD_FOR_DURATION: &str = "hold-for-duration";
+pub const TYPE_TEXT: &str = "type-text";
 pub const CLIPBOARD_SET: &str = "clipboard-set";
 pub const CLIPBOARD_CMD_SET: &str = "clipboard-cmd-set";
 pub const CLIPBOARD_SAVE: &str = "clipboard-save";
@@ -140,145 +147,164 @@ pub const CLIPBOARD_SAVE_SWAP: &str = "clipboard-save-swap";
 pub const TAP_HOLD_ORDER: &str = "tap-hold-order";
 pub const TAP_HOLD_OPPOSITE_HAND: &str = "tap-hold-opposite-hand";
 pub const TAP_HOLD_OPPOSITE_HAND_RELEASE: &str = "tap-hold-opposite-hand-release";
+pub const STATE_SET: &str = "state-set";
+pub const STATE_TOGGLE: &str = "state-toggle";
+pub const STATE_INC: &str = "state-inc";
+pub const STATE_DEC: &str = "state-dec";
+pub const STATE_RESET: &str = "state-reset";
+
+/// All list action names, including the unicode alternatives.
+pub const LIST_ACTIONS: &[&str] = &[
+    LAYER_SWITCH,
+    LAYER_TOGGLE,
+    LAYER_WHILE_HELD,
+    TAP_HOLD,
+    TAP_HOLD_PRESS,
+    TAP_HOLD_PRESS_A,
+    TAP_HOLD_RELEASE,
+    TAP_HOLD_RELEASE_A,
+    TAP_HOLD_PRESS_TIMEOUT,
+    TAP_HOLD_PRESS_TIMEOUT_A,
+    TAP_HOLD_RELEASE_TIMEOUT,
+    TAP_HOLD_RELEASE_TIMEOUT_A,
+    TAP_HOLD_RELEASE_KEYS,
+    TAP_HOLD_RELEASE_KEYS_TAP_RELEASE,
+    TAP_HOLD_RELEASE_KEYS_A,
+    TAP_HOLD_EXCEPT_KEYS,
+    TAP_HOLD_EXCEPT_KEYS_A,
+    TAP_HOLD_TAP_KEYS,
+    TAP_HOLD_TAP_KEYS_A,
+    TAP_HOLD_KEYS,
+    MULTI,
+    MACRO,
+    MACRO_REPEAT,
+    MACRO_REPEAT_A,
+    MACRO_RELEASE_CANCEL,
+    MACRO_RELEASE_CANCEL_A,
+    MACRO_REPEAT_RELEASE_CANCEL,
+    MACRO_REPEAT_RELEASE_CANCEL_A,
+    UNICODE,
+    SYM,
+    ONE_SHOT,
+    ONE_SHOT_PRESS,
+    ONE_SHOT_PRESS_A,
+    ONE_SHOT_RELEASE,
+    ONE_SHOT_RELEASE_A,
+    ONE_SHOT_PRESS_PCANCEL,
+    ONE_SHOT_PRESS_PCANCEL_A,
+    ONE_SHOT_RELEASE_PCANCEL,
+    ONE_SHOT_RELEASE_PCANCEL_A,
+    TAP_DANCE,
+    TAP_DANCE_EAGER,
+    TAP_DANCE_HOLD,
+    CHORD,
+    RELEASE_KEY,
+    RELEASE_KEY_A,
+    RELEASE_LAYER,
+    RELEASE_LAYER_A,
+    ON_PRESS_FAKEKEY,
+    ON_PRESS_FAKEKEY_A,
+    ON_RELEASE_FAKEKEY,
+    ON_RELEASE_FAKEKEY_A,
+    ON_PRESS_DELAY,
+    ON_RELEASE_DELAY,
+    ON_PRESS_FAKEKEY_DELAY,
+    ON_PRESS_FAKEKEY_DELAY_A,
+    ON_RELEASE_FAKEKEY_DELAY,
+    ON_RELEASE_FAKEKEY_DELAY_A,
+    ON_IDLE_FAKEKEY,
+    MWHEEL_UP,
+    MWHEEL_UP_A,
+    MWHEEL_DOWN,
+    MWHEEL_DOWN_A,
+    MWHEEL_LEFT,
+    MWHEEL_LEFT_A,
+    MWHEEL_RIGHT,
+    MWHEEL_RIGHT_A,
+    MWHEEL_ACCEL_UP,
+    MWHEEL_ACCEL_DOWN,
+    MWHEEL_ACCEL_LEFT,
+    MWHEEL_ACCEL_RIGHT,
+    MOVEMOUSE_UP,
+    MOVEMOUSE_UP_A,
+    MOVEMOUSE_DOWN,
+    MOVEMOUSE_DOWN_A,
+    MOVEMOUSE_LEFT,
+    MOVEMOUSE_LEFT_A,
+    MOVEMOUSE_RIGHT,
+    MOVEMOUSE_RIGHT_A,
+    MOVEMOUSE_ACCEL_UP,
+    MOVEMOUSE_ACCEL_UP_A,
+    MOVEMOUSE_ACCEL_DOWN,
+    MOVEMOUSE_ACCEL_DOWN_A,
+    MOVEMOUSE_ACCEL_LEFT,
+    MOVEMOUSE_ACCEL_LEFT_A,
+    MOVEMOUSE_ACCEL_RIGHT,
+    MOVEMOUSE_ACCEL_RIGHT_A,
+    MOVEMOUSE_SPEED,
+    MOVEMOUSE_SPEED_A,
+    SETMOUSE,
+    SETMOUSE_A,
+    DYNAMIC_MACRO_RECORD,
+    DYNAMIC_MACRO_PLAY,
+    ARBITRARY_CODE,
+    CMD,
+    CMD_OUTPUT_KEYS,
+    CMD_LOG,
+    PUSH_MESSAGE,
+    FORK,
+    CAPS_WORD,
+    CAPS_WORD_A,
+    CAPS_WORD_TOGGLE,
+    CAPS_WORD_TOGGLE_A,
+    CAPS_WORD_CUSTOM,
+    CAPS_WORD_CUSTOM_A,
+    CAPS_WORD_CUSTOM_TOGGLE,
+    CAPS_WORD_CUSTOM_TOGGLE_A,
+    CASE_WORD,
+    CASE_WORD_CUSTOM,
+    CASE_WORD_TOGGLE,
+    CASE_WORD_CUSTOM_TOGGLE,
+    LAYER_WORD,
+    DYNAMIC_MACRO_RECORD_STOP_TRUNCATE,
+    SWITCH,
+    SEQUENCE,
+    SEQUENCE_NOERASE,
+    UNMOD,
+    UNSHIFT,
+    UNSHIFT_A,
+    LIVE_RELOAD_NUM,
+    LIVE_RELOAD_FILE,
+    ON_PRESS,
+    ON_PRESS_A,
+    ON_RELEASE,
+    ON_RELEASE_A,
+    ON_IDLE,
+    ON_PHYSICAL_IDLE,
+    HOLD_FOR_DURATION,
+    MACRO_CANCEL_ON_NEXT_PRESS,
+    MACRO_REPEAT_CANCEL_ON_NEXT_PRESS,
+    MACRO_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
+    MACRO_REPEAT_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
+    ONE_SHOT_PAUSE_PROCESSING,
+    TYPE_TEXT,
+    CLIPBOARD_SET,
+    CLIPBOARD_CMD_SET,
+    CLIPBOARD_SAVE,
+    CLIPBOARD_RESTORE,
+    CLIPBOARD_SAVE_SET,
+    CLIPBOARD_SAVE_CMD_SET,
+    CLIPBOARD_SAVE_SWAP,
+    TAP_HOLD_ORDER,
+    TAP_HOLD_OPPOSITE_HAND,
+    TAP_HOLD_OPPOSITE_HAND_RELEASE,
+    STATE_SET,
+    STATE_TOGGLE,
+    STATE_INC,
+    STATE_DEC,
+    STATE_RESET,
+];
 
 pub fn is_list_action(ac: &str) -> bool {
-    const LIST_ACTIONS: &[&str] = &[
-        LAYER_SWITCH,
-        LAYER_TOGGLE,
-        LAYER_WHILE_HELD,
-        TAP_HOLD,
-        TAP_HOLD_PRESS,
-        TAP_HOLD_PRESS_A,
-        TAP_HOLD_RELEASE,
-        TAP_HOLD_RELEASE_A,
-        TAP_HOLD_PRESS_TIMEOUT,
-        TAP_HOLD_PRESS_TIMEOUT_A,
-        TAP_HOLD_RELEASE_TIMEOUT,
-        TAP_HOLD_RELEASE_TIMEOUT_A,
-        TAP_HOLD_RELEASE_KEYS,
-        TAP_HOLD_RELEASE_KEYS_TAP_RELEASE,
-        TAP_HOLD_RELEASE_KEYS_A,
-        TAP_HOLD_EXCEPT_KEYS,
-        TAP_HOLD_EXCEPT_KEYS_A,
-        TAP_HOLD_TAP_KEYS,
-        TAP_HOLD_TAP_KEYS_A,
-        TAP_HOLD_KEYS,
-        MULTI,
-        MACRO,
-        MACRO_REPEAT,
-        MACRO_REPEAT_A,
-        MACRO_RELEASE_CANCEL,
-        MACRO_RELEASE_CANCEL_A,
-        MACRO_REPEAT_RELEASE_CANCEL,
-        MACRO_REPEAT_RELEASE_CANCEL_A,
-        UNICODE,
-        SYM,
-        ONE_SHOT,
-        ONE_SHOT_PRESS,
-        ONE_SHOT_PRESS_A,
-        ONE_SHOT_RELEASE,
-        ONE_SHOT_RELEASE_A,
-        ONE_SHOT_PRESS_PCANCEL,
-        ONE_SHOT_PRESS_PCANCEL_A,
-        ONE_SHOT_RELEASE_PCANCEL,
-        ONE_SHOT_RELEASE_PCANCEL_A,
-        TAP_DANCE,
-        TAP_DANCE_EAGER,
-        CHORD,
-        RELEASE_KEY,
-        RELEASE_KEY_A,
-        RELEASE_LAYER,
-        RELEASE_LAYER_A,
-        ON_PRESS_FAKEKEY,
-        ON_PRESS_FAKEKEY_A,
-        ON_RELEASE_FAKEKEY,
-        ON_RELEASE_FAKEKEY_A,
-        ON_PRESS_DELAY,
-        ON_RELEASE_DELAY,
-        ON_PRESS_FAKEKEY_DELAY,
-        ON_PRESS_FAKEKEY_DELAY_A,
-        ON_RELEASE_FAKEKEY_DELAY,
-        ON_RELEASE_FAKEKEY_DELAY_A,
-        ON_IDLE_FAKEKEY,
-        MWHEEL_UP,
-        MWHEEL_UP_A,
-        MWHEEL_DOWN,
-        MWHEEL_DOWN_A,
-        MWHEEL_LEFT,
-        MWHEEL_LEFT_A,
-        MWHEEL_RIGHT,
-        MWHEEL_RIGHT_A,
-        MWHEEL_ACCEL_UP,
-        MWHEEL_ACCEL_DOWN,
-        MWHEEL_ACCEL_LEFT,
-        MWHEEL_ACCEL_RIGHT,
-        MOVEMOUSE_UP,
-        MOVEMOUSE_UP_A,
-        MOVEMOUSE_DOWN,
-        MOVEMOUSE_DOWN_A,
-        MOVEMOUSE_LEFT,
-        MOVEMOUSE_LEFT_A,
-        MOVEMOUSE_RIGHT,
-        MOVEMOUSE_RIGHT_A,
-        MOVEMOUSE_ACCEL_UP,
-        MOVEMOUSE_ACCEL_UP_A,
-        MOVEMOUSE_ACCEL_DOWN,
-        MOVEMOUSE_ACCEL_DOWN_A,
-        MOVEMOUSE_ACCEL_LEFT,
-        MOVEMOUSE_ACCEL_LEFT_A,
-        MOVEMOUSE_ACCEL_RIGHT,
-        MOVEMOUSE_ACCEL_RIGHT_A,
-        MOVEMOUSE_SPEED,
-        MOVEMOUSE_SPEED_A,
-        SETMOUSE,
-        SETMOUSE_A,
-        DYNAMIC_MACRO_RECORD,
-        DYNAMIC_MACRO_PLAY,
-        ARBITRARY_CODE,
-        CMD,
-        CMD_OUTPUT_KEYS,
-        CMD_LOG,
-        PUSH_MESSAGE,
-        FORK,
-        CAPS_WORD,
-        CAPS_WORD_A,
-        CAPS_WORD_TOGGLE,
-        CAPS_WORD_TOGGLE_A,
-        CAPS_WORD_CUSTOM,
-        CAPS_WORD_CUSTOM_A,
-        CAPS_WORD_CUSTOM_TOGGLE,
-        CAPS_WORD_CUSTOM_TOGGLE_A,
-        DYNAMIC_MACRO_RECORD_STOP_TRUNCATE,
-        SWITCH,
-        SEQUENCE,
-        SEQUENCE_NOERASE,
-        UNMOD,
-        UNSHIFT,
-        UNSHIFT_A,
-        LIVE_RELOAD_NUM,
-        LIVE_RELOAD_FILE,
-        ON_PRESS,
-        ON_PRESS_A,
-        ON_RELEASE,
-        ON_RELEASE_A,
-        ON_IDLE,
-        ON_PHYSICAL_IDLE,
-        HOLD_FOR_DURATION,
-        MACRO_CANCEL_ON_NEXT_PRESS,
-        MACRO_REPEAT_CANCEL_ON_NEXT_PRESS,
-        MACRO_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
-        MACRO_REPEAT_CANCEL_ON_NEXT_PRESS_CANCEL_ON_RELEASE,
-        ONE_SHOT_PAUSE_PROCESSING,
-        CLIPBOARD_SET,
-        CLIPBOARD_CMD_SET,
-        CLIPBOARD_SAVE,
-        CLIPBOARD_RESTORE,
-        CLIPBOARD_SAVE_SET,
-        CLIPBOARD_SAVE_CMD_SET,
-        CLIPBOARD_SAVE_SWAP,
-        TAP_HOLD_ORDER,
-        TAP_HOLD_OPPOSITE_HAND,
-        TAP_HOLD_OPPOSITE_HAND_RELEASE,
-    ];
     LIST_ACTIONS.contains(&ac)
 }
@@ -54,17 +54,38 @@ mod cmd;
 use cmd::*;
 mod custom_tap_hold;
 use custom_tap_hold::*;
+mod defaltrepeat;
+pub use defaltrepeat::AltRepeat;
+use defaltrepeat::*;
+mod defautocorrect;
+pub use defautocorrect::Autocorrect;
+use defautocorrect::*;
+mod defautoshift;
+use defautoshift::*;
+mod defexpand;
+use defexpand::*;
+pub use defexpand::{ExpandChar, Expansions};
 mod defcfg;
 pub use defcfg::*;
 mod definputdevices;
 pub use definputdevices::*;
 mod defhands;
+mod deflayerconditions;
+mod defmirror;
+mod defstate;
 use defhands::{
     parse_defhands, parse_tap_hold_opposite_hand, parse_tap_hold_opposite_hand_release,
 };
+use deflayerconditions::*;
+use defmirror::*;
+pub use defstate::StateVar;
+use defstate::*;
 mod deflocalkeys;
 use deflocalkeys::*;
+mod defoslayout;
+use defoslayout::*;
 mod defsrc;
+pub use defsrc::DefsrcKey;
 use defsrc::*;
 mod deflayer;
 use deflayer::*;
@@ -79,6 +100,8 @@ pub use fake_key::{FAKE_KEY_ROW, NORMAL_KEY_ROW};
 use fork::*;
 mod is_a_button;
 use is_a_button::*;
+mod lint;
+pub use lint::Lint;
 mod live_reload;
 use live_reload::*;
 mod key_outputs;
@@ -309,6 +332,16 @@ pub struct Cfg {
     pub zippy: Option<(ZchPossibleChords, ZchConfig)>,
     /// Input device ID mappings from `definputdevices`.
     pub input_devices: Option<Vec<(std::num::NonZeroU8, InputDeviceMatcher)>>,
+    /// The keys of `defsrc` in order, with where they are written.
+    pub defsrc_keys: Vec<DefsrcKey>,
+    /// State variables from `defstate`, in index order.
+    pub state_vars: Vec<StateVar>,
+    /// Outputs of `alt-repeat` from `defaltrepeat`.
+    pub alt_repeats: Box<[AltRepeat]>,
+    /// Text expansions from `defexpand`.
+    pub expansions: Expansions,
+    /// Typos and their corrections from `defautocorrect`.
+    pub autocorrect: Autocorrect,
 }
 
 /// Parse a new configuration from a file.
@@ -318,9 +351,39 @@ pub fn new_from_file(p: &Path) -> MResult<Cfg> {
 
 pub fn new_from_str(cfg_text: &str, file_content: HashMap<String, String>) -> MResult<Cfg> {
     let mut s = ParserState::default();
-    let icfg = parse_cfg_raw_string(
+    let icfg = parse_cfg_str(cfg_text, file_content, &mut s)?;
+    log::info!("config file is valid");
+    Ok(populate_cfg_with_icfg(icfg, s))
+}
+
+/// Check a configuration file without using it.
+///
+/// Warnings are returned even if there are errors, though there may be fewer of them since
+/// parsing stops at some errors.
+pub fn check_file(p: &Path) -> (Result<()>, Vec<ParseWarning>) {
+    let mut s = ParserState::default();
+    let res = parse_cfg_raw(p, &mut s).map(|_| ());
+    (res, s.warnings.take())
+}
+
+/// Check a configuration string without using it, like [`check_file`].
+pub fn check_str(
+    cfg_text: &str,
+    file_content: HashMap<String, String>,
+) -> (Result<()>, Vec<ParseWarning>) {
+    let mut s = ParserState::default();
+    let res = parse_cfg_str(cfg_text, file_content, &mut s).map(|_| ());
+    (res, s.warnings.take())
+}
+
+fn parse_cfg_str(
+    cfg_text: &str,
+    file_content: HashMap<String, String>,
+    s: &mut ParserState,
+) -> Result<IntermediateCfg> {
+    parse_cfg_raw_string(
         cfg_text,
-        &mut s,
+        s,
         &PathBuf::from("configuration"),
         &mut FileContentProvider {
             get_file_content_fn: &mut move |fname| match file_content
@@ -332,9 +395,31 @@ pub fn new_from_str(cfg_text: &str, file_content: HashMap<String, String>) -> MR
         },
         DEF_LOCAL_KEYS,
         Err("environment variables are not supported".into()),
-    )?;
-    log::info!("config file is valid");
-    Ok(populate_cfg_with_icfg(icfg, s))
+    )
+}
+
+/// Check a configuration for editor tooling.
+///
+/// The content of the main file is passed in directly so that unsaved edits can be checked.
+/// The hints collected for the language server and the warnings are returned even if parsing
+/// fails, though they will be incomplete in that case.
+#[cfg(feature = "lsp")]
+pub fn check_for_lsp(
+    cfg_text: &str,
+    cfg_path: &Path,
+    file_content_provider: &mut FileContentProvider,
+) -> (Result<()>, LspHints, Vec<ParseWarning>) {
+    let mut s = ParserState::default();
+    let res = parse_cfg_raw_string(
+        cfg_text,
+        &mut s,
+        cfg_path,
+        file_content_provider,
+        DEF_LOCAL_KEYS,
+        Ok(std::env::vars().collect()),
+    )
+    .map(|_| ());
+    (res, s.lsp_hints.into_inner(), s.warnings.into_inner())
 }
 
 pub type MappedKeys = HashSet<OsCode>;
@@ -356,7 +441,8 @@ fn parse_cfg(p: &Path) -> MResult<Cfg> {
 
 fn populate_cfg_with_icfg(icfg: IntermediateCfg, s: ParserState) -> Cfg {
     let (layers, allocations) = icfg.klayers.get();
-    let key_outputs = create_key_outputs(&layers, &icfg.overrides, &icfg.chords_v2);
+    let key_outputs =
+        create_key_outputs(&layers, &icfg.overrides, &icfg.chords_v2, &icfg.auto_shift);
     let max_key_timing_check = std::cmp::max(
         s.max_key_timing_check.get(),
         icfg.options.tap_hold_require_prior_idle,
@@ -374,6 +460,16 @@ fn populate_cfg_with_icfg(icfg: IntermediateCfg, s: ParserState) -> Cfg {
     layout.bm().quick_tap_hold_timeout = icfg.options.concurrent_tap_hold;
     layout.bm().tap_hold_require_prior_idle = icfg.options.tap_hold_require_prior_idle;
     layout.bm().oneshot.pause_input_processing_delay = icfg.options.rapid_event_delay;
+    layout.bm().state_vars = icfg.state_vars.iter().map(|v| v.initial).collect();
+    layout.bm().layer_conditions = icfg.layer_conditions;
+    layout.bm().mirror = icfg.mirror;
+    layout.bm().auto_shift = icfg.auto_shift;
+    layout.bm().layer_lock_unlock_keys = icfg
+        .options
+        .layer_lock_unlock_keys
+        .iter()
+        .map(|&key| u16::from(key))
+        .collect();
     if let Some(s) = icfg.start_action {
         layout
             .bm()
@@ -398,6 +494,11 @@ fn populate_cfg_with_icfg(icfg: IntermediateCfg, s: ParserState) -> Cfg {
         max_key_timing_check,
         zippy: icfg.zippy,
         input_devices: s.input_devices,
+        defsrc_keys: icfg.defsrc_keys,
+        state_vars: icfg.state_vars,
+        alt_repeats: icfg.alt_repeats,
+        expansions: icfg.expansions,
+        autocorrect: icfg.autocorrect,
     }
 }
 
@@ -412,6 +513,14 @@ pub struct IntermediateCfg {
     pub chords_v2: Option<ChordsV2<'static, KanataCustom>>,
     pub start_action: Option<&'static KanataAction>,
     pub zippy: Option<(ZchPossibleChords, ZchConfig)>,
+    pub defsrc_keys: Vec<DefsrcKey>,
+    pub state_vars: Vec<StateVar>,
+    pub layer_conditions: Box<[LayerCondition]>,
+    pub mirror: Box<[(u16, u16)]>,
+    pub auto_shift: Option<AutoShift<'static, KanataCustom>>,
+    pub alt_repeats: Box<[AltRepeat]>,
+    pub expansions: Expansions,
+    pub autocorrect: Autocorrect,
 }
 
 // A snapshot of enviroment variables, or an error message with an explanation
@@ -419,7 +528,7 @@ pub struct IntermediateCfg {
 pub type EnvVars = std::result::Result<Vec<(String, String)>, String>;
 
 #[allow(clippy::type_complexity)] // return type is not pub
-fn parse_cfg_raw(p: &Path, s: &mut ParserState) -> MResult<IntermediateCfg> {
+fn parse_cfg_raw(p: &Path, s: &mut ParserState) -> Result<IntermediateCfg> {
     const INVALID_PATH_ERROR: &str = "The provided config file path is not valid";
 
     let mut loaded_files: HashSet<PathBuf> = HashSet::default();
@@ -457,11 +566,11 @@ fn parse_cfg_raw(p: &Path, s: &mut ParserState) -> MResult<IntermediateCfg> {
     // so we need to provide only the name, not the whole path.
     let cfg_file_name: PathBuf = p
         .file_name()
-        .ok_or_else(|| miette::miette!(INVALID_PATH_ERROR))?
+        .ok_or_else(|| ParseError::new_without_span(INVALID_PATH_ERROR))?
         .into();
     let text = file_content_provider
         .get_file_content(&cfg_file_name)
-        .map_err(|e| miette::miette!(e))?;
+        .map_err(ParseError::new_without_span)?;
 
     let env_vars: EnvVars = Ok(std::env::vars().collect());
 
@@ -473,7 +582,6 @@ fn parse_cfg_raw(p: &Path, s: &mut ParserState) -> MResult<IntermediateCfg> {
         DEF_LOCAL_KEYS,
         env_vars,
     )
-    .map_err(|e| e.into())
 }
 
 fn expand_includes(
@@ -536,16 +644,51 @@ pub fn parse_cfg_raw_string(
     file_content_provider: &mut FileContentProvider,
     def_local_keys_variant_to_apply: &str,
     env_vars: EnvVars,
+) -> Result<IntermediateCfg> {
+    s.warnings.get_mut().clear();
+    let res = parse_cfg_raw_string_impl(
+        text,
+        s,
+        cfg_path,
+        file_content_provider,
+        def_local_keys_variant_to_apply,
+        env_vars,
+    );
+    // Errors from independent top-level items are collected while parsing continues. Report all
+    // of them, in the order they were found, along with the error that stopped parsing if any.
+    let recovered = s.recovered_errors.take();
+    match res {
+        Ok(icfg) => match ParseError::from_errors(recovered) {
+            Some(e) => Err(e),
+            None => Ok(icfg),
+        },
+        Err(e) => {
+            Err(ParseError::from_errors(recovered.into_iter().chain([e]))
+                .expect("at least one error"))
+        }
+    }
+}
+
+fn parse_cfg_raw_string_impl(
+    text: &str,
+    s: &mut ParserState,
+    cfg_path: &Path,
+    file_content_provider: &mut FileContentProvider,
+    def_local_keys_variant_to_apply: &str,
+    env_vars: EnvVars,
 ) -> Result<IntermediateCfg> {
     let mut lsp_hints: LspHints = Default::default();
 
     let spanned_root_exprs = sexpr::parse(text, &cfg_path.to_string_lossy())
-        .and_then(|xs| expand_includes(xs, file_content_provider, &mut lsp_hints))
-        .and_then(|xs| {
-            filter_platform_specific_cfg(xs, def_local_keys_variant_to_apply, &mut lsp_hints)
-        })
-        .and_then(|xs| filter_env_specific_cfg(xs, &env_vars, &mut lsp_hints))
-        .and_then(|xs| expand_templates(xs, &mut lsp_hints))?;
+        .and_then(|xs| expand_includes(xs, file_content_provider, &mut lsp_hints))?;
+    let atom_counts = lint::count_atoms(&spanned_root_exprs);
+    let spanned_root_exprs = filter_platform_specific_cfg(
+        spanned_root_exprs,
+        def_local_keys_variant_to_apply,
+        &mut lsp_hints,
+    )
+    .and_then(|xs| filter_env_specific_cfg(xs, &env_vars, &mut lsp_hints))
+    .and_then(|xs| expand_templates(xs, &mut lsp_hints))?;
 
     if let Some(spanned) = spanned_root_exprs
         .iter()
@@ -608,10 +751,14 @@ pub fn parse_cfg_raw_string(
     let mut cfg = root_exprs
         .iter()
         .find(gen_first_atom_filter("defcfg"))
-        .map(|cfg| parse_defcfg(cfg))
-        .transpose()?
+        .map(|cfg| {
+            let (cfg, errors, warnings) = parse_defcfg_recovering(cfg);
+            s.recovered_errors.get_mut().extend(errors);
+            s.warnings.get_mut().extend(warnings);
+            cfg
+        })
         .unwrap_or_else(|| {
-            log::warn!("No defcfg is defined. Consider whether the process-unmapped-keys defcfg option should be yes vs. no. Adding defcfg with process-unmapped-keys defined will remove this warning.");
+            s.warn(None, "No defcfg is defined. Consider whether the process-unmapped-keys defcfg option should be yes vs. no. Adding defcfg with process-unmapped-keys defined will remove this warning.");
             Default::default()
         });
     if let Some(spanned) = spanned_root_exprs
@@ -639,6 +786,7 @@ pub fn parse_cfg_raw_string(
         )
     }
     let (mut mapped_keys, mapping_order) = parse_defsrc(src_expr, &cfg)?;
+    let defsrc_keys = parse_defsrc_keys(src_expr);
 
     if let Some(spanned) = spanned_root_exprs
         .iter()
@@ -771,6 +919,8 @@ pub fn parse_cfg_raw_string(
         default_sequence_input_mode: cfg.sequence_input_mode,
         block_unmapped_keys: cfg.block_unmapped_keys,
         lsp_hints: RefCell::new(lsp_hints),
+        recovered_errors: RefCell::new(s.recovered_errors.take()),
+        warnings: RefCell::new(s.warnings.take()),
         vars,
         max_key_timing_check: Cell::new(cfg.rapid_event_delay),
         input_devices,
@@ -800,6 +950,85 @@ pub fn parse_cfg_raw_string(
         }
     }
 
+    let defmirror_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFMIRROR))
+        .collect::<Vec<_>>();
+    match defmirror_exprs.len() {
+        0 => {}
+        1 => s.mirror = parse_defmirror(&defmirror_exprs[0].t, s)?,
+        _ => bail_span!(
+            defmirror_exprs[1],
+            "Only one defmirror block is allowed, found more. Delete the extras."
+        ),
+    }
+
+    let defoslayout_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFOSLAYOUT))
+        .collect::<Vec<_>>();
+    match defoslayout_exprs.len() {
+        0 => {}
+        1 => s.os_layout = parse_defoslayout(&defoslayout_exprs[0].t, s)?,
+        _ => bail_span!(
+            defoslayout_exprs[1],
+            "Only one defoslayout block is allowed, found more. Delete the extras."
+        ),
+    }
+
+    let defaltrepeat_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFALTREPEAT))
+        .collect::<Vec<_>>();
+    match defaltrepeat_exprs.len() {
+        0 => {}
+        1 => s.alt_repeats = parse_defaltrepeat(&defaltrepeat_exprs[0].t, s)?,
+        _ => bail_span!(
+            defaltrepeat_exprs[1],
+            "Only one defaltrepeat block is allowed, found more. Delete the extras."
+        ),
+    }
+
+    let defautocorrect_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFAUTOCORRECT))
+        .collect::<Vec<_>>();
+    let autocorrect = match defautocorrect_exprs.len() {
+        0 => Autocorrect::default(),
+        1 => parse_defautocorrect(&defautocorrect_exprs[0].t, s, file_content_provider)?,
+        _ => bail_span!(
+            defautocorrect_exprs[1],
+            "Only one defautocorrect block is allowed, found more. Delete the extras."
+        ),
+    };
+    s.autocorrect_defined = !autocorrect.is_empty();
+
+    let defautoshift_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFAUTOSHIFT))
+        .collect::<Vec<_>>();
+    let auto_shift = match defautoshift_exprs.len() {
+        0 => None,
+        1 => Some(parse_defautoshift(&defautoshift_exprs[0].t, s)?),
+        _ => bail_span!(
+            defautoshift_exprs[1],
+            "Only one defautoshift block is allowed, found more. Delete the extras."
+        ),
+    };
+    s.auto_shift_defined = auto_shift.is_some();
+
+    let state_exprs = root_exprs
+        .iter()
+        .filter(gen_first_atom_filter(DEFSTATE))
+        .collect::<Vec<_>>();
+    s.state_vars = parse_defstate(&state_exprs, s)?;
+
+    let layer_conditions_exprs = root_exprs
+        .iter()
+        .filter(gen_first_atom_filter(DEFLAYERCONDITIONS))
+        .collect::<Vec<_>>();
+    let layer_conditions = parse_deflayerconditions(&layer_conditions_exprs, s)?;
+
     let chords_exprs = spanned_root_exprs
         .iter()
         .filter(gen_first_atom_filter_spanned("defchords"))
@@ -863,7 +1092,7 @@ pub fn parse_cfg_raw_string(
         .filter(gen_first_atom_filter("defoverrides"))
         .collect::<Vec<_>>();
     let (overrides, overrides_v1_exists) = match override_exprs.len() {
-        0 => (Overrides::new(&[]), false),
+        0 => (vec![], false),
         1 => (parse_overrides(override_exprs[0], s)?, true),
         _ => {
             let spanned = spanned_root_exprs
@@ -909,6 +1138,13 @@ pub fn parse_cfg_raw_string(
             )
         }
     };
+    let spanned_overrides = overrides;
+    let overrides = Overrides::new(
+        &spanned_overrides
+            .iter()
+            .map(|o| o.t.clone())
+            .collect::<Vec<_>>(),
+    );
 
     let defchordsv2_filter = |exprs: &&Vec<SExpr>| -> bool {
         if exprs.is_empty() {
@@ -991,6 +1227,19 @@ pub fn parse_cfg_raw_string(
         }
     };
 
+    let defexpand_exprs = spanned_root_exprs
+        .iter()
+        .filter(gen_first_atom_filter_spanned(DEFEXPAND))
+        .collect::<Vec<_>>();
+    let expansions = match defexpand_exprs.len() {
+        0 => Expansions::default(),
+        1 => parse_defexpand(&defexpand_exprs[0].t, s, file_content_provider)?,
+        _ => bail_span!(
+            defexpand_exprs[1],
+            "Only one defexpand block is allowed, found more. Delete the extras."
+        ),
+    };
+
     #[cfg(feature = "lsp")]
     LSP_VARIABLE_REFERENCES.with_borrow_mut(|refs| {
         s.lsp_hints
@@ -1001,6 +1250,19 @@ pub fn parse_cfg_raw_string(
             .extend(refs.0.drain());
     });
 
+    // Skip lints while there are errors to fix, since parts of the configuration were skipped.
+    if s.recovered_errors.get_mut().is_empty() {
+        let lints = lint::lint_cfg(
+            &spanned_root_exprs,
+            &atom_counts,
+            &cfg,
+            &mapped_keys,
+            &spanned_overrides,
+            s,
+        );
+        s.warnings.get_mut().extend(lints);
+    }
+
     let klayers = unsafe { KanataLayers::new(layers, s.a.clone()) };
     Ok(IntermediateCfg {
         options: cfg,
@@ -1012,6 +1274,14 @@ pub fn parse_cfg_raw_string(
         chords_v2,
         start_action,
         zippy,
+        defsrc_keys,
+        state_vars: s.state_vars.clone(),
+        layer_conditions,
+        mirror: s.mirror.clone(),
+        auto_shift,
+        alt_repeats: s.alt_repeats.clone(),
+        expansions,
+        autocorrect,
     })
 }
 
@@ -1051,6 +1321,14 @@ fn error_on_unknown_top_level_atoms(exprs: &[Spanned<Vec<SExpr>>]) -> Result<()>
                 | "defzippy-experimental"
                 | "defseq"
                 | "defhands"
+                | DEFSTATE
+                | DEFLAYERCONDITIONS
+                | DEFMIRROR
+                | DEFAUTOSHIFT
+                | DEFALTREPEAT
+                | DEFOSLAYOUT
+                | DEFEXPAND
+                | DEFAUTOCORRECT
                 | "definputdevices" => Ok(()),
                 _ => err_span!(expr, "Found unknown configuration item"),
             })
@@ -1173,7 +1451,16 @@ pub struct ParserState {
     input_devices: Option<Vec<(std::num::NonZeroU8, InputDeviceMatcher)>>,
     pctx: ParserContext,
     pub lsp_hints: RefCell<LspHints>,
+    /// Errors that did not stop parsing. They are reported together once parsing is done.
+    recovered_errors: RefCell<Vec<ParseError>>,
+    warnings: RefCell<Vec<ParseWarning>>,
     hand_map: Option<&'static custom_tap_hold::HandMap>,
+    state_vars: Vec<StateVar>,
+    mirror: Box<[(u16, u16)]>,
+    auto_shift_defined: bool,
+    alt_repeats: Box<[AltRepeat]>,
+    autocorrect_defined: bool,
+    os_layout: HashMap<char, LayoutKey>,
     a: Arc<Allocations>,
 }
 
@@ -1181,6 +1468,27 @@ impl ParserState {
     fn vars(&self) -> Option<&HashMap<String, SExpr>> {
         Some(&self.vars)
     }
+
+    /// Log a warning and keep it, so that tooling can report it along with its location.
+    fn warn(&self, span: Option<Span>, msg: impl AsRef<str>) {
+        log::warn!("{}", msg.as_ref());
+        self.warnings.borrow_mut().push(ParseWarning {
+            msg: msg.as_ref().to_string(),
+            span,
+            lint: None,
+        });
+    }
+
+    /// Record the error, if any, and continue parsing.
+    fn recover<T>(&self, res: Result<T>) -> Option<T> {
+        match res {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.recovered_errors.borrow_mut().push(e);
+                None
+            }
+        }
+    }
 }
 
 impl Default for ParserState {
@@ -1205,7 +1513,15 @@ impl Default for ParserState {
             multi_action_nest_count: Cell::new(0),
             input_devices: None,
             lsp_hints: Default::default(),
+            recovered_errors: Default::default(),
+            warnings: Default::default(),
             hand_map: None,
+            state_vars: Default::default(),
+            mirror: Default::default(),
+            auto_shift_defined: false,
+            alt_repeats: Default::default(),
+            autocorrect_defined: false,
+            os_layout: Default::default(),
             a: unsafe { Allocations::new() },
             pctx: ParserContext::default(),
         }
@@ -1331,7 +1647,11 @@ fn read_alias_name_action_pairs<'a>(
             Some(v) => v,
             None => bail_expr!(alias_expr, "Found alias without an action - add an action"),
         };
-        let action = parse_action(action, s)?;
+        // An alias whose action has an error is still defined, as a no-op, so that its uses
+        // don't cause more errors.
+        let action = s
+            .recover(parse_action(action, s))
+            .unwrap_or_else(|| s.a.sref(Action::NoOp));
         if s.aliases.insert(alias.into(), action).is_some() {
             bail_expr!(alias_expr, "Duplicate alias: {}", alias);
         }
@@ -1444,6 +1764,41 @@ fn parse_action_atom(ac_span: &Spanned<String>, s: &ParserState) -> Result<&'sta
         }
         "rpt" | "repeat" | "rpt-key" => return custom(CustomAction::Repeat, &s.a),
         "rpt-any" => return Ok(s.a.sref(Action::Repeat)),
+        "autocorrect-toggle" | "autocorrect-enable" | "autocorrect-disable" => {
+            if !s.autocorrect_defined {
+                bail_span!(ac_span, "{ac} requires a defautocorrect block");
+            }
+            let mode = match ac {
+                "autocorrect-toggle" => AutocorrectMode::Toggle,
+                "autocorrect-enable" => AutocorrectMode::Enable,
+                _ => AutocorrectMode::Disable,
+            };
+            return custom(CustomAction::Autocorrect(mode), &s.a);
+        }
+        "alt-repeat" | "alt-rpt" => {
+            if s.alt_repeats.is_empty() {
+                bail_span!(ac_span, "{ac} requires a defaltrepeat block");
+            }
+            return custom(CustomAction::AltRepeat, &s.a);
+        }
+        "layer-lock" => return Ok(s.a.sref(Action::LayerLock)),
+        "auto-shift-toggle" => {
+            if !s.auto_shift_defined {
+                bail_span!(ac_span, "{ac} requires a defautoshift block");
+            }
+            return custom(CustomAction::AutoShiftToggle, &s.a);
+        }
+        "swap-hands" | "swap-hands-toggle" | "swap-hands-one-shot" => {
+            if s.mirror.is_empty() {
+                bail_span!(ac_span, "{ac} requires a defmirror block");
+            }
+            let mode = match ac {
+                "swap-hands" => SwapHandsMode::Hold,
+                "swap-hands-toggle" => SwapHandsMode::Toggle,
+                _ => SwapHandsMode::OneShot,
+            };
+            return custom(CustomAction::SwapHands(mode), &s.a);
+        }
         "dynamic-macro-record-stop" => {
             return custom(CustomAction::DynamicMacroRecordStop(0), &s.a);
         }
@@ -1464,7 +1819,8 @@ fn parse_action_atom(ac_span: &Spanned<String>, s: &ParserState) -> Result<&'sta
     };
     if let Some(oscode) = str_to_oscode(ac) {
         if matches!(ac, "comp" | "cmp") {
-            log::warn!(
+            s.warn(
+                Some(ac_span.span.clone()),
                 "comp/cmp/cmps is not actually a compose key even though its correpsonding code is KEY_COMPOSE. Its actual functionality is context menu which somewhat behaves like right-click.\nTo remove this warning, replace this usage with an equivalent key name such as: menu"
             );
         }
@@ -1609,6 +1965,7 @@ fn parse_action_list(ac: &[SExpr], s: &ParserState) -> Result<&'static KanataAct
         ONE_SHOT_PAUSE_PROCESSING => parse_one_shot_pause_processing(&ac[1..], s),
         TAP_DANCE => parse_tap_dance(&ac[1..], s, TapDanceConfig::Lazy),
         TAP_DANCE_EAGER => parse_tap_dance(&ac[1..], s, TapDanceConfig::Eager),
+        TAP_DANCE_HOLD => parse_tap_dance_hold(&ac[1..], s),
         CHORD => parse_chord(&ac[1..], s),
         RELEASE_KEY | RELEASE_KEY_A => parse_release_key(&ac[1..], s),
         RELEASE_LAYER | RELEASE_LAYER_A => parse_release_layer(&ac[1..], s),
@@ -1672,6 +2029,15 @@ fn parse_action_list(ac: &[SExpr], s: &ParserState) -> Result<&'static KanataAct
         CAPS_WORD_CUSTOM_TOGGLE | CAPS_WORD_CUSTOM_TOGGLE_A => {
             parse_caps_word_custom(&ac[1..], CapsWordRepressBehaviour::Toggle, s)
         }
+        CASE_WORD => parse_case_word(&ac[1..], CapsWordRepressBehaviour::Overwrite, s),
+        CASE_WORD_CUSTOM => {
+            parse_case_word_custom(&ac[1..], CapsWordRepressBehaviour::Overwrite, s)
+        }
+        CASE_WORD_TOGGLE => parse_case_word(&ac[1..], CapsWordRepressBehaviour::Toggle, s),
+        CASE_WORD_CUSTOM_TOGGLE => {
+            parse_case_word_custom(&ac[1..], CapsWordRepressBehaviour::Toggle, s)
+        }
+        LAYER_WORD => parse_layer_word(&ac[1..], s),
         DYNAMIC_MACRO_RECORD_STOP_TRUNCATE => parse_macro_record_stop_truncate(&ac[1..], s),
         SWITCH => parse_switch(&ac[1..], s),
         SEQUENCE => parse_sequence_start(&ac[1..], s),
@@ -1680,6 +2046,7 @@ fn parse_action_list(ac: &[SExpr], s: &ParserState) -> Result<&'static KanataAct
         UNSHIFT | UNSHIFT_A => parse_unmod(UNSHIFT, &ac[1..], s),
         LIVE_RELOAD_NUM => parse_live_reload_num(&ac[1..], s),
         LIVE_RELOAD_FILE => parse_live_reload_file(&ac[1..], s),
+        TYPE_TEXT => parse_type_text(&ac[1..], s),
         CLIPBOARD_SET => parse_clipboard_set(&ac[1..], s),
         CLIPBOARD_CMD_SET => parse_cmd(&ac[1..], s, CmdType::ClipboardSet),
         CLIPBOARD_SAVE => parse_clipboard_save(&ac[1..], s),
@@ -1687,6 +2054,11 @@ fn parse_action_list(ac: &[SExpr], s: &ParserState) -> Result<&'static KanataAct
         CLIPBOARD_SAVE_SET => parse_clipboard_save_set(&ac[1..], s),
         CLIPBOARD_SAVE_CMD_SET => parse_cmd(&ac[1..], s, CmdType::ClipboardSaveSet),
         CLIPBOARD_SAVE_SWAP => parse_clipboard_save_swap(&ac[1..], s),
+        STATE_SET => parse_state_set(&ac[1..], s),
+        STATE_TOGGLE => parse_state_toggle(&ac[1..], s),
+        STATE_INC => parse_state_add(&ac[1..], s, STATE_INC),
+        STATE_DEC => parse_state_add(&ac[1..], s, STATE_DEC),
+        STATE_RESET => parse_state_reset(&ac[1..], s),
         _ => unreachable!(),
     }
 }
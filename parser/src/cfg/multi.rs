@@ -49,7 +49,7 @@ pub(crate) fn parse_multi(ac_params: &[SExpr], s: &ParserState) -> Result<&'stat
             matches!(
                 ac,
                 Action::TapDance(TapDance {
-                    config: TapDanceConfig::Lazy,
+                    config: TapDanceConfig::Lazy | TapDanceConfig::LazyHold { .. },
                     ..
                 }) | Action::HoldTap { .. }
                     | Action::Chords { .. }
@@ -3,22 +3,24 @@ use super::*;
 use crate::anyhow_expr;
 use crate::bail_expr;
 
-pub(crate) fn parse_overrides(exprs: &[SExpr], s: &ParserState) -> Result<Overrides> {
+pub(crate) fn parse_overrides(exprs: &[SExpr], s: &ParserState) -> Result<Vec<Spanned<Override>>> {
     const ERR_MSG: &str =
         "defoverrides expects pairs of parameters: <input key list> <output key list>";
     let mut subexprs = check_first_expr(exprs.iter(), "defoverrides")?;
 
-    let mut overrides = Vec::<Override>::new();
+    let mut overrides = Vec::<Spanned<Override>>::new();
     while let Some(in_keys_expr) = subexprs.next() {
         let out_keys_expr = subexprs
             .next()
             .ok_or_else(|| anyhow_expr!(in_keys_expr, "Missing output keys for input keys"))?;
         let (in_keys, out_keys) = parse_override_inout_keys(in_keys_expr, out_keys_expr, s)?;
-        overrides
-            .push(Override::try_new(&in_keys, &out_keys).map_err(|e| anyhow!("{ERR_MSG}: {e}"))?);
+        overrides.push(Spanned::new(
+            Override::try_new(&in_keys, &out_keys).map_err(|e| anyhow!("{ERR_MSG}: {e}"))?,
+            in_keys_expr.span().cover(&out_keys_expr.span()),
+        ));
     }
     log::debug!("All overrides:\n{overrides:#?}");
-    Ok(Overrides::new(&overrides))
+    Ok(overrides)
 }
 
 pub(crate) fn parse_override_inout_keys(
@@ -60,11 +62,14 @@ pub(crate) fn parse_override_inout_keys(
     Ok((in_keys, out_keys))
 }
 
-pub(crate) fn parse_overridesv2(exprs: &[SExpr], s: &ParserState) -> Result<Overrides> {
+pub(crate) fn parse_overridesv2(
+    exprs: &[SExpr],
+    s: &ParserState,
+) -> Result<Vec<Spanned<Override>>> {
     const ERR_MSG: &str = "defoverridesv2 expects 4-tuples of parameters: <input key list> <output key list> <without mods> <excluded layers>";
     let mut subexprs = check_first_expr(exprs.iter(), "defoverridesv2")?;
 
-    let mut overrides = Vec::<Override>::new();
+    let mut overrides = Vec::<Spanned<Override>>::new();
     while let Some(in_keys_expr) = subexprs.next() {
         let out_keys_expr = subexprs
             .next()
@@ -121,7 +126,7 @@ pub(crate) fn parse_overridesv2(exprs: &[SExpr], s: &ParserState) -> Result<Over
             },
         )?;
 
-        overrides.push(
+        overrides.push(Spanned::new(
             Override::try_new_v2(
                 &in_keys,
                 &out_keys,
@@ -129,8 +134,9 @@ pub(crate) fn parse_overridesv2(exprs: &[SExpr], s: &ParserState) -> Result<Over
                 excluded_layers.into(),
             )
             .map_err(|e| anyhow!("{ERR_MSG}: {e}"))?,
-        );
+            in_keys_expr.span().cover(&excluded_layers_expr.span()),
+        ));
     }
     log::debug!("All overrides:\n{overrides:#?}");
-    Ok(Overrides::new(&overrides))
+    Ok(overrides)
 }
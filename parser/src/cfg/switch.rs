@@ -247,6 +247,7 @@ pub fn parse_switch_case_bool(
             BaseLayer,
             DeviceHistory,
             CmdExit,
+            State,
         }
         #[derive(Copy, Clone)]
         enum InputType {
@@ -275,6 +276,7 @@ pub fn parse_switch_case_bool(
                 "base-layer" => Some(AllowedListOps::BaseLayer),
                 "device-history" => Some(AllowedListOps::DeviceHistory),
                 "cmd-exit" => Some(AllowedListOps::CmdExit),
+                "state" => Some(AllowedListOps::State),
                 _ => None,
             })
             .ok_or_else(|| {
@@ -282,7 +284,7 @@ pub fn parse_switch_case_bool(
                     op_expr,
                     "lists inside switch logic must begin with one of:\n\
                     or | and | not | key-history | key-timing\n\
-                    | input | input-history | layer | base-layer | device-history",
+                    | input | input-history | layer | base-layer | device-history | state",
                 )
             })?;
 
@@ -461,6 +463,19 @@ pub fn parse_switch_case_bool(
                 ops.extend(&[op1, op2]);
                 Ok(())
             }
+            AllowedListOps::State => {
+                if l.len() != 3 {
+                    bail_expr!(
+                        op_expr,
+                        "state must have 2 parameters: state-variable, value"
+                    );
+                }
+                let var = state_var_idx(&l[1], s)?;
+                let value = parse_state_value(&l[2], s)?;
+                let (op1, op2) = OpCode::new_state_var(var, value);
+                ops.extend(&[op1, op2]);
+                Ok(())
+            }
             AllowedListOps::CmdExit => {
                 #[cfg(not(feature = "cmd"))]
                 {
@@ -2,6 +2,7 @@ use super::*;
 
 use crate::anyhow_expr;
 use crate::bail;
+use crate::bail_expr;
 
 pub(crate) fn parse_tap_dance(
     ac_params: &[SExpr],
@@ -32,3 +33,49 @@ pub(crate) fn parse_tap_dance(
         config,
     }))))
 }
+
+/// Parse `tap-dance-hold`, a lazy tap-dance where each tap count has a tap and a hold action.
+pub(crate) fn parse_tap_dance_hold(
+    ac_params: &[SExpr],
+    s: &ParserState,
+) -> Result<&'static KanataAction> {
+    const ERR_MSG: &str = "tap-dance-hold expects a timeout (number), a hold timeout (number) \
+         and a list of (tap-action hold-action) pairs";
+    if ac_params.len() != 3 {
+        bail!(ERR_MSG);
+    }
+
+    let timeout = parse_non_zero_u16(&ac_params[0], s, "timeout")?;
+    let hold_timeout = parse_non_zero_u16(&ac_params[1], s, "hold timeout")?;
+    let pairs = ac_params[2]
+        .list(s.vars())
+        .ok_or_else(|| anyhow_expr!(&ac_params[2], "{ERR_MSG}: expected a list"))?;
+    if pairs.is_empty() {
+        bail_expr!(&ac_params[2], "{ERR_MSG}: found an empty list");
+    }
+    let mut actions = Vec::new();
+    for pair_expr in pairs {
+        let pair = pair_expr
+            .list(s.vars())
+            .filter(|pair| pair.len() == 2)
+            .ok_or_else(|| {
+                anyhow_expr!(
+                    pair_expr,
+                    "expected a list of a tap action and a hold action"
+                )
+            })?;
+        let tap = parse_action(&pair[0], s)?;
+        let hold = parse_action(&pair[1], s)?;
+        if matches!(tap, Action::HoldTap { .. }) || matches!(hold, Action::HoldTap { .. }) {
+            bail_expr!(pair_expr, "tap-hold does not work in tap-dance-hold");
+        }
+        actions.push(tap);
+        actions.push(hold);
+    }
+
+    Ok(s.a.sref(Action::TapDance(s.a.sref(TapDance {
+        timeout,
+        actions: s.a.sref_vec(actions),
+        config: TapDanceConfig::LazyHold { hold_timeout },
+    }))))
+}
@@ -8,6 +8,7 @@ use crate::bail_expr;
 #[derive(Default)]
 pub(crate) struct TapHoldOptions {
     pub(crate) require_prior_idle: Option<u16>,
+    pub(crate) adaptive: Option<AdaptiveBounds>,
 }
 
 /// Parse the value of a `(tap-repress-timeout <ms>)` option list.
@@ -46,6 +47,43 @@ pub(crate) fn parse_require_prior_idle_option(
     Ok(prior_idle)
 }
 
+/// Parse the bounds of an `(adaptive <min-timeout> <max-timeout> <min-idle> <max-idle>)` option
+/// list.
+pub(crate) fn parse_adaptive_option(
+    option: &[SExpr],
+    option_expr: &SExpr,
+    s: &ParserState,
+) -> Result<AdaptiveBounds> {
+    if option.len() != 5 {
+        bail_expr!(
+            option_expr,
+            "adaptive option expects exactly 5 items: \
+            `(adaptive <min-timeout> <max-timeout> <min-prior-idle> <max-prior-idle>)`"
+        );
+    }
+    let min_timeout = parse_non_zero_u16(&option[1], s, "adaptive min-timeout")?;
+    let max_timeout = parse_non_zero_u16(&option[2], s, "adaptive max-timeout")?;
+    if max_timeout < min_timeout {
+        bail_expr!(&option[2], "max-timeout must not be less than min-timeout");
+    }
+    let min_prior_idle = parse_u16(&option[3], s, "adaptive min-prior-idle")?;
+    let max_prior_idle = parse_u16(&option[4], s, "adaptive max-prior-idle")?;
+    if max_prior_idle < min_prior_idle {
+        bail_expr!(
+            &option[4],
+            "max-prior-idle must not be less than min-prior-idle"
+        );
+    }
+    s.max_key_timing_check
+        .set(std::cmp::max(max_prior_idle, s.max_key_timing_check.get()));
+    Ok(AdaptiveBounds {
+        min_timeout,
+        max_timeout,
+        min_prior_idle,
+        max_prior_idle,
+    })
+}
+
 /// Parse trailing `(keyword value)` option lists from tap-hold action parameters.
 /// Returns the parsed options. Errors on unknown or duplicate options.
 pub(crate) fn parse_tap_hold_options(
@@ -76,10 +114,13 @@ pub(crate) fn parse_tap_hold_options(
                 opts.require_prior_idle =
                     Some(parse_require_prior_idle_option(option, option_expr, s)?);
             }
+            "adaptive" => {
+                opts.adaptive = Some(parse_adaptive_option(option, option_expr, s)?);
+            }
             _ => bail_expr!(
                 &option[0],
                 "unknown tap-hold option '{}'. \
-                Valid options: require-prior-idle",
+                Valid options: require-prior-idle, adaptive",
                 kw
             ),
         }
@@ -87,9 +128,10 @@ pub(crate) fn parse_tap_hold_options(
     Ok(opts)
 }
 
-const TAP_HOLD_OPTION_KEYWORDS: &[&str] = &["require-prior-idle"];
+const TAP_HOLD_OPTION_KEYWORDS: &[&str] = &["require-prior-idle", "adaptive"];
 const TAP_HOLD_KEYS_OPTION_KEYWORDS: &[&str] = &[
     "require-prior-idle",
+    "adaptive",
     "tap-on-press",
     "tap-on-press-release",
     "hold-on-press",
@@ -146,6 +188,7 @@ Params in order:
         timeout_action: *hold_action,
         on_press_reset_timeout_to: None,
         require_prior_idle: opts.require_prior_idle,
+        adaptive: opts.adaptive,
     }))))
 }
 
@@ -210,6 +253,7 @@ pub(crate) fn parse_tap_hold_timeout(
         timeout_action: *timeout_action,
         on_press_reset_timeout_to,
         require_prior_idle: opts.require_prior_idle,
+        adaptive: opts.adaptive,
     }))))
 }
 
@@ -244,6 +288,7 @@ Params in order:
         timeout_action: *tap_action,
         on_press_reset_timeout_to: None,
         require_prior_idle: opts.require_prior_idle,
+        adaptive: opts.adaptive,
     }))))
 }
 
@@ -282,6 +327,7 @@ Params in order:
         timeout_action: *hold_action,
         on_press_reset_timeout_to: None,
         require_prior_idle: opts.require_prior_idle,
+        adaptive: opts.adaptive,
     }))))
 }
 
@@ -325,6 +371,7 @@ Params in order:
         timeout_action: *hold_action,
         on_press_reset_timeout_to: None,
         require_prior_idle: opts.require_prior_idle,
+        adaptive: opts.adaptive,
     }))))
 }
 
@@ -356,7 +403,7 @@ Params in order:
 <tap-repress-timeout> <hold-timeout> <tap-action> <hold-action>
 Followed by optional lists:
 (tap-on-press <keys...>) (tap-on-press-release <keys...>) (hold-on-press <keys...>)
-(require-prior-idle <ms>)",
+(require-prior-idle <ms>) (adaptive <min-timeout> <max-timeout> <min-idle> <max-idle>)",
             TAP_HOLD_KEYS,
             n_positional,
         )
@@ -370,6 +417,7 @@ Followed by optional lists:
     }
 
     let mut require_prior_idle = None;
+    let mut adaptive = None;
     let mut tap_on_press: Vec<OsCode> = vec![];
     let mut tap_on_press_release: Vec<OsCode> = vec![];
     let mut hold_on_press: Vec<OsCode> = vec![];
@@ -396,6 +444,9 @@ Followed by optional lists:
             "require-prior-idle" => {
                 require_prior_idle = Some(parse_require_prior_idle_option(option, option_expr, s)?);
             }
+            "adaptive" => {
+                adaptive = Some(parse_adaptive_option(option, option_expr, s)?);
+            }
             "tap-on-press" => {
                 tap_on_press =
                     parse_key_list_from_option(option, option_expr, s, kw, &mut seen_keys)?;
@@ -411,7 +462,8 @@ Followed by optional lists:
             _ => bail_expr!(
                 &option[0],
                 "unknown tap-hold-keys option '{}'. \
-                Valid options: tap-on-press, tap-on-press-release, hold-on-press, require-prior-idle",
+                Valid options: tap-on-press, tap-on-press-release, hold-on-press, require-prior-idle, \
+                adaptive",
                 kw
             ),
         }
@@ -431,6 +483,7 @@ Followed by optional lists:
         timeout_action: *hold_action,
         on_press_reset_timeout_to: None,
         require_prior_idle,
+        adaptive,
     }))))
 }
 
@@ -10,6 +10,7 @@ mod defcfg;
 mod defhands;
 mod device_detect;
 mod environment;
+mod lint;
 mod macros;
 
 static CFG_PARSE_LOCK: Mutex<()> = Mutex::new(());
@@ -214,6 +215,59 @@ fn test_span_of_an_unterminated_block_comment_error() {
     assert_eq!(span.end.line_beginning, 0);
 }
 
+#[test]
+fn independent_errors_are_reported_together() {
+    let source = "
+(defcfg
+  process-unmapped-keys notabool
+  sequence-timeout 100
+  unknown-option yes)
+(defsrc a b)
+(defalias
+  bad (tap-hold 200)
+  good b
+  alsobad (notanaction))
+(deflayer one @bad @good)
+(deflayer two notakey (layer-while-held three))
+";
+    let err = parse_cfg(source).map(|_| ()).expect_err("has errors");
+    let spans = err
+        .errors()
+        .map(|e| {
+            let span = e.span.as_ref().expect("span should be Some");
+            &source[span.start()..span.end()]
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        [
+            "notabool",
+            "unknown-option",
+            "(tap-hold 200)",
+            "notanaction",
+            "notakey",
+        ]
+    );
+    assert!(err.related.iter().all(|e| e.related.is_empty()));
+
+    let report = format!("{:?}", miette::Error::from(err));
+    assert!(report.contains("unknown-option"), "{report}");
+    assert!(report.contains("notanaction"), "{report}");
+}
+
+#[test]
+fn recovered_errors_are_reported_with_fatal_error() {
+    let source = "
+(defcfg unknown-option yes)
+(defsrc a b)
+(deflayer one a)
+";
+    let err = parse_cfg(source).map(|_| ()).expect_err("has errors");
+    assert_eq!(err.errors().count(), 2);
+    assert!(err.msg.contains("unknown-option"));
+    assert!(err.related[0].msg.contains("Layer one has 1 item(s)"));
+}
+
 #[test]
 fn parse_action_vars() {
     let source = r#"
@@ -1707,6 +1761,11 @@ fn parse_defvar_concat() {
     rootpath "/home/myuser/mysubdir"
     ;; $otherpath will be the string: /home/myuser/mysubdir/helloworld
     otherpath (concat $rootpath "/helloworld")
+
+    up (upper a (b "c"))
+    low (lower $rootpath "/A")
+    rep (concat (replace $rootpath "/my" "/your") /x)
+    norep (replace a b)
 )
 "#;
     let mut s = ParserState::default();
@@ -1757,6 +1816,20 @@ fn parse_defvar_concat() {
         SExpr::Atom(a) => assert_eq!(&a.t, "/home/myuser/mysubdir/helloworld"),
         SExpr::List(l) => panic!("expected string not list: {l:?}"),
     }
+    for (var, expected) in [
+        ("up", "ABC"),
+        ("low", "/home/myuser/mysubdir/a"),
+        ("rep", "/home/youruser/yoursubdir/x"),
+    ] {
+        match s.vars().unwrap().get(var).unwrap() {
+            SExpr::Atom(a) => assert_eq!(&a.t, expected),
+            SExpr::List(l) => panic!("expected string not list: {l:?}"),
+        }
+    }
+    match s.vars().unwrap().get("norep").unwrap() {
+        SExpr::Atom(a) => panic!("expected list not string {a:?}"),
+        SExpr::List(_) => {}
+    }
 }
 
 #[test]
@@ -1986,6 +2059,465 @@ fn parse_template_8() {
     .expect("parses");
 }
 
+#[test]
+fn parse_template_for_and_arithmetic() {
+    let _lk = lock(&CFG_PARSE_LOCK);
+
+    let source = r#"
+(deftemplate digits (first count)
+  (template-for i (range $first (- (+ $first $count) 1))
+    (defalias (concat d $i) (if-equal $i 2 (lower F2)) (if-not-equal $i 2 $i))))
+(t! digits 1 3)
+(template-for row (0 1)
+  (template-for col (range 1 2)
+    (defalias (concat k $row $col) (+ (* $row 2) $col))))
+(template-for key (a b)
+  (defalias (concat hold- $key) (tap-hold 200 200 $key (replace lsft l r))))
+(defcfg concurrent-tap-hold yes)
+(defsrc a b c - =)
+(deflayer base @d1 @d2 @d3 - =)
+(template-for key (c)
+  (defchordsv2 (- =) $key 200 all-released ()))
+"#;
+    let mut s = ParserState::default();
+    parse_cfg_raw_string(
+        source,
+        &mut s,
+        &PathBuf::from("test"),
+        &mut FileContentProvider {
+            get_file_content_fn: &mut |_| unimplemented!(),
+        },
+        DEF_LOCAL_KEYS,
+        Err("env vars not implemented".into()),
+    )
+    .map_err(|e| {
+        eprintln!("{:?}", miette::Error::from(e));
+        ""
+    })
+    .expect("parses");
+    assert!(s.aliases["d1"].key_codes().eq(vec![KeyCode::Kb1]));
+    assert!(s.aliases["d2"].key_codes().eq(vec![KeyCode::F2]));
+    assert!(s.aliases["d3"].key_codes().eq(vec![KeyCode::Kb3]));
+    assert!(!s.aliases.contains_key("d4"));
+    assert!(s.aliases["k01"].key_codes().eq(vec![KeyCode::Kb1]));
+    assert!(s.aliases["k12"].key_codes().eq(vec![KeyCode::Kb4]));
+    assert!(s.aliases.contains_key("hold-a"));
+    assert!(s.aliases.contains_key("hold-b"));
+}
+
+#[test]
+fn parse_template_for_errors() {
+    let source = "
+(defsrc a)
+(deflayer base a)
+(template-for i (range 1 x) (defalias (concat a $i) a))
+";
+    let err = parse_cfg(source).map(|_| ()).expect_err("range is invalid");
+    assert!(
+        err.msg.contains("range expects two integers"),
+        "{}",
+        err.msg
+    );
+
+    let source = "
+(defsrc a)
+(deflayer base a)
+(template-for i (range 1 2) (defalias x (* $i 9223372036854775807)))
+";
+    let err = parse_cfg(source).map(|_| ()).expect_err("overflow");
+    assert!(err.msg.contains("too large"), "{}", err.msg);
+}
+
+#[test]
+fn parse_defstate() {
+    let source = "
+(defsrc a b)
+(defstate mode 0 flag true)
+(defstate count -32768)
+(deflayer base (state-set mode 2) (switch ((state flag false)) (state-inc count 10) break))
+";
+    parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+
+    for (source, expected_err) in [
+        ("(defstate mode 0 mode 1)", "duplicate state variable"),
+        ("(defstate mode)", "subsequent initial value"),
+        ("(defstate mode 32768)", "integer from -32768 to 32767"),
+        (
+            "(defalias x (state-toggle unknown))",
+            "not declared in any defstate",
+        ),
+        (
+            "(defstate mode 0) (defalias x (switch ((state mode)) a break))",
+            "state must have 2 parameters",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_deflayerconditions() {
+    let source = "
+(defsrc a b)
+(deflayer base (layer-while-held nav) (layer-while-held sym))
+(deflayer nav _ _)
+(deflayer sym _ _)
+(deflayer num _ _)
+(deflayer adjust _ _)
+(deflayerconditions
+  (nav sym) -> num
+  (num nav) -> adjust
+)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+    assert_eq!(
+        &icfg.layer_conditions[..],
+        &[
+            LayerCondition {
+                if_active: Box::new([1, 2]),
+                then: 3,
+            },
+            LayerCondition {
+                if_active: Box::new([3, 1]),
+                then: 4,
+            },
+        ]
+    );
+
+    for (source, expected_err) in [
+        (
+            "(deflayerconditions nav -> base)",
+            "expected a list of layers",
+        ),
+        ("(deflayerconditions (nav) => base)", "expected ->"),
+        ("(deflayerconditions (nav))", "must be followed by ->"),
+        (
+            "(deflayerconditions (nav) ->)",
+            "layer to activate after ->",
+        ),
+        (
+            "(deflayerconditions (nav unknown) -> base)",
+            "not a known layer",
+        ),
+        (
+            "(deflayerconditions (nav base) -> base)",
+            "must not be one of",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) (deflayer nav a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_defmirror() {
+    let source = "
+(defsrc f j)
+(defmirror f j)
+(deflayer base swap-hands swap-hands-one-shot)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+    let (f, j) = (u16::from(OsCode::KEY_F), u16::from(OsCode::KEY_J));
+    assert_eq!(&icfg.mirror[..], &[(f, j), (j, f)]);
+
+    for (source, expected_err) in [
+        ("(defmirror f)", "followed by the key that mirrors it"),
+        ("(defmirror f f)", "cannot mirror itself"),
+        ("(defmirror f j d j)", "already mirrored"),
+        ("(defmirror f j) (defmirror d k)", "Only one defmirror"),
+        (
+            "(defalias x swap-hands-toggle)",
+            "requires a defmirror block",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_case_word_errors() {
+    for (source, expected_err) in [
+        ("(defalias x (case-word 100))", "expects 2 params"),
+        ("(defalias x (case-word 100 title))", "mode must be one of"),
+        (
+            "(defalias x (case-word 100 (separator)))",
+            "expects 1 param",
+        ),
+        (
+            "(defalias x (case-word 100 (separator O-a)))",
+            "O- is not allowed",
+        ),
+        (
+            "(defalias x (case-word-custom 100 snake (a)))",
+            "expects 4 params",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_defexpand() {
+    let source = r#"
+(defsrc a)
+(deflayer base a)
+(defexpand expansions (btw "by the way" Ty "Thanks") boundary-keys (spc .))
+"#;
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+    let expansions = icfg.expansions;
+    assert_eq!(expansions.max_trigger_len, 3);
+    assert_eq!(
+        &expansions.boundary_keys[..],
+        &[OsCode::KEY_SPACE, OsCode::KEY_DOT]
+    );
+    let thanks = &expansions.expansions[&[OsCode::KEY_T, OsCode::KEY_Y][..]];
+    assert_eq!(
+        thanks[0],
+        ExpandChar {
+            osc: OsCode::KEY_T,
+            mods: &[KeyCode::LShift],
+            letter: true
+        }
+    );
+    assert_eq!(
+        expansions.expansions[&[OsCode::KEY_B, OsCode::KEY_T, OsCode::KEY_W][..]].len(),
+        10
+    );
+
+    for (source, expected_err) in [
+        ("(defexpand)", "at least one expansion"),
+        ("(defexpand expansions (btw))", "followed by its expansion"),
+        (
+            r#"(defexpand expansions (btw "by the way" btw "x"))"#,
+            "duplicate trigger",
+        ),
+        (
+            r#"(defexpand expansions ("b w" "x"))"#,
+            "Unknown trigger key name",
+        ),
+        (
+            r#"(defexpand expansions (btw "by ☺"))"#,
+            "Unknown output key name",
+        ),
+        ("(defexpand timeout 100)", "Unknown defexpand option"),
+        (
+            "(defexpand expansions (a b)) (defexpand expansions (c d))",
+            "Only one defexpand",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_defautocorrect_errors() {
+    for (source, expected_err) in [
+        ("(defautocorrect)", "expects a file name"),
+        ("(defautocorrect (a.txt))", "Filename must be a string"),
+        (
+            "(defautocorrect a.txt timeout 100)",
+            "Unknown defautocorrect option",
+        ),
+        (
+            "(defautocorrect a.txt boundary-keys)",
+            "must be followed by a value",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+    let err = parse_cfg("(defsrc a) (deflayer base autocorrect-toggle)")
+        .map(|_| ())
+        .expect_err("config is invalid");
+    assert!(
+        err.msg.contains("requires a defautocorrect block"),
+        "{}",
+        err.msg
+    );
+}
+
+#[test]
+fn parse_defoslayout_errors() {
+    for (source, expected_err) in [
+        ("(defoslayout y)", "followed by the key"),
+        ("(defoslayout yz z)", "single character"),
+        ("(defoslayout y notakey)", "unknown key"),
+        ("(defoslayout y z y x)", "duplicate character"),
+        (
+            "(defoslayout y z) (defoslayout z y)",
+            "Only one defoslayout",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+    for (source, expected_err) in [
+        ("(type-text)", "expects 1 parameter"),
+        ("(type-text (a b))", "found a list"),
+    ] {
+        let source = format!("(defsrc a) (deflayer base {source})");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_tap_dance_hold_errors() {
+    for (source, expected_err) in [
+        ("(tap-dance-hold 200 ((a b)))", "expects a timeout"),
+        ("(tap-dance-hold 200 100 a)", "expected a list"),
+        ("(tap-dance-hold 200 100 ())", "empty list"),
+        (
+            "(tap-dance-hold 200 100 ((a b c)))",
+            "a tap action and a hold action",
+        ),
+        (
+            "(tap-dance-hold 200 100 (a))",
+            "a tap action and a hold action",
+        ),
+        (
+            "(tap-dance-hold 200 100 ((a (tap-hold 200 200 b c))))",
+            "tap-hold does not work",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base {source})");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_defaltrepeat() {
+    let source = "
+(defsrc a)
+(deflayer base alt-repeat)
+(defaltrepeat S-9 S-0 up down)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+    assert_eq!(
+        &icfg.alt_repeats[..],
+        &[
+            AltRepeat {
+                from: u16::from(OsCode::KEY_9) | mod_mask_for_keycode(KeyCode::LShift),
+                mods: mod_mask_for_keycode(KeyCode::LShift),
+                to: OsCode::KEY_0,
+            },
+            AltRepeat {
+                from: u16::from(OsCode::KEY_UP),
+                mods: 0,
+                to: OsCode::KEY_DOWN,
+            },
+        ]
+    );
+
+    for (source, expected_err) in [
+        (
+            "(defaltrepeat up)",
+            "followed by the key that alt-repeat outputs",
+        ),
+        (
+            "(defaltrepeat up down up left)",
+            "already has an alt-repeat output",
+        ),
+        ("(defaltrepeat O-up down)", "O- is not allowed"),
+        ("(defaltrepeat up dwn)", "unknown key"),
+        (
+            "(defaltrepeat up down) (defaltrepeat a b)",
+            "Only one defaltrepeat",
+        ),
+        ("(defalias x alt-repeat)", "requires a defaltrepeat block"),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
+#[test]
+fn parse_defautoshift() {
+    let source = "
+(defsrc a 1)
+(deflayer base a auto-shift-toggle)
+(defautoshift timeout 150 numbers-symbols-timeout 250 exclude (z 2) repeat-after-hold yes)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("parses");
+    let auto_shift = icfg.auto_shift.expect("auto-shift is defined");
+    assert!(auto_shift.enabled);
+    let timeout = |kc| match auto_shift.action_for(&Action::KeyCode(kc)) {
+        Some(Action::HoldTap(ht)) => Some(ht.timeout),
+        _ => None,
+    };
+    assert_eq!(timeout(KeyCode::A), Some(150));
+    assert_eq!(timeout(KeyCode::Kb1), Some(250));
+    assert_eq!(timeout(KeyCode::Z), None);
+    assert_eq!(timeout(KeyCode::Kb2), None);
+    assert_eq!(timeout(KeyCode::Space), None);
+
+    for (source, expected_err) in [
+        ("(defautoshift timeout)", "must be followed by a value"),
+        ("(defautoshift timeout 0)", "timeout"),
+        (
+            "(defautoshift shift-keys (a))",
+            "Unknown defautoshift option",
+        ),
+        ("(defautoshift exclude a)", "must be a list of key names"),
+        ("(defautoshift) (defautoshift)", "Only one defautoshift"),
+        (
+            "(defalias x auto-shift-toggle)",
+            "requires a defautoshift block",
+        ),
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        let err = parse_cfg(&source)
+            .map(|_| ())
+            .expect_err("config is invalid");
+        assert!(err.msg.contains(expected_err), "{}", err.msg);
+    }
+}
+
 #[test]
 fn test_deflayermap() {
     let source = r#"
@@ -263,6 +263,35 @@ fn per_action_require_prior_idle_on_tap_hold_press_timeout() {
         .expect("passes");
 }
 
+#[test]
+fn per_action_adaptive_parses() {
+    let source = "
+(defcfg adaptive-tap-hold-file adaptive.txt)
+(defsrc a b)
+(deflayer base @a @b)
+(defalias
+  a (tap-hold 200 200 a lctl (adaptive 150 300 0 150))
+  b (tap-hold-keys 200 200 b lalt (tap-on-press a) (adaptive 150 300 0 0)))
+";
+    parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("passes");
+}
+
+#[test]
+fn per_action_adaptive_errors() {
+    for source in [
+        "(defalias a (tap-hold 200 200 a lctl (adaptive 150 300 0)))",
+        "(defalias a (tap-hold 200 200 a lctl (adaptive 300 150 0 0)))",
+        "(defalias a (tap-hold 200 200 a lctl (adaptive 150 300 100 50)))",
+        "(defalias a (tap-hold 200 200 a lctl (adaptive 0 300 0 0)))",
+        "(defcfg adaptive-tap-hold-file ())",
+    ] {
+        let source = format!("(defsrc a) (deflayer base a) {source}");
+        parse_cfg(&source).map(|_| ()).expect_err(&source);
+    }
+}
+
 #[test]
 fn per_action_require_prior_idle_on_tap_hold_release_keys() {
     let source = "
@@ -311,3 +340,62 @@ fn per_action_require_prior_idle_on_tap_hold_release_tap_keys_release() {
         .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
         .expect("passes");
 }
+
+#[test]
+fn layer_lock_unlock_keys() {
+    let source = "
+(defcfg layer-lock-unlock-keys (esc spc))
+(defsrc a)
+(deflayer base layer-lock)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("passes");
+    assert_eq!(
+        icfg.options.layer_lock_unlock_keys,
+        vec![OsCode::KEY_ESC, OsCode::KEY_SPACE]
+    );
+
+    let source = "
+(defcfg layer-lock-unlock-keys esc)
+(defsrc a)
+(deflayer base a)
+";
+    let err = parse_cfg(source).map(|_| ()).expect_err("fails");
+    assert!(
+        err.msg.contains("must be a list of key names"),
+        "{}",
+        err.msg
+    );
+}
+
+#[test]
+fn stats_options() {
+    let source = "
+(defcfg
+  stats yes
+  stats-file stats.csv
+  stats-save-interval 300
+  stats-exclude (letters bigrams))
+(defsrc a)
+(deflayer base a)
+";
+    let icfg = parse_cfg(source)
+        .map_err(|e| eprintln!("{:?}", miette::Error::from(e)))
+        .expect("passes");
+    assert!(icfg.options.stats);
+    assert_eq!(icfg.options.stats_file.as_deref(), Some("stats.csv"));
+    assert_eq!(icfg.options.stats_save_interval, 300);
+    assert!(icfg.options.stats_exclude_letters);
+    assert!(icfg.options.stats_exclude_bigrams);
+
+    for source in [
+        "(defcfg stats-file \"\")",
+        "(defcfg stats-save-interval 0)",
+        "(defcfg stats-exclude letters)",
+        "(defcfg stats-exclude (digits))",
+    ] {
+        let source = format!("{source} (defsrc a) (deflayer base a)");
+        parse_cfg(&source).map(|_| ()).expect_err(&source);
+    }
+}
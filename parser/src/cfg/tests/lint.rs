@@ -0,0 +1,198 @@
+use super::*;
+
+/// The lints reported for the configuration, with the text that each one points at.
+fn lints(source: &str) -> Vec<(Lint, String)> {
+    init_log();
+    let _lk = lock(&CFG_PARSE_LOCK);
+    let (res, warnings) = check_str(source, Default::default());
+    res.expect("config is valid");
+    warnings
+        .into_iter()
+        .filter_map(|w| {
+            let span = w.span?;
+            Some((w.lint?, span.file_content()[span].to_string()))
+        })
+        .collect()
+}
+
+#[test]
+fn clean_config_has_no_lints() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defvar tt 200)
+(defsrc a b)
+(defalias nav (layer-while-held nav))
+(defvirtualkeys vk x)
+(deftemplate hold (key) (tap-hold $tt $tt $key lctl))
+(deflayer base (t! hold a) @nav)
+(deflayer nav (on-press tap-vkey vk) _)
+";
+    assert_eq!(lints(source), vec![]);
+}
+
+#[test]
+fn unused_definitions() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defvar used 200 unused 300)
+(defsrc a)
+(defalias used (tap-hold $used $used a b) unused c)
+(defvirtualkeys vk x)
+(deftemplate tmpl () a)
+(deflayer base @used)
+";
+    assert_eq!(
+        lints(source),
+        vec![
+            (Lint::UnusedAlias, "unused".into()),
+            (Lint::UnusedVariable, "unused".into()),
+            (Lint::UnusedVirtualKey, "vk".into()),
+            (Lint::UnusedTemplate, "tmpl".into()),
+        ]
+    );
+}
+
+#[test]
+fn names_used_only_for_another_platform_are_used() {
+    let other_platform = if cfg!(target_os = "macos") {
+        "linux"
+    } else {
+        "macos"
+    };
+    let source = format!(
+        "
+(defcfg process-unmapped-keys no)
+(defsrc a)
+(defalias x b)
+(deflayer base a)
+(platform ({other_platform}) (deflayer other @x))
+"
+    );
+    assert_eq!(lints(&source), vec![]);
+}
+
+#[test]
+fn unreachable_and_transparent_layers() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defsrc a b)
+(defalias sw (layer-switch switched))
+(deflayer base @sw (layer-while-held held))
+(deflayer switched a (layer-toggle from-switched))
+(deflayer held _ _)
+(deflayer from-switched a b)
+(deflayer self-only a (layer-switch self-only))
+";
+    assert_eq!(
+        lints(source),
+        vec![
+            (Lint::UnreachableLayer, "self-only".into()),
+            (Lint::TransparentLayer, "held".into()),
+        ]
+    );
+}
+
+#[test]
+fn layer_word_layers_are_reachable() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defsrc a b)
+(deflayer base (layer-word num 1000 (1 2)) b)
+(deflayer num 1 2)
+";
+    assert_eq!(lints(source), vec![]);
+}
+
+#[test]
+fn conditional_layers_are_reachable() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defsrc a b)
+(deflayer base (layer-while-held nav) (layer-while-held sym))
+(deflayer nav a b)
+(deflayer sym a b)
+(deflayer num a (layer-while-held from-num))
+(deflayer from-num a b)
+(deflayer unreached a b)
+(deflayer never a b)
+(deflayerconditions
+  (nav sym) -> num
+  (unreached nav) -> never
+)
+";
+    assert_eq!(
+        lints(source),
+        vec![
+            (Lint::UnreachableLayer, "unreached".into()),
+            (Lint::UnreachableLayer, "never".into()),
+        ]
+    );
+}
+
+#[test]
+fn unmapped_chord_key() {
+    let source = "
+(defcfg process-unmapped-keys no concurrent-tap-hold yes)
+(defsrc a b)
+(deflayer base a b)
+(defchordsv2
+  (a b) c 200 all-released ()
+  (a z) d 200 all-released ()
+)
+";
+    assert_eq!(lints(source), vec![(Lint::UnmappedChordKey, "z".into())]);
+}
+
+#[test]
+fn home_row_timeouts() {
+    let source = "
+(defcfg process-unmapped-keys no allow-lints (unreachable-layer))
+(defsrc a s d f)
+(deflayer base
+  (tap-hold 200 200 a lmet) (tap-hold 200 200 s lalt)
+  (tap-hold 200 450 d lsft) (tap-hold 200 250 f lctl))
+(deflayer nav
+  (tap-hold 200 200 a lmet) (tap-hold 200 250 s lalt) d f)
+";
+    assert_eq!(lints(source), vec![(Lint::HomeRowTimeouts, "base".into())]);
+}
+
+#[test]
+fn shadowed_overrides() {
+    let source = "
+(defcfg process-unmapped-keys no)
+(defsrc a)
+(deflayer base a)
+(defoverrides
+  (lsft a) (b)
+  (a) (c)
+  (lctl a) (d)
+)
+";
+    assert_eq!(
+        lints(source),
+        vec![(Lint::ShadowedOverride, "(lsft a) (b)".into())]
+    );
+}
+
+#[test]
+fn allowed_lints_are_not_reported() {
+    let source = "
+(defcfg process-unmapped-keys no allow-lints (unused-alias transparent-layer))
+(defvar x 1)
+(defsrc a)
+(defalias unused a)
+(deflayer base _)
+";
+    assert_eq!(lints(source), vec![(Lint::UnusedVariable, "x".into())]);
+
+    let source = "
+(defcfg allow-lints (not-a-lint))
+(defsrc a)
+(deflayer base a)
+";
+    let err = parse_cfg(source)
+        .map(|_| ())
+        .expect_err("unknown lint name");
+    assert!(err.msg.contains("unused-alias"), "{}", err.msg);
+}
@@ -47,6 +47,30 @@ pub(crate) fn parse_list_var(expr: &Spanned<Vec<SExpr>>, vars: &HashMap<String,
                     t: concat_str,
                 })
             }
+            "upper" | "lower" => {
+                let mut s = String::new();
+                push_all_atoms(&expr.t[1..], vars, &mut s);
+                SExpr::Atom(Spanned {
+                    span: expr.span.clone(),
+                    t: match a.t.as_str() {
+                        "upper" => s.to_uppercase(),
+                        _ => s.to_lowercase(),
+                    },
+                })
+            }
+            "replace" => {
+                let args: Option<Vec<&str>> = expr.t[1..]
+                    .iter()
+                    .map(|arg| arg.atom(Some(vars)).map(|arg| arg.trim_atom_quotes()))
+                    .collect();
+                match args.as_deref() {
+                    Some([text, from, to]) if !from.is_empty() => SExpr::Atom(Spanned {
+                        span: expr.span.clone(),
+                        t: text.replace(from, to),
+                    }),
+                    _ => SExpr::List(expr.clone()),
+                }
+            }
             _ => SExpr::List(expr.clone()),
         },
         _ => SExpr::List(expr.clone()),
@@ -58,8 +82,11 @@ pub(crate) fn push_all_atoms(exprs: &[SExpr], vars: &HashMap<String, SExpr>, pus
     for expr in exprs {
         if let Some(a) = expr.atom(Some(vars)) {
             pusheen.push_str(a.trim_atom_quotes());
-        } else if let Some(l) = expr.list(Some(vars)) {
-            push_all_atoms(l, vars, pusheen);
+        } else if let Some(l) = expr.span_list(Some(vars)) {
+            match parse_list_var(l, vars) {
+                SExpr::Atom(a) => pusheen.push_str(a.t.trim_atom_quotes()),
+                SExpr::List(l) => push_all_atoms(&l.t, vars, pusheen),
+            }
         }
     }
 }
@@ -301,9 +301,10 @@ mod inner {
         use crate::subset::GetOrIsSubsetOfKnownKey::*;
 
         if exprs[0].atom(None).expect("should be atom") == "defzippy-experimental" {
-            log::warn!(
+            s.warn(
+                Some(exprs[0].span()),
                 "You should replace defzippy-experimental with defzippy.\n\
-             Using -experimental will be invalid in the future."
+             Using -experimental will be invalid in the future.",
             );
         }
 
@@ -72,6 +72,7 @@ pub enum CustomAction {
     LiveReloadNum(u16),
     LiveReloadFile(&'static str),
     Repeat,
+    AltRepeat,
     CancelMacroOnRelease,
     CancelMacroOnNextPress(u32),
     DynamicMacroRecord(u16),
@@ -79,6 +80,7 @@ pub enum CustomAction {
     DynamicMacroPlay(u16),
     SendArbitraryCode(u16),
     CapsWord(CapsWordCfg),
+    LayerWord(LayerWordCfg),
     SetMouse {
         x: u16,
         y: u16,
@@ -98,6 +100,52 @@ pub enum CustomAction {
     ClipboardSaveSet(u16, &'static str),
     ClipboardSaveCmdSet(u16, &'static [&'static str]),
     ClipboardSaveSwap(u16, u16),
+    /// Change a state variable declared in `defstate`, referenced by index.
+    StateVar {
+        var: u16,
+        op: StateVarOp,
+    },
+    SwapHands(SwapHandsMode),
+    AutoShiftToggle,
+    Autocorrect(AutocorrectMode),
+}
+
+/// How `swap-hands` activates the mirrored layout from `defmirror`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwapHandsMode {
+    /// Swap while the key is held.
+    Hold,
+    Toggle,
+    /// Swap the next mirrored key press only.
+    OneShot,
+}
+
+/// How the autocorrect actions change whether `defautocorrect` is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutocorrectMode {
+    Toggle,
+    Enable,
+    Disable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateVarOp {
+    Set(i16),
+    /// Zero becomes 1 and every other value becomes 0.
+    Toggle,
+    /// Add to the value, saturating at the limits of i16.
+    Add(i16),
+}
+
+impl StateVarOp {
+    /// Return the new value of a state variable after this operation.
+    pub fn apply(self, value: i16) -> i16 {
+        match self {
+            StateVarOp::Set(v) => v,
+            StateVarOp::Toggle => i16::from(value == 0),
+            StateVarOp::Add(amount) => value.saturating_add(amount),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -215,6 +263,30 @@ pub struct CapsWordCfg {
     pub keys_nonterminal: &'static [KeyCode],
     pub timeout: u16,
     pub repress_behaviour: CapsWordRepressBehaviour,
+    pub case: CapsWordCase,
+    /// Keys to output instead of space. If `None`, space ends the state like any other
+    /// terminating key.
+    pub separator: Option<&'static [KeyCode]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayerWordCfg {
+    pub layer: u16,
+    pub keys_nonterminal: &'static [KeyCode],
+    pub timeout: u16,
+}
+
+/// Which of the keys in `keys_to_capitalize` are capitalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapsWordCase {
+    /// Every key, as in `caps-word`.
+    Upper,
+    /// None of the keys.
+    Lower,
+    /// The first key after each separator.
+    Camel,
+    /// The first key of the word and the first key after each separator.
+    Pascal,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -792,3 +792,16 @@ fn roundtrip_oscode_keycode() {
         assert_eq!(osc, roundtrip);
     }
 }
+
+#[test]
+fn key_names_are_valid() {
+    for name in super::key_names() {
+        assert!(
+            super::str_to_oscode(name).is_some(),
+            "invalid key name: {name}"
+        );
+    }
+    assert!(super::KEY_NAMES.contains(&"bspc"));
+    assert!(super::KEY_NAMES.contains(&"<"));
+    assert!(!super::KEY_NAMES.contains(&"Backspace"));
+}
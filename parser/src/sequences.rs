@@ -22,6 +22,15 @@ pub fn mod_mask_for_keycode(kc: KeyCode) -> u16 {
     }
 }
 
+/// The modifier keys to press for a mod mask, using the left modifiers where a bit stands for
+/// both sides.
+pub fn keycodes_for_mod_mask(mask: u16) -> impl Iterator<Item = KeyCode> {
+    use KeyCode::*;
+    [LShift, LCtrl, LAlt, RAlt, LGui]
+        .into_iter()
+        .filter(move |kc| mask & mod_mask_for_keycode(*kc) != 0)
+}
+
 #[test]
 fn keys_fit_within_mask() {
     use crate::keys::OsCode;
@@ -0,0 +1,262 @@
+//! Interactive simulation session.
+//!
+//! Each line read from stdin is evaluated like a line of a simulation file. After every line the
+//! output events produced by it and a summary of the kanata state are printed. With JSON output
+//! only the events go to stdout; the state summary and other messages go to stderr.
+
+use super::*;
+use kanata_keyberon::key_code::KeyCode;
+use std::io::{BufRead, IsTerminal, Write};
+
+const HELP: &str = "\
+Enter simulation items in the same format as a simulation file, e.g.:
+    d:a t:50 u:a
+    vk:name:tap
+    ls:layer
+Commands:
+    :reload  re-parse the configuration file, keeping the session
+    :state   print the current state again
+    :help    print this message
+    :quit    end the session";
+
+pub(crate) fn run(args: &ValidatedArgs, fmt: OutputFormat) -> Result<()> {
+    let mut k = Kanata::new(args)?;
+    let interactive = std::io::stdin().is_terminal();
+    let input = std::io::stdin().lock();
+    match fmt {
+        OutputFormat::Text => session(
+            &mut k,
+            fmt,
+            input,
+            &mut std::io::stdout(),
+            &mut std::io::stdout(),
+            interactive,
+        ),
+        // Keep stdout parseable as JSON lines; everything meant for humans goes to stderr.
+        OutputFormat::Json => session(
+            &mut k,
+            fmt,
+            input,
+            &mut std::io::stdout(),
+            &mut std::io::stderr(),
+            interactive,
+        ),
+    }
+}
+
+/// Evaluate the lines read from `input` until it ends or the session is quit.
+/// Output events are written to `out`; help, prompts, errors and the state summary to `info`.
+fn session(
+    k: &mut Kanata,
+    fmt: OutputFormat,
+    input: impl BufRead,
+    out: &mut dyn Write,
+    info: &mut dyn Write,
+    interactive: bool,
+) -> Result<()> {
+    let mut held: Vec<OsCode> = vec![];
+    let mut cursor = 0;
+    if interactive {
+        writeln!(info, "{HELP}")?;
+    }
+    print_state(info, k, &held)?;
+    let mut lines = input.lines();
+    loop {
+        if interactive {
+            write!(info, "> ")?;
+            info.flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
+        match line.trim() {
+            "" => continue,
+            ":quit" | ":q" | ":exit" => break,
+            ":help" | ":h" => {
+                writeln!(info, "{HELP}")?;
+                continue;
+            }
+            ":state" => {}
+            ":reload" | ":r" => match k.live_reload_now() {
+                Ok(()) => {
+                    // The reloaded layout starts with no keys pressed.
+                    held.clear();
+                    writeln!(info, "reloaded configuration")?;
+                }
+                Err(e) => writeln!(info, "error: {e}")?,
+            },
+            line => {
+                if let Err(e) = sim_line(k, line, &mut held) {
+                    writeln!(info, "error: {e}")?;
+                }
+            }
+        }
+        for ev in new_output(k, fmt, &mut cursor) {
+            writeln!(out, "{ev}")?;
+        }
+        out.flush()?;
+        print_state(info, k, &held)?;
+    }
+    Ok(())
+}
+
+/// Output events recorded since the previous call.
+#[cfg(all(
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+))]
+fn new_output(k: &Kanata, fmt: OutputFormat, cursor: &mut usize) -> Vec<String> {
+    let out = match fmt {
+        OutputFormat::Text => k.kbd_out.outputs.events[*cursor..].to_vec(),
+        OutputFormat::Json => k.kbd_out.log.events[*cursor..]
+            .iter()
+            .map(json_line)
+            .collect(),
+    };
+    *cursor += out.len();
+    out
+}
+
+#[cfg(not(all(
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+)))]
+fn new_output(_k: &Kanata, _fmt: OutputFormat, _cursor: &mut usize) -> Vec<String> {
+    vec![]
+}
+
+fn print_state(w: &mut dyn Write, k: &Kanata, held: &[OsCode]) -> std::io::Result<()> {
+    let layout = k.layout.b();
+    let layer_name = |idx: usize| {
+        k.layer_info
+            .get(idx)
+            .map(|l| l.name.as_str())
+            .unwrap_or("?")
+    };
+    let mut layers = layout
+        .active_held_layers()
+        .map(|l| layer_name(l.into()).to_string())
+        .collect::<Vec<_>>();
+    layers.push(format!("{} (default)", layer_name(layout.default_layer)));
+    writeln!(w, "  layers:  {}", layers.join(" > "))?;
+
+    let held = held
+        .iter()
+        .map(|&kc| format!("{:?}", KeyCode::from(kc)))
+        .collect::<Vec<_>>();
+    writeln!(w, "  held:    {}", held.join(" "))?;
+
+    let out = layout
+        .keycodes()
+        .map(|kc| format!("{kc:?}"))
+        .collect::<Vec<_>>();
+    writeln!(w, "  out:     {}", out.join(" "))?;
+
+    for waiting in layout.waiting.iter().chain(layout.extra_waiting.iter()) {
+        writeln!(
+            w,
+            "  waiting: {} on {} ({}ms left)",
+            waiting.kind(),
+            coord_name(k, waiting.coord()),
+            waiting.remaining_timeout()
+        )?;
+    }
+    Ok(())
+}
+
+fn coord_name(k: &Kanata, (row, idx): (u8, u16)) -> String {
+    match row {
+        0 => match OsCode::from_u16(idx) {
+            Some(osc) => format!("{:?}", KeyCode::from(osc)),
+            None => format!("{idx}"),
+        },
+        FAKE_KEY_ROW => k
+            .virtual_keys
+            .iter()
+            .find(|(_, vk_idx)| **vk_idx == usize::from(idx))
+            .map(|(name, _)| format!("vk:{name}"))
+            .unwrap_or_else(|| format!("vk:{idx}")),
+        _ => format!("({row},{idx})"),
+    }
+}
+
+#[cfg(all(
+    test,
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+))]
+mod tests {
+    use super::*;
+
+    const CFG: &str = "
+(defsrc a b)
+(deflayer base @th b)
+(deflayer nav _ x)
+(defalias th (tap-hold 100 100 a (layer-while-held nav)))
+";
+
+    fn run_session(fmt: OutputFormat, input: &str) -> (String, String) {
+        let mut k = Kanata::new_from_str(CFG, Default::default()).expect("valid config");
+        let (mut out, mut info) = (vec![], vec![]);
+        session(&mut k, fmt, input.as_bytes(), &mut out, &mut info, false).unwrap();
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(info).unwrap(),
+        )
+    }
+
+    #[test]
+    fn json_session_writes_only_events_to_output() {
+        let (out, info) = run_session(
+            OutputFormat::Json,
+            "d:a t:150\nd:b t:10\nbogus\n:quit\nd:b t:10\n",
+        );
+        for line in out.lines() {
+            serde_json::from_str::<serde_json::Value>(line).expect("JSON line");
+        }
+        assert_eq!(out.lines().count(), 3);
+        assert!(out.contains(r#""key":"X","kind":"press","layer":"nav""#));
+        assert_eq!(
+            info.lines().collect::<Vec<_>>(),
+            [
+                "  layers:  base (default)",
+                "  held:    ",
+                "  out:     ",
+                "  layers:  nav > base (default)",
+                "  held:    A",
+                "  out:     ",
+                "  layers:  nav > base (default)",
+                "  held:    A B",
+                "  out:     X",
+                "error: invalid pair: bogus",
+                "  layers:  nav > base (default)",
+                "  held:    A B",
+                "  out:     X",
+            ]
+        );
+    }
+
+    #[test]
+    fn text_session_writes_events_and_state() {
+        let (out, info) = run_session(OutputFormat::Text, "d:b t:10\n:state\n");
+        assert_eq!(out, "out:↓B\n");
+        assert_eq!(
+            info.lines().collect::<Vec<_>>(),
+            [
+                "  layers:  base (default)",
+                "  held:    ",
+                "  out:     ",
+                "  layers:  base (default)",
+                "  held:    B",
+                "  out:     B",
+                "  layers:  base (default)",
+                "  held:    B",
+                "  out:     B",
+            ]
+        );
+    }
+}
@@ -6,6 +6,16 @@ use kanata_state_machine::{FAKE_KEY_ROW, FakeKeyAction, oskbd::*, *};
 use simplelog::{format_description, *};
 use std::path::PathBuf;
 
+mod repl;
+
+/// Simulation options parsed from the command line.
+struct SimArgs {
+    paths: Vec<PathBuf>,
+    appendix: Option<String>,
+    fmt: OutputFormat,
+    repl: bool,
+}
+
 pub fn default_sim() -> Vec<PathBuf> {
     let mut cfgs = Vec::new();
 
@@ -80,6 +90,25 @@ test/sim.txt in the current working directory and
     /// This flag generates an error if the binary is compiled without simulated output.
     #[arg(short = 'o', long, verbatim_doc_comment)]
     out: Option<String>,
+
+    /// Format of the simulation result.
+    /// `text` prints a timeline table meant for humans.
+    /// `json` prints one JSON object per line for each input and output event.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, verbatim_doc_comment)]
+    output_format: OutputFormat,
+
+    /// Start an interactive session instead of evaluating simulation files.
+    /// Simulation items are read from stdin line by line
+    /// and the output events and state are printed after each line.
+    /// Type `:help` in the session for the list of commands.
+    #[arg(long, verbatim_doc_comment)]
+    repl: bool,
+}
+
+#[derive(clap::ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
 }
 
 fn log_init() {
@@ -101,11 +130,10 @@ fn log_init() {
 }
 
 /// Parse CLI arguments
-fn cli_init_fsim() -> Result<(ValidatedArgs, Vec<PathBuf>, Option<String>)> {
+fn cli_init_fsim() -> Result<(ValidatedArgs, SimArgs)> {
     let args = Args::parse();
     let cfg_paths = args.cfg.unwrap_or_else(default_cfg);
     let sim_paths = args.sim.unwrap_or_else(default_sim);
-    let sim_appendix = args.out;
 
     log::info!(
         "kanata_simulated_input v{} starting",
@@ -122,7 +150,9 @@ fn cli_init_fsim() -> Result<(ValidatedArgs, Vec<PathBuf>, Option<String>)> {
     } else {
         bail!("No config files provided\nFor more info, pass the `-h` or `--help` flags.");
     }
-    if let Some(config_sim_file) = sim_paths.first() {
+    if args.repl {
+        // Simulation files are not used in the interactive session.
+    } else if let Some(config_sim_file) = sim_paths.first() {
         if !config_sim_file.exists() {
             bail!(
                 "Could not find the simulation file ({})\nFor more info, pass the `-h` or `--help` flags.",
@@ -142,8 +172,12 @@ fn cli_init_fsim() -> Result<(ValidatedArgs, Vec<PathBuf>, Option<String>)> {
             symlink_path: None,
             nodelay: true,
         },
-        sim_paths,
-        sim_appendix,
+        SimArgs {
+            paths: sim_paths,
+            appendix: args.out,
+            fmt: args.output_format,
+            repl: args.repl,
+        },
     ))
 }
 
@@ -212,10 +246,6 @@ fn kbd_out_log(
     _key_code: Option<OsCode>,
     _tick: Option<u128>,
 ) {
-    let a = LogFmtT::InTick;
-    if a == LogFmtT::InTick {
-        println!("{}", 42)
-    };
     #[cfg(all(
         not(feature = "simulated_input"),
         not(feature = "passthru_ahk"),
@@ -246,135 +276,189 @@ fn kbd_out_log(
         }
     }
 }
+
+/// Attribute the events logged from now on to the currently active layer.
+/// Kanata keeps this up to date on every tick; this covers the layer set before the first tick
+/// and changes made directly by the simulator, e.g. `ls:`.
+fn log_layer(_k: &mut Kanata) {
+    #[cfg(all(
+        not(feature = "simulated_input"),
+        not(feature = "passthru_ahk"),
+        feature = "simulated_output"
+    ))]
+    {
+        let layer = _k.layout.b().current_layer();
+        let name = &_k.layer_info[layer].name;
+        _k.kbd_out.log.set_layer(name);
+    }
+}
+
+fn sim_tick(k: &mut Kanata, val: &str) -> Result<()> {
+    let tick = str::parse::<u128>(val)?;
+    kbd_out_log(&mut k.kbd_out, LogFmtT::InTick, None, Some(tick));
+    k.tick_ms(tick, &None)?;
+    Ok(())
+}
+
+fn sim_key(k: &mut Kanata, val: &str, value: KeyValue, held: &mut Vec<OsCode>) -> Result<()> {
+    let key_code = str_to_oscode(val).ok_or_else(|| anyhow!("unknown key: {val}"))?;
+    let log_type = match value {
+        KeyValue::Press => LogFmtT::InKeyDown,
+        KeyValue::Release => LogFmtT::InKeyUp,
+        _ => LogFmtT::InKeyRep,
+    };
+    kbd_out_log(&mut k.kbd_out, log_type, Some(key_code), None);
+    k.handle_input_event(&KeyEvent::new(key_code, value))?;
+    match value {
+        KeyValue::Press if !held.contains(&key_code) => held.push(key_code),
+        KeyValue::Release => held.retain(|&kc| kc != key_code),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Evaluate one line of the simulation input format.
+/// Input keys that are pressed and not yet released are tracked in `held`.
+fn sim_line(k: &mut Kanata, l: &str, held: &mut Vec<OsCode>) -> Result<()> {
+    for pair in l.split_whitespace() {
+        log_layer(k);
+        match pair.split_once(':') {
+            Some((kind, val)) => match kind {
+                "tick" | "🕐" | "t" => sim_tick(k, val)?,
+                "press" | "↓" | "d" | "down" => sim_key(k, val, KeyValue::Press, held)?,
+                "release" | "↑" | "u" | "up" => sim_key(k, val, KeyValue::Release, held)?,
+                "repeat" | "⟳" | "r" => sim_key(k, val, KeyValue::Repeat, held)?,
+                // Virtual/fake key activation: fakekey:name[:action] or vk:name[:action]
+                // Supported actions: press, release, tap, toggle
+                // Examples: fakekey:vk_bear:press, vk:vk_bear, virtualkey:vk_bear:tap
+                "fakekey" | "vk" | "virtualkey" | "🎭" => {
+                    let (vk_name, action) = parse_fakekey_spec(val)?;
+                    apply_fakekey_action(k, vk_name, action)?;
+                }
+                // Layer switch: ls:layer_name
+                // Switches to the specified layer as the new default layer.
+                // Example: ls:nav, ls:symbols
+                "ls" | "layer-switch" | "🔀" => {
+                    apply_layer_switch(k, val)?;
+                }
+                _ => bail!("invalid pair prefix: {kind}"),
+            },
+            None => {
+                let (kind, val) = split_at_1(pair);
+                match kind {
+                    //allow skipping : separator for unique non-key symbols
+                    "🕐" => sim_tick(k, val)?,
+                    "↓" => sim_key(k, val, KeyValue::Press, held)?,
+                    "↑" => sim_key(k, val, KeyValue::Release, held)?,
+                    "⟳" => sim_key(k, val, KeyValue::Repeat, held)?,
+                    "🎭" => {
+                        // Virtual key activation with emoji prefix (defaults to press)
+                        // Format: 🎭vk_name or 🎭vk_name:action
+                        let (vk_name, action) = parse_fakekey_spec(val)?;
+                        apply_fakekey_action(k, vk_name, action)?;
+                    }
+                    "🔀" => {
+                        // Layer switch with emoji prefix
+                        // Format: 🔀layer_name
+                        apply_layer_switch(k, val)?;
+                    }
+                    _ => bail!("invalid pair: {l}"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn main_impl() -> Result<()> {
     log_init();
-    let (args, sim_paths, _sim_appendix) = cli_init_fsim()?;
+    let (args, sim) = cli_init_fsim()?;
     #[cfg(not(feature = "simulated_output"))]
     {
-        if _sim_appendix.is_some() {
+        if sim.appendix.is_some() {
             bail!(
                 "The program was compiled without simulated output. The -o|--out flag is unsupported"
             );
         }
+        if sim.fmt == OutputFormat::Json {
+            bail!("The program was compiled without simulated output. JSON output is unsupported");
+        }
+    }
+
+    if sim.repl {
+        return repl::run(&args, sim.fmt);
     }
 
-    for config_sim_file in &sim_paths {
+    for config_sim_file in &sim.paths {
         let mut k = Kanata::new(&args)?;
         log::info!("Evaluating simulation file = {:?}", config_sim_file);
         let s = std::fs::read_to_string(config_sim_file)?;
+        let mut held = vec![];
         for l in s.lines() {
-            for pair in l.split_whitespace() {
-                match pair.split_once(':') {
-                    Some((kind, val)) => match kind {
-                        "tick" | "🕐" | "t" => {
-                            let tick = str::parse::<u128>(val)?;
-                            kbd_out_log(&mut k.kbd_out, LogFmtT::InTick, None, Some(tick));
-                            k.tick_ms(tick, &None)?;
-                        }
-                        "press" | "↓" | "d" | "down" => {
-                            let key_code =
-                                str_to_oscode(val).ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                            kbd_out_log(&mut k.kbd_out, LogFmtT::InKeyDown, Some(key_code), None);
-                            k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Press))?;
-                        }
-                        "release" | "↑" | "u" | "up" => {
-                            let key_code =
-                                str_to_oscode(val).ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                            kbd_out_log(&mut k.kbd_out, LogFmtT::InKeyUp, Some(key_code), None);
-                            k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Release))?;
-                        }
-                        "repeat" | "⟳" | "r" => {
-                            let key_code =
-                                str_to_oscode(val).ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                            kbd_out_log(&mut k.kbd_out, LogFmtT::InKeyRep, Some(key_code), None);
-                            k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Repeat))?;
-                        }
-                        // Virtual/fake key activation: fakekey:name[:action] or vk:name[:action]
-                        // Supported actions: press, release, tap, toggle
-                        // Examples: fakekey:vk_bear:press, vk:vk_bear, virtualkey:vk_bear:tap
-                        "fakekey" | "vk" | "virtualkey" | "🎭" => {
-                            let (vk_name, action) = parse_fakekey_spec(val)?;
-                            apply_fakekey_action(&mut k, vk_name, action)?;
-                        }
-                        // Layer switch: ls:layer_name
-                        // Switches to the specified layer as the new default layer.
-                        // Example: ls:nav, ls:symbols
-                        "ls" | "layer-switch" | "🔀" => {
-                            apply_layer_switch(&mut k, val)?;
-                        }
-                        _ => bail!("invalid pair prefix: {kind}"),
-                    },
-                    None => {
-                        let (kind, val) = split_at_1(pair);
-                        match kind {
-                            //allow skipping : separator for unique non-key symbols
-                            "🕐" => {
-                                let tick = str::parse::<u128>(val)?;
-                                kbd_out_log(&mut k.kbd_out, LogFmtT::InTick, None, Some(tick));
-                                k.tick_ms(tick, &None)?;
-                            }
-                            "↓" => {
-                                let key_code = str_to_oscode(val)
-                                    .ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                                kbd_out_log(
-                                    &mut k.kbd_out,
-                                    LogFmtT::InKeyDown,
-                                    Some(key_code),
-                                    None,
-                                );
-                                k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Press))?;
-                            }
-                            "↑" => {
-                                let key_code = str_to_oscode(val)
-                                    .ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                                kbd_out_log(&mut k.kbd_out, LogFmtT::InKeyUp, Some(key_code), None);
-                                k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Release))?;
-                            }
-                            "⟳" => {
-                                let key_code = str_to_oscode(val)
-                                    .ok_or_else(|| anyhow!("unknown key: {val}"))?;
-                                kbd_out_log(
-                                    &mut k.kbd_out,
-                                    LogFmtT::InKeyRep,
-                                    Some(key_code),
-                                    None,
-                                );
-                                k.handle_input_event(&KeyEvent::new(key_code, KeyValue::Repeat))?;
-                            }
-                            "🎭" => {
-                                // Virtual key activation with emoji prefix (defaults to press)
-                                // Format: 🎭vk_name or 🎭vk_name:action
-                                let (vk_name, action) = parse_fakekey_spec(val)?;
-                                apply_fakekey_action(&mut k, vk_name, action)?;
-                            }
-                            "🔀" => {
-                                // Layer switch with emoji prefix
-                                // Format: 🔀layer_name
-                                apply_layer_switch(&mut k, val)?;
-                            }
-                            _ => bail!("invalid pair: {l}"),
-                        }
-                    }
-                }
-            }
+            sim_line(&mut k, l, &mut held)?;
         }
         #[cfg(all(
             not(feature = "simulated_input"),
             not(feature = "passthru_ahk"),
             feature = "simulated_output"
         ))]
-        println!("{}", k.kbd_out.outputs.events.join("\n"));
-        #[cfg(all(
-            not(feature = "simulated_input"),
-            not(feature = "passthru_ahk"),
-            feature = "simulated_output"
-        ))]
-        k.kbd_out.log.end(config_sim_file, _sim_appendix.clone());
+        match sim.fmt {
+            OutputFormat::Text => {
+                println!("{}", k.kbd_out.outputs.events.join("\n"));
+                k.kbd_out.log.end(config_sim_file, sim.appendix.clone());
+            }
+            OutputFormat::Json => {
+                write_json(&k.kbd_out.log.events, config_sim_file, sim.appendix.clone())?;
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Serialize one event as a single-line JSON object.
+#[cfg(all(
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+))]
+fn json_line(ev: &SimEvent) -> String {
+    serde_json::json!({
+        "tick": ev.tick,
+        "direction": ev.kind.direction(),
+        "kind": ev.kind.name(),
+        "key": ev.value,
+        "layer": ev.layer,
+    })
+    .to_string()
+}
+
+/// Print the events as JSON lines (one object per event) to stdout
+/// and optionally save them next to the simulation file.
+#[cfg(all(
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+))]
+fn write_json(
+    events: &[SimEvent],
+    in_path: &std::path::Path,
+    appendix: Option<String>,
+) -> Result<()> {
+    let mut out = String::new();
+    for ev in events {
+        out.push_str(&json_line(ev));
+        out.push('\n');
+    }
+    print!("{out}");
+    if let Some(appendix) = appendix {
+        let out_path = append_file_name(in_path, appendix);
+        std::fs::write(&out_path, out)?;
+        log::info!("Saved output → {}", out_path.display());
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let ret = main_impl();
     if let Err(ref e) = ret {
@@ -382,3 +466,53 @@ fn main() -> Result<()> {
     }
     ret
 }
+
+#[cfg(all(
+    test,
+    not(feature = "simulated_input"),
+    not(feature = "passthru_ahk"),
+    feature = "simulated_output"
+))]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn json_events_carry_layer_active_at_the_time() {
+        let cfg = "
+(defsrc a b c)
+(deflayer base @th b c)
+(deflayer nav _ x _)
+(defalias th (tap-hold 100 100 a (multi lctl (layer-while-held nav))))
+";
+        let mut k = Kanata::new_from_str(cfg, Default::default()).expect("valid config");
+        let mut held = vec![];
+        sim_line(&mut k, "d:c d:a t:150 d:b t:10 u:b u:a u:c t:10", &mut held).unwrap();
+        let events = k
+            .kbd_out
+            .log
+            .events
+            .iter()
+            .map(|ev| serde_json::from_str::<Value>(&json_line(ev)).unwrap())
+            .collect::<Vec<_>>();
+        // The tap-hold resolves to hold partway through `t:150`: the output before it is still on
+        // `base` and the output of the hold action is already on `nav`.
+        assert_eq!(
+            events,
+            [
+                json!({"tick": 0, "direction": "in", "kind": "press", "key": "C", "layer": "base"}),
+                json!({"tick": 0, "direction": "in", "kind": "press", "key": "A", "layer": "base"}),
+                json!({"tick": 0, "direction": "out", "kind": "press", "key": "C", "layer": "base"}),
+                json!({"tick": 101, "direction": "out", "kind": "press", "key": "LCtrl", "layer": "nav"}),
+                json!({"tick": 150, "direction": "in", "kind": "press", "key": "B", "layer": "nav"}),
+                json!({"tick": 150, "direction": "out", "kind": "press", "key": "X", "layer": "nav"}),
+                json!({"tick": 160, "direction": "in", "kind": "release", "key": "B", "layer": "nav"}),
+                json!({"tick": 160, "direction": "in", "kind": "release", "key": "A", "layer": "nav"}),
+                json!({"tick": 160, "direction": "in", "kind": "release", "key": "C", "layer": "nav"}),
+                json!({"tick": 160, "direction": "out", "kind": "release", "key": "X", "layer": "nav"}),
+                json!({"tick": 161, "direction": "out", "kind": "release", "key": "LCtrl", "layer": "base"}),
+                json!({"tick": 162, "direction": "out", "kind": "release", "key": "C", "layer": "base"}),
+            ]
+        );
+    }
+}
@@ -1276,6 +1276,7 @@ impl SystemTray {
         }
     }
     fn exit(&self) {
+        crate::kanata::save_state_before_exit();
         let handlers = self.handlers_dyn.borrow();
         for handler in handlers.iter() {
             nwg::unbind_event_handler(handler);
@@ -0,0 +1,180 @@
+//! Persistence of the timing learned by tap-hold actions with the `adaptive` option, in the
+//! file from `adaptive-tap-hold-file`.
+//!
+//! The file has one line per key with the key name, the learned timeout and prior-idle
+//! threshold, then the statistics of [`AdaptiveKey`] in declaration order. It is written at most
+//! once per [`SAVE_INTERVAL`] while keys are learned, and on live reload and exit.
+
+use super::*;
+use kanata_keyberon::adaptive_tap_hold::{AdaptiveKey, AdaptiveTapHold};
+use kanata_keyberon::layout::REAL_KEY_ROW;
+use kanata_parser::keys::{KEY_NAMES, str_to_oscode};
+use std::path::Path;
+use web_time::{Duration, Instant};
+
+const HEADER: &str = ";; key timeout prior-idle taps holds tap-ms hold-ms false-holds false-taps";
+
+/// Minimum time between two writes of the file while keys are learned.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The file from `adaptive-tap-hold-file` and whether it is up to date.
+#[derive(Debug)]
+pub(crate) struct AdaptiveTapHoldFile {
+    path: PathBuf,
+    last_save: Instant,
+    changed: bool,
+}
+
+impl AdaptiveTapHoldFile {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_save: Instant::now(),
+            changed: false,
+        }
+    }
+
+    /// The file at `path`, after loading its learned keys.
+    pub(crate) fn load(path: PathBuf, learned: &mut AdaptiveTapHold) -> Self {
+        load_adaptive_tap_hold(&path, learned);
+        Self::new(path)
+    }
+
+    /// Save the learned keys if they changed and the save interval elapsed.
+    pub(crate) fn tick(&mut self, learned: &mut AdaptiveTapHold) {
+        self.changed |= learned.take_changed();
+        if self.changed && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.flush(learned);
+        }
+    }
+
+    /// Save the learned keys if they changed since the previous save.
+    pub(crate) fn flush(&mut self, learned: &mut AdaptiveTapHold) {
+        self.changed |= learned.take_changed();
+        if !self.changed {
+            return;
+        }
+        self.last_save = Instant::now();
+        self.changed = false;
+        save_adaptive_tap_hold(&self.path, learned);
+    }
+}
+
+/// The path of `adaptive-tap-hold-file`, relative to the directory of the configuration file.
+pub(crate) fn adaptive_tap_hold_path(cfg_path: &Path, file: Option<&str>) -> Option<PathBuf> {
+    let file = Path::new(file?);
+    Some(match cfg_path.parent() {
+        Some(dir) => dir.join(file),
+        None => file.to_owned(),
+    })
+}
+
+/// The kanata key name of a key code.
+pub(crate) fn kanata_key_name(code: u16) -> String {
+    KEY_NAMES
+        .iter()
+        .copied()
+        .find(|name| str_to_oscode(name).is_some_and(|osc| osc as u16 == code))
+        .map(str::to_owned)
+        .unwrap_or_else(|| OsCode::from(code).to_string().to_lowercase())
+}
+
+/// The learned keys of the physical keys, with their names.
+pub(crate) fn adaptive_keys(learned: &AdaptiveTapHold) -> Vec<(String, AdaptiveKey)> {
+    learned
+        .keys()
+        .filter(|(coord, _)| coord.0 == REAL_KEY_ROW)
+        .map(|(coord, key)| (kanata_key_name(coord.1), *key))
+        .collect()
+}
+
+/// Load the learned keys from `path`. A missing file is not an error since nothing is learned
+/// before the first misfire.
+pub(crate) fn load_adaptive_tap_hold(path: &Path, learned: &mut AdaptiveTapHold) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            log::error!("could not read adaptive tap-hold file {path:?}: {e}");
+            return;
+        }
+    };
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(";;") {
+            continue;
+        }
+        match parse_line(line) {
+            Some((code, key)) => learned.set_key((REAL_KEY_ROW, code), key),
+            None => log::warn!("ignoring invalid line in {path:?}: {line}"),
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<(u16, AdaptiveKey)> {
+    let mut items = line.split_whitespace();
+    let code = str_to_oscode(items.next()?)? as u16;
+    let mut num = || items.next()?.parse::<u64>().ok();
+    let key = AdaptiveKey {
+        timeout: num()?.try_into().ok()?,
+        prior_idle: num()?.try_into().ok()?,
+        taps: num()?.try_into().ok()?,
+        holds: num()?.try_into().ok()?,
+        tap_ticks: num()?,
+        hold_ticks: num()?,
+        false_holds: num()?.try_into().ok()?,
+        false_taps: num()?.try_into().ok()?,
+    };
+    Some((code, key))
+}
+
+/// Write the learned keys to `path`.
+pub(crate) fn save_adaptive_tap_hold(path: &Path, learned: &AdaptiveTapHold) {
+    let mut content = format!("{HEADER}\n");
+    for (name, key) in adaptive_keys(learned) {
+        content.push_str(&format!(
+            "{name} {} {} {} {} {} {} {} {}\n",
+            key.timeout,
+            key.prior_idle,
+            key.taps,
+            key.holds,
+            key.tap_ticks,
+            key.hold_ticks,
+            key.false_holds,
+            key.false_taps,
+        ));
+    }
+    if let Err(e) = std::fs::write(path, content) {
+        log::error!("could not write adaptive tap-hold file {path:?}: {e}");
+    }
+}
+
+#[test]
+fn adaptive_tap_hold_file_round_trips() {
+    let mut learned = AdaptiveTapHold::default();
+    let key = AdaptiveKey {
+        timeout: 210,
+        prior_idle: 130,
+        taps: 120,
+        holds: 14,
+        tap_ticks: 9600,
+        hold_ticks: 4200,
+        false_holds: 2,
+        false_taps: 1,
+    };
+    learned.set_key((REAL_KEY_ROW, OsCode::KEY_LEFTCTRL as u16), key);
+    let path = std::env::temp_dir().join(format!("kanata-adaptive-{}.txt", std::process::id()));
+    save_adaptive_tap_hold(&path, &learned);
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        content,
+        format!("{HEADER}\nlctrl 210 130 120 14 9600 4200 2 1\n")
+    );
+    let mut loaded = AdaptiveTapHold::default();
+    load_adaptive_tap_hold(&path, &mut loaded);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        loaded.keys().collect::<Vec<_>>(),
+        vec![((REAL_KEY_ROW, OsCode::KEY_LEFTCTRL as u16), &key)]
+    );
+}
@@ -0,0 +1,176 @@
+//! Autocorrect from `defautocorrect`: a typo followed by a boundary key is backspaced and
+//! replaced by its correction. A backspace right after a correction undoes it.
+//!
+//! A word that is also a `defexpand` trigger is expanded and not corrected.
+
+use super::*;
+use kanata_parser::trie::GetOrDescendentExistsResult::*;
+
+#[derive(Debug)]
+pub(crate) struct AutocorrectState {
+    /// Toggled by [`CustomAction::Autocorrect`].
+    pub(crate) enabled: bool,
+    /// The keys of the word typed since the last boundary key, as long as they are a prefix of
+    /// some typo.
+    typed: Vec<u16>,
+    /// Whether each key in `typed` was typed with shift.
+    shifted: Vec<bool>,
+    /// The number of keys typed after `typed` stopped being a prefix of any typo. Backspacing
+    /// them can make the word a typo again.
+    unmatched: usize,
+    /// The typo and the correction typed for it at the last boundary key, kept until the next
+    /// key press to undo it.
+    last_correction: Option<LastCorrection>,
+    /// The backspace that undid a correction. It was never pressed, so its release is skipped.
+    swallowed: Option<KeyCode>,
+}
+
+#[derive(Debug)]
+struct LastCorrection {
+    typo: Vec<u16>,
+    shifted: Vec<bool>,
+    /// The number of characters typed for the correction.
+    len: usize,
+    /// The boundary key that ended the typo, typed again after the typo on undo.
+    boundary: ExpandChar,
+}
+
+impl Default for AutocorrectState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            typed: vec![],
+            shifted: vec![],
+            unmatched: 0,
+            last_correction: None,
+            swallowed: None,
+        }
+    }
+}
+
+impl AutocorrectState {
+    fn clear(&mut self) {
+        self.typed.clear();
+        self.shifted.clear();
+        self.unmatched = 0;
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: AutocorrectMode) {
+        self.enabled = match mode {
+            AutocorrectMode::Toggle => !self.enabled,
+            AutocorrectMode::Enable => true,
+            AutocorrectMode::Disable => false,
+        };
+        self.end_word();
+        log::info!("autocorrect enabled: {}", self.enabled);
+    }
+
+    /// Forget the tracked word and the last correction, e.g. after a text expansion.
+    pub(crate) fn end_word(&mut self) {
+        self.clear();
+        self.last_correction = None;
+    }
+
+    /// Whether the release of `k` must be skipped because its press undid a correction.
+    pub(crate) fn take_swallowed_release(&mut self, k: KeyCode) -> bool {
+        if self.swallowed != Some(k) {
+            return false;
+        }
+        self.swallowed = None;
+        true
+    }
+
+    /// Track a newly pressed key. If it is a boundary key after a typo, the typo is backspaced
+    /// and the correction is typed before the boundary key gets pressed. Returns true if the key
+    /// was a backspace that undid a correction, in which case the key must not be pressed.
+    pub(crate) fn press(
+        &mut self,
+        k: KeyCode,
+        cur_keys: &[KeyCode],
+        autocorrect: &Autocorrect,
+        kbd_out: &mut KbdOut,
+    ) -> Result<bool> {
+        if autocorrect.is_empty() || !self.enabled {
+            return Ok(false);
+        }
+        let osc = OsCode::from(k);
+        if osc.is_modifier() {
+            return Ok(false);
+        }
+        let last_correction = self.last_correction.take();
+        if other_mods_held(cur_keys) {
+            self.clear();
+            return Ok(false);
+        }
+        if osc == OsCode::KEY_BACKSPACE {
+            if let Some(correction) = last_correction {
+                log::debug!("undoing correction of {:?}", correction.typo);
+                let typo: Vec<ExpandChar> = correction
+                    .typo
+                    .iter()
+                    .zip(correction.shifted.iter())
+                    .map(|(key, shift)| typed_char(OsCode::from(*key), *shift))
+                    .chain(std::iter::once(correction.boundary))
+                    .collect();
+                // The boundary key is erased as well and typed again after the typo.
+                retype(correction.len + 1, &typo, &[], cur_keys, kbd_out)?;
+                self.swallowed = Some(k);
+                return Ok(true);
+            }
+            if self.unmatched > 0 {
+                self.unmatched -= 1;
+            } else {
+                self.typed.pop();
+                self.shifted.pop();
+            }
+            return Ok(false);
+        }
+        if autocorrect.boundary_keys.contains(&osc) {
+            if self.unmatched == 0
+                && let HasValue(i) = autocorrect.typos.get_or_descendant_exists(&self.typed)
+            {
+                log::debug!("correcting {:?}", self.typed);
+                let correction = &autocorrect.corrections[usize::from(i)];
+                retype(
+                    self.typed.len(),
+                    correction,
+                    &self.shifted,
+                    cur_keys,
+                    kbd_out,
+                )?;
+                self.last_correction = Some(LastCorrection {
+                    typo: std::mem::take(&mut self.typed),
+                    shifted: std::mem::take(&mut self.shifted),
+                    len: correction.len(),
+                    boundary: typed_char(osc, shift_held(cur_keys)),
+                });
+            }
+            self.clear();
+            return Ok(false);
+        }
+        if self.unmatched == 0 {
+            self.typed.push(osc.into());
+            if matches!(
+                autocorrect.typos.get_or_descendant_exists(&self.typed),
+                NotInTrie
+            ) {
+                self.typed.pop();
+                self.unmatched = 1;
+            } else {
+                self.shifted.push(shift_held(cur_keys));
+            }
+        } else {
+            self.unmatched += 1;
+        }
+        Ok(false)
+    }
+}
+
+/// A key as it was typed, with or without shift.
+fn typed_char(osc: OsCode, shift: bool) -> ExpandChar {
+    ExpandChar {
+        osc,
+        mods: if shift { &[KeyCode::LShift] } else { &[] },
+        letter: false,
+    }
+}
@@ -1,7 +1,7 @@
 use kanata_keyberon::key_code::KeyCode;
 use rustc_hash::FxHashSet as HashSet;
 
-use kanata_parser::custom_action::CapsWordCfg;
+use kanata_parser::custom_action::{CapsWordCase, CapsWordCfg, LayerWordCfg};
 
 #[derive(Debug)]
 pub struct CapsWordState {
@@ -11,14 +11,18 @@ pub struct CapsWordState {
     /// An extra list of keys that should **not** terminate the caps_word state, in addition to
     /// keys_to_capitalize, but which don't trigger a capitalization.
     pub keys_nonterminal: HashSet<KeyCode>,
-    /// The configured timeout for caps_word.
-    pub timeout: u16,
-    /// The number of ticks remaining for caps_word, after which its state should be cleared. The
-    /// number of ticks gets reset back to `timeout` when `tick_maybe_add_lsft` is called. The reason
-    /// for having this timeout at all is in case somebody was in the middle of typing a word, had
-    /// to go do something, and forgot that caps_word was active. Having this timeout means that
-    /// shift won't be active for their next keypress.
-    pub timeout_ticks: u16,
+    /// The timeout for caps_word. The reason for having this timeout at all is in case somebody
+    /// was in the middle of typing a word, had to go do something, and forgot that caps_word was
+    /// active. Having this timeout means that shift won't be active for their next keypress.
+    pub timeout: WordTimeout,
+    /// Which keys in `keys_to_capitalize` are capitalized.
+    pub case: CapsWordCase,
+    /// Keys that replace space. Space ends the state if this is `None`.
+    pub separator: Option<&'static [KeyCode]>,
+    /// For camel and pascal case: whether the next key in `keys_to_capitalize` gets capitalized.
+    capitalize_next: bool,
+    /// For camel and pascal case: the key that is currently held and capitalized.
+    capitalized_key: Option<KeyCode>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -29,13 +33,51 @@ pub enum CapsWordNextState {
 
 use CapsWordNextState::*;
 
+/// Timeout and termination rules shared by caps-word and layer-word.
+#[derive(Debug)]
+pub struct WordTimeout {
+    /// The configured timeout.
+    pub timeout: u16,
+    /// The number of ticks remaining, after which the state should be cleared. The number of
+    /// ticks gets reset back to `timeout` on every tick where keys are active.
+    pub timeout_ticks: u16,
+}
+
+impl WordTimeout {
+    fn new(timeout: u16) -> Self {
+        Self {
+            timeout,
+            timeout_ticks: timeout,
+        }
+    }
+
+    /// Ends the word if the timeout expired or if any active key is terminating.
+    fn tick(
+        &mut self,
+        active_keys: &[KeyCode],
+        is_nonterminal: impl Fn(&KeyCode) -> bool,
+    ) -> CapsWordNextState {
+        if self.timeout_ticks == 0 || !active_keys.iter().all(is_nonterminal) {
+            return End;
+        }
+        if !active_keys.is_empty() {
+            self.timeout_ticks = self.timeout;
+        }
+        self.timeout_ticks = self.timeout_ticks.saturating_sub(1);
+        Active
+    }
+}
+
 impl CapsWordState {
     pub(crate) fn new(cfg: &CapsWordCfg) -> Self {
         Self {
             keys_to_capitalize: cfg.keys_to_capitalize.iter().copied().collect(),
             keys_nonterminal: cfg.keys_nonterminal.iter().copied().collect(),
-            timeout: cfg.timeout,
-            timeout_ticks: cfg.timeout,
+            timeout: WordTimeout::new(cfg.timeout),
+            case: cfg.case,
+            separator: cfg.separator,
+            capitalize_next: cfg.case == CapsWordCase::Pascal,
+            capitalized_key: None,
         }
     }
 
@@ -43,26 +85,83 @@ impl CapsWordState {
         &mut self,
         active_keys: &mut Vec<KeyCode>,
     ) -> CapsWordNextState {
-        if self.timeout_ticks == 0 {
+        let space_is_separator = self.separator.is_some();
+        let next = self.timeout.tick(active_keys, |kc| {
+            self.keys_to_capitalize.contains(kc)
+                || self.keys_nonterminal.contains(kc)
+                || (space_is_separator && *kc == KeyCode::Space)
+        });
+        if next == End {
             log::trace!("caps-word ended");
             return End;
         }
-        for kc in active_keys.iter() {
-            if !self.keys_to_capitalize.contains(kc) && !self.keys_nonterminal.contains(kc) {
-                return End;
+        let last_key = active_keys
+            .last()
+            .copied()
+            .filter(|kc| self.keys_to_capitalize.contains(kc));
+        let capitalize = match (self.case, last_key) {
+            (_, None) => false,
+            (CapsWordCase::Upper, Some(_)) => true,
+            (CapsWordCase::Lower, Some(_)) => false,
+            (CapsWordCase::Camel | CapsWordCase::Pascal, Some(kc)) => {
+                if self.capitalize_next {
+                    self.capitalize_next = false;
+                    self.capitalized_key = Some(kc);
+                }
+                self.capitalized_key == Some(kc)
             }
+        };
+        if !self
+            .capitalized_key
+            .is_some_and(|kc| active_keys.contains(&kc))
+        {
+            self.capitalized_key = None;
         }
-        if active_keys
-            .last()
-            .map(|kc| self.keys_to_capitalize.contains(kc))
-            .unwrap_or(false)
+        if let Some(separator) = self.separator
+            && let Some(space_idx) = active_keys.iter().position(|kc| *kc == KeyCode::Space)
         {
-            active_keys.insert(0, KeyCode::LShift);
+            active_keys.splice(space_idx..=space_idx, separator.iter().copied());
+            if last_key.is_none() && matches!(self.case, CapsWordCase::Camel | CapsWordCase::Pascal)
+            {
+                self.capitalize_next = true;
+            }
         }
-        if !active_keys.is_empty() {
-            self.timeout_ticks = self.timeout;
+        if capitalize {
+            active_keys.insert(0, KeyCode::LShift);
         }
-        self.timeout_ticks = self.timeout_ticks.saturating_sub(1);
         Active
     }
 }
+
+/// State of `layer-word`: a layer that stays active until a terminating key is pressed or the
+/// timeout expires, with the same rules as caps-word. The layer itself is kept active through
+/// the layout's `word_layer`.
+#[derive(Debug)]
+pub struct LayerWordState {
+    /// The layer that is active while this state is active.
+    pub layer: u16,
+    /// Keys that do **not** terminate the layer-word state.
+    pub keys_nonterminal: HashSet<KeyCode>,
+    /// The timeout for layer-word.
+    pub timeout: WordTimeout,
+}
+
+impl LayerWordState {
+    pub(crate) fn new(cfg: &LayerWordCfg) -> Self {
+        Self {
+            layer: cfg.layer,
+            keys_nonterminal: cfg.keys_nonterminal.iter().copied().collect(),
+            timeout: WordTimeout::new(cfg.timeout),
+        }
+    }
+
+    pub(crate) fn tick(&mut self, active_keys: &[KeyCode]) -> CapsWordNextState {
+        let next = self
+            .timeout
+            .tick(active_keys, |kc| self.keys_nonterminal.contains(kc));
+        if next == End {
+            log::trace!("layer-word ended");
+        }
+        next
+    }
+}
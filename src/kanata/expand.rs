@@ -0,0 +1,157 @@
+//! Text expansion from `defexpand`: a trigger word followed by a boundary key is backspaced and
+//! replaced by its expansion.
+
+use super::*;
+
+/// The word typed since the last boundary key.
+#[derive(Debug, Default)]
+pub(crate) struct ExpandState {
+    /// The keys of the word, up to the length of the longest trigger.
+    typed: Vec<OsCode>,
+    /// Whether each key in `typed` was typed with shift.
+    shifted: Vec<bool>,
+    /// The number of keys typed after `typed` was already longer than any trigger. A word this
+    /// long cannot be expanded but backspace must still be tracked.
+    overflow: usize,
+}
+
+impl ExpandState {
+    fn clear(&mut self) {
+        self.typed.clear();
+        self.shifted.clear();
+        self.overflow = 0;
+    }
+
+    /// Track a newly pressed key. If it is a boundary key after a trigger, the trigger is
+    /// backspaced and the expansion is typed before the boundary key gets pressed. Returns true
+    /// if an expansion was typed.
+    pub(crate) fn press(
+        &mut self,
+        k: KeyCode,
+        cur_keys: &[KeyCode],
+        expansions: &Expansions,
+        kbd_out: &mut KbdOut,
+    ) -> Result<bool> {
+        if expansions.is_empty() {
+            return Ok(false);
+        }
+        let osc = OsCode::from(k);
+        if osc.is_modifier() {
+            return Ok(false);
+        }
+        let shift_held = shift_held(cur_keys);
+        let other_mods_held = other_mods_held(cur_keys);
+        if other_mods_held {
+            self.clear();
+            return Ok(false);
+        }
+        if osc == OsCode::KEY_BACKSPACE {
+            if self.overflow > 0 {
+                self.overflow -= 1;
+            } else {
+                self.typed.pop();
+                self.shifted.pop();
+            }
+            return Ok(false);
+        }
+        if expansions.boundary_keys.contains(&osc) {
+            let mut expanded = false;
+            if self.overflow == 0
+                && let Some(expansion) = expansions.expansions.get(&self.typed[..])
+            {
+                log::debug!("expanding {:?}", self.typed);
+                retype(
+                    self.typed.len(),
+                    expansion,
+                    &self.shifted,
+                    cur_keys,
+                    kbd_out,
+                )?;
+                expanded = true;
+            }
+            self.clear();
+            return Ok(expanded);
+        }
+        if self.typed.len() < expansions.max_trigger_len {
+            self.typed.push(osc);
+            self.shifted.push(shift_held);
+        } else {
+            self.overflow += 1;
+        }
+        Ok(false)
+    }
+}
+
+pub(crate) fn shift_held(cur_keys: &[KeyCode]) -> bool {
+    cur_keys
+        .iter()
+        .any(|kc| matches!(kc, KeyCode::LShift | KeyCode::RShift))
+}
+
+/// Whether a modifier other than shift is held, in which case a key press is a shortcut rather
+/// than part of a word.
+pub(crate) fn other_mods_held(cur_keys: &[KeyCode]) -> bool {
+    cur_keys.iter().any(|kc| {
+        matches!(
+            kc,
+            KeyCode::LCtrl
+                | KeyCode::RCtrl
+                | KeyCode::LAlt
+                | KeyCode::RAlt
+                | KeyCode::LGui
+                | KeyCode::RGui
+        )
+    })
+}
+
+/// Backspace a typed word and type its replacement, carrying over the case of the word as given
+/// by `shifted`: a capitalized word capitalizes the first letter and a word typed fully in
+/// uppercase capitalizes all letters.
+pub(crate) fn retype(
+    backspaces: usize,
+    replacement: &[ExpandChar],
+    shifted: &[bool],
+    cur_keys: &[KeyCode],
+    kbd_out: &mut KbdOut,
+) -> Result<()> {
+    let all_caps = shifted.len() > 1 && shifted.iter().all(|s| *s);
+    let mut capitalize_next = shifted.first().copied().unwrap_or(false);
+    let held_shifts: Vec<OsCode> = cur_keys
+        .iter()
+        .filter(|kc| matches!(kc, KeyCode::LShift | KeyCode::RShift))
+        .map(|kc| OsCode::from(*kc))
+        .collect();
+    for osc in held_shifts.iter().copied() {
+        release_key(kbd_out, osc)?;
+    }
+    for _ in 0..backspaces {
+        press_key(kbd_out, OsCode::KEY_BACKSPACE)?;
+        release_key(kbd_out, OsCode::KEY_BACKSPACE)?;
+    }
+    for c in replacement.iter() {
+        let mut capitalize = false;
+        if c.letter {
+            capitalize = all_caps || capitalize_next;
+            capitalize_next = false;
+        }
+        let add_shift = capitalize && !shift_held(c.mods);
+        for osc in c.mods.iter().map(|kc| OsCode::from(*kc)) {
+            press_key(kbd_out, osc)?;
+        }
+        if add_shift {
+            press_key(kbd_out, OsCode::KEY_LEFTSHIFT)?;
+        }
+        press_key(kbd_out, c.osc)?;
+        release_key(kbd_out, c.osc)?;
+        if add_shift {
+            release_key(kbd_out, OsCode::KEY_LEFTSHIFT)?;
+        }
+        for osc in c.mods.iter().rev().map(|kc| OsCode::from(*kc)) {
+            release_key(kbd_out, osc)?;
+        }
+    }
+    for osc in held_shifts.iter().copied() {
+        press_key(kbd_out, osc)?;
+    }
+    Ok(())
+}
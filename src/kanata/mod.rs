@@ -64,9 +64,9 @@ use std::sync::mpsc::Sender as ASender;
 
 use kanata_keyberon::action::ReleasableState;
 use kanata_keyberon::key_code::*;
-use kanata_keyberon::layout::{CustomEvent, Event, Layout, State};
+use kanata_keyberon::layout::{CustomEvent, Event, Layout, State, SwapHands};
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time;
 
@@ -89,6 +89,13 @@ use clipboard::*;
 mod dynamic_macro;
 use dynamic_macro::*;
 
+mod adaptive_tap_hold;
+pub(crate) use adaptive_tap_hold::*;
+mod autocorrect;
+use autocorrect::*;
+mod expand;
+use expand::*;
+
 mod key_repeat;
 
 mod millisecond_counting;
@@ -100,6 +107,9 @@ use scroll::*;
 mod sequences;
 use sequences::*;
 
+mod stats;
+use stats::*;
+
 pub mod cfg_forced;
 use cfg_forced::*;
 
@@ -145,6 +155,23 @@ pub(crate) static PRESSED_KEYS: Lazy<Mutex<HashMap<OsCode, web_time::Instant>>>
 /// Configurable via --emergency-exit-code CLI argument. Default is 0.
 pub static EMERGENCY_EXIT_CODE: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
 
+/// The running kanata, for saving learned state when exiting.
+static RUNNING_KANATA: Mutex<Option<std::sync::Weak<Mutex<Kanata>>>> = Mutex::new(None);
+
+/// Save the state of the running kanata that is written to files periodically, before exiting.
+///
+/// The processing loop may hold the lock, so this gives up after a short wait instead of
+/// blocking the exit.
+pub(crate) fn save_state_before_exit() {
+    let Some(kanata) = RUNNING_KANATA.lock().as_ref().and_then(|k| k.upgrade()) else {
+        return;
+    };
+    match kanata.try_lock_for(std::time::Duration::from_millis(100)) {
+        Some(mut k) => k.save_state(),
+        None => log::warn!("could not save state before exiting: kanata is busy"),
+    };
+}
+
 pub struct Kanata {
     /// Handle to some OS keyboard output mechanism.
     pub kbd_out: KbdOut,
@@ -169,6 +196,8 @@ pub struct Kanata {
     pub layer_info: Vec<LayerInfo>,
     /// Used to track when a layer change occurs.
     pub prev_layer: usize,
+    /// Used to track when a layer is locked or unlocked.
+    pub prev_locked_layer: Option<u16>,
     /// Vertical scrolling state tracker. Is Some(...) when a vertical scrolling action is active
     /// and None otherwise.
     pub scroll_state: Option<ScrollState>,
@@ -258,6 +287,8 @@ pub struct Kanata {
     log_layer_changes: bool,
     /// Tracks the caps-word state. Is Some(...) if caps-word is active and None otherwise.
     pub caps_word: Option<CapsWordState>,
+    /// Tracks the layer-word state. Is Some(...) if layer-word is active and None otherwise.
+    pub layer_word: Option<LayerWordState>,
     /// Config items from `defcfg`.
     #[cfg(any(target_os = "linux", target_os = "android"))]
     pub x11_repeat_rate: Option<KeyRepeatSettings>,
@@ -302,8 +333,27 @@ pub struct Kanata {
     unshifted_keys: Vec<KeyCode>,
     /// Keep track of last pressed key for [`CustomAction::Repeat`].
     last_pressed_key: KeyCode,
+    /// Mod mask of the modifiers held when `last_pressed_key` was pressed, for
+    /// [`CustomAction::AltRepeat`].
+    last_pressed_mods: u16,
+    /// Outputs of [`CustomAction::AltRepeat`] from `defaltrepeat`.
+    alt_repeats: Box<[AltRepeat]>,
+    /// Text expansions from `defexpand`.
+    expansions: Expansions,
+    /// Tracks the word being typed for text expansion.
+    expand_state: ExpandState,
+    /// Typos and their corrections from `defautocorrect`.
+    autocorrect: Autocorrect,
+    /// Tracks the word being typed for autocorrect.
+    autocorrect_state: AutocorrectState,
+    /// File that the timing learned by adaptive tap-hold actions is saved to.
+    adaptive_tap_hold_file: Option<AdaptiveTapHoldFile>,
+    /// Statistics of key usage, if enabled with `stats` in `defcfg`.
+    pub(crate) stats: Option<StatsCollector>,
     /// Names of fake keys mapped to their index in the fake keys row
     pub virtual_keys: HashMap<String, usize>,
+    /// Names of the state variables from `defstate`, in the order of their values in the layout.
+    pub state_var_names: Vec<String>,
     /// The maximum value of the any time-dependent check in the configuration.
     pub max_key_timing_check: u16,
     #[cfg(feature = "tcp_server")]
@@ -447,16 +497,25 @@ impl Kanata {
             zch().zch_configure(cfg.zippy.unwrap_or_default());
         }
 
+        let stats = StatsCollector::from_cfg(&cfg.options, &args.paths[0], None);
+        let mut layout = cfg.layout;
+        let adaptive_tap_hold_file = adaptive_tap_hold_path(
+            &args.paths[0],
+            cfg.options.adaptive_tap_hold_file.as_deref(),
+        )
+        .map(|path| AdaptiveTapHoldFile::load(path, &mut layout.bm().adaptive_tap_hold));
+
         Ok(Self {
             kbd_out,
             cfg_paths: args.paths.clone(),
             cur_cfg_idx: 0,
             key_outputs: cfg.key_outputs,
-            layout: cfg.layout,
+            layout,
             layer_info: cfg.layer_info,
             cur_keys: Vec::new(),
             prev_keys: Vec::new(),
             prev_layer: 0,
+            prev_locked_layer: None,
             scroll_state: None,
             hscroll_state: None,
             move_mouse_state_vertical: None,
@@ -512,6 +571,7 @@ impl Kanata {
             log_layer_changes: get_forced_log_layer_changes()
                 .unwrap_or(cfg.options.log_layer_changes),
             caps_word: None,
+            layer_word: None,
             movemouse_smooth_diagonals: cfg.options.movemouse_smooth_diagonals,
             override_release_on_activation: cfg.options.override_release_on_activation,
             movemouse_inherit_accel_state: cfg.options.movemouse_inherit_accel_state,
@@ -537,7 +597,16 @@ impl Kanata {
             unmodded_mods: UnmodMods::empty(),
             unshifted_keys: vec![],
             last_pressed_key: KeyCode::No,
+            last_pressed_mods: 0,
+            alt_repeats: cfg.alt_repeats,
+            expansions: cfg.expansions,
+            expand_state: ExpandState::default(),
+            autocorrect: cfg.autocorrect,
+            autocorrect_state: AutocorrectState::default(),
+            adaptive_tap_hold_file,
+            stats,
             virtual_keys: cfg.fake_keys,
+            state_var_names: cfg.state_vars.into_iter().map(|v| v.name).collect(),
             max_key_timing_check: cfg.max_key_timing_check,
             input_devices: cfg.input_devices,
             #[cfg(feature = "tcp_server")]
@@ -601,16 +670,23 @@ impl Kanata {
             zch().zch_configure(cfg.zippy.unwrap_or_default());
         }
 
+        let stats = StatsCollector::from_cfg(&cfg.options, Path::new(""), None);
+        let mut layout = cfg.layout;
+        let adaptive_tap_hold_file = cfg.options.adaptive_tap_hold_file.map(|path| {
+            AdaptiveTapHoldFile::load(PathBuf::from(path), &mut layout.bm().adaptive_tap_hold)
+        });
+
         Ok(Self {
             kbd_out,
             cfg_paths: vec!["config string".into()],
             cur_cfg_idx: 0,
             key_outputs: cfg.key_outputs,
-            layout: cfg.layout,
+            layout,
             layer_info: cfg.layer_info,
             cur_keys: Vec::new(),
             prev_keys: Vec::new(),
             prev_layer: 0,
+            prev_locked_layer: None,
             scroll_state: None,
             hscroll_state: None,
             move_mouse_state_vertical: None,
@@ -666,6 +742,7 @@ impl Kanata {
             log_layer_changes: get_forced_log_layer_changes()
                 .unwrap_or(cfg.options.log_layer_changes),
             caps_word: None,
+            layer_word: None,
             movemouse_smooth_diagonals: cfg.options.movemouse_smooth_diagonals,
             override_release_on_activation: cfg.options.override_release_on_activation,
             movemouse_inherit_accel_state: cfg.options.movemouse_inherit_accel_state,
@@ -691,7 +768,16 @@ impl Kanata {
             unmodded_mods: UnmodMods::empty(),
             unshifted_keys: vec![],
             last_pressed_key: KeyCode::No,
+            last_pressed_mods: 0,
+            alt_repeats: cfg.alt_repeats,
+            expansions: cfg.expansions,
+            expand_state: ExpandState::default(),
+            autocorrect: cfg.autocorrect,
+            autocorrect_state: AutocorrectState::default(),
+            adaptive_tap_hold_file,
+            stats,
             virtual_keys: cfg.fake_keys,
+            state_var_names: cfg.state_vars.into_iter().map(|v| v.name).collect(),
             max_key_timing_check: cfg.max_key_timing_check,
             input_devices: cfg.input_devices,
             #[cfg(feature = "tcp_server")]
@@ -729,6 +815,16 @@ impl Kanata {
         Ok(Arc::new(Mutex::new(k)))
     }
 
+    /// Write the state that is saved periodically, if it changed since it was last saved.
+    pub(crate) fn save_state(&mut self) {
+        if let Some(file) = &mut self.adaptive_tap_hold_file {
+            file.flush(&mut self.layout.bm().adaptive_tap_hold);
+        }
+        if let Some(stats) = &mut self.stats {
+            stats.flush();
+        }
+    }
+
     fn do_live_reload(&mut self, _tx: &Option<Sender<ServerMessage>>) -> Result<()> {
         let cfg = match cfg::new_from_file(&self.cfg_paths[self.cur_cfg_idx]) {
             Ok(c) => c,
@@ -748,7 +844,22 @@ impl Kanata {
         self.sequence_always_on = cfg.options.sequence_always_on;
         self.sequence_input_mode = cfg.options.sequence_input_mode;
         self.sequence_timeout = cfg.options.sequence_timeout;
-        self.layout = cfg.layout;
+        self.save_state();
+        let mut layout = cfg.layout;
+        for (coord, key) in self.layout.b().adaptive_tap_hold.keys() {
+            layout.bm().adaptive_tap_hold.set_key(coord, *key);
+        }
+        self.layout = layout;
+        self.adaptive_tap_hold_file = adaptive_tap_hold_path(
+            &self.cfg_paths[self.cur_cfg_idx],
+            cfg.options.adaptive_tap_hold_file.as_deref(),
+        )
+        .map(AdaptiveTapHoldFile::new);
+        self.stats = StatsCollector::from_cfg(
+            &cfg.options,
+            &self.cfg_paths[self.cur_cfg_idx],
+            self.stats.take(),
+        );
         self.key_outputs = cfg.key_outputs;
         self.layer_info = cfg.layer_info;
         self.sequences = cfg.sequences;
@@ -768,6 +879,12 @@ impl Kanata {
         // This matches behavior of other device configs (macos-dev-names-include, etc.).
         // See: https://github.com/malpern/kanata/issues/13
         self.virtual_keys = cfg.fake_keys;
+        self.alt_repeats = cfg.alt_repeats;
+        self.expansions = cfg.expansions;
+        self.expand_state = ExpandState::default();
+        self.autocorrect = cfg.autocorrect;
+        self.autocorrect_state = AutocorrectState::default();
+        self.state_var_names = cfg.state_vars.into_iter().map(|v| v.name).collect();
         #[cfg(target_os = "windows")]
         {
             self.windows_sync_keystates = cfg.options.windows_opts.windows_sync_keystates;
@@ -897,10 +1014,17 @@ impl Kanata {
                         !matches!(s, State::FakeKey { .. } | State::RepeatingSequence { .. })
                     });
                 }
+                if let Some(stats) = &mut self.stats {
+                    let layer = self.layout.b().current_layer();
+                    stats.press(event.code, &self.layer_info[layer].name);
+                }
                 Event::Press(0, evc)
             }
             KeyValue::Release => {
                 record_release(&mut self.dynamic_macro_record_state, event.code);
+                if let Some(stats) = &mut self.stats {
+                    stats.release(event.code);
+                }
                 Event::Release(0, evc)
             }
             KeyValue::Repeat => {
@@ -1221,9 +1345,46 @@ impl Kanata {
     fn handle_keystate_changes(&mut self, _tx: &Option<Sender<ServerMessage>>) -> Result<bool> {
         let layout = self.layout.bm();
         let custom_event = layout.tick();
+        #[cfg(all(feature = "simulated_output", not(feature = "simulated_input")))]
+        self.kbd_out
+            .log
+            .set_layer(&self.layer_info[layout.current_layer()].name);
+
+        if let Some(file) = &mut self.adaptive_tap_hold_file {
+            file.tick(&mut layout.adaptive_tap_hold);
+        }
+
+        let hold_activated = layout.tap_hold_tracker.take_hold_activated();
+        let tap_activated = layout.tap_hold_tracker.take_tap_activated();
+        let activated_chord = layout.chords_v2.as_mut().and_then(|ch| ch.take_activated());
+        let completed_sequence = self.sequence_state.completed.take();
+        if let Some(stats) = &mut self.stats {
+            stats.tick();
+            if let Some(hold_info) = hold_activated
+                && hold_info.coord.0 == NORMAL_KEY_ROW
+            {
+                stats.hold(OsCode::from(hold_info.coord.1));
+            }
+            if let Some(tap_info) = tap_activated
+                && tap_info.coord.0 == NORMAL_KEY_ROW
+            {
+                stats.tap(OsCode::from(tap_info.coord.1));
+            }
+            if let Some(keys) = activated_chord {
+                stats.chord(keys);
+            }
+            if let Some(j) = completed_sequence
+                && let Some((name, _)) = self
+                    .virtual_keys
+                    .iter()
+                    .find(|(_, idx)| **idx == usize::from(j))
+            {
+                stats.sequence(name);
+            }
+        }
 
         #[cfg(feature = "tcp_server")]
-        if let Some(hold_info) = layout.tap_hold_tracker.take_hold_activated()
+        if let Some(hold_info) = hold_activated
             && hold_info.coord.0 == NORMAL_KEY_ROW
             && let Some(tx) = _tx
         {
@@ -1238,7 +1399,7 @@ impl Kanata {
             }
         }
         #[cfg(feature = "tcp_server")]
-        if let Some(tap_info) = layout.tap_hold_tracker.take_tap_activated()
+        if let Some(tap_info) = tap_activated
             && tap_info.coord.0 == NORMAL_KEY_ROW
             && let Some(tx) = _tx
         {
@@ -1341,6 +1502,13 @@ impl Kanata {
             self.caps_word = None;
         }
 
+        if let Some(layer_word) = &mut self.layer_word
+            && layer_word.tick(cur_keys) == CapsWordNextState::End
+        {
+            layout.word_layer = None;
+            self.layer_word = None;
+        }
+
         // Release keys that do not exist in the current state but exist in the previous state.
         // This used to use a HashSet but it was changed to a Vec because the order of operations
         // matters.
@@ -1384,7 +1552,7 @@ impl Kanata {
             true => &mut rev_release,
         };
         for k in keys {
-            if cur_keys.contains(k) {
+            if cur_keys.contains(k) || self.autocorrect_state.take_swallowed_release(*k) {
                 continue;
             }
             log::debug!("key release   {:?}", k);
@@ -1441,6 +1609,7 @@ impl Kanata {
             // allocations and logic.
             self.prev_keys.push(*k);
             self.last_pressed_key = *k;
+            self.last_pressed_mods = get_mod_mask_for_cur_keys(cur_keys);
 
             if self.sequence_always_on && self.sequence_state.is_inactive() {
                 self.sequence_state
@@ -1458,6 +1627,20 @@ impl Kanata {
                     layout,
                 )?;
             } else {
+                let expanded =
+                    self.expand_state
+                        .press(*k, cur_keys, &self.expansions, &mut self.kbd_out)?;
+                if expanded {
+                    // An expansion takes precedence over correcting the same word.
+                    self.autocorrect_state.end_word();
+                } else if self.autocorrect_state.press(
+                    *k,
+                    cur_keys,
+                    &self.autocorrect,
+                    &mut self.kbd_out,
+                )? {
+                    continue;
+                }
                 log::debug!("key press     {:?}", k);
                 if let Err(e) = press_key(&mut self.kbd_out, k.into()) {
                     bail!("failed to press key: {:?}", e);
@@ -1662,6 +1845,11 @@ impl Kanata {
                     CustomAction::CmdOutputKeys(_cmd) => {
                         #[cfg(feature = "cmd")]
                         {
+                            #[cfg(all(
+                                feature = "simulated_output",
+                                not(feature = "simulated_input")
+                            ))]
+                            self.kbd_out.log_cmd(_cmd);
                             // Maybe improvement in the future:
                             // A delay here, as in KeyAction::Delay, will pause the entire
                             // state machine loop. That is _probably_ OK, but ideally this
@@ -1743,9 +1931,10 @@ impl Kanata {
                         if !cur_keys.contains(&KeyCode::LShift)
                             && let Some(ref mut cw) = self.caps_word {
                                 cur_keys.push(keycode);
-                                let prev_len = cur_keys.len();
                                 cw.tick_maybe_add_lsft(cur_keys);
-                                if cur_keys.len() > prev_len {
+                                // Check for shift instead of a length change because a case-word
+                                // separator can also change the length.
+                                if cur_keys.contains(&KeyCode::LShift) {
                                     do_caps_word = true;
                                     press_key(&mut self.kbd_out, OsCode::KEY_LEFTSHIFT)?;
                                 }
@@ -1758,6 +1947,44 @@ impl Kanata {
                             self.kbd_out.release_key(OsCode::KEY_LEFTSHIFT)?;
                         }
                     }
+                    CustomAction::AltRepeat => {
+                        let key = u16::from(OsCode::from(self.last_pressed_key));
+                        let mods = self.last_pressed_mods;
+                        let alt_repeat = self
+                            .alt_repeats
+                            .iter()
+                            .find(|r| r.from == key | mods)
+                            .or_else(|| self.alt_repeats.iter().find(|r| r.from == key));
+                        match alt_repeat {
+                            Some(alt_repeat) => {
+                                log::debug!("alt-repeating {:?} as {:?}", self.last_pressed_key, alt_repeat.to);
+                                // Modifiers held with the last key that were not part of the
+                                // matched entry are kept for the output.
+                                let mod_mask = alt_repeat.mods | (mods & !alt_repeat.from);
+                                let mods_to_press: Vec<OsCode> = keycodes_for_mod_mask(mod_mask)
+                                    .filter(|kc| {
+                                        !cur_keys.iter().any(|held| {
+                                            mod_mask_for_keycode(*held) == mod_mask_for_keycode(*kc)
+                                        })
+                                    })
+                                    .map(OsCode::from)
+                                    .collect();
+                                for osc in mods_to_press.iter().copied() {
+                                    press_key(&mut self.kbd_out, osc)?;
+                                }
+                                release_key(&mut self.kbd_out, alt_repeat.to)?;
+                                press_key(&mut self.kbd_out, alt_repeat.to)?;
+                                release_key(&mut self.kbd_out, alt_repeat.to)?;
+                                for osc in mods_to_press.iter().rev().copied() {
+                                    release_key(&mut self.kbd_out, osc)?;
+                                }
+                            }
+                            None => log::debug!(
+                                "no alt-repeat output for {:?}",
+                                self.last_pressed_key
+                            ),
+                        }
+                    }
                     CustomAction::DynamicMacroRecord(macro_id) => {
                         if let Some((macro_id, prev_recorded_macro)) =
                             begin_record_macro(*macro_id, &mut self.dynamic_macro_record_state)
@@ -1808,6 +2035,11 @@ impl Kanata {
                             };
                         }
                     },
+                    CustomAction::LayerWord(cfg) => {
+                        log::trace!("layer-word");
+                        layout.word_layer = Some(cfg.layer);
+                        self.layer_word = Some(LayerWordState::new(cfg));
+                    }
                     CustomAction::SetMouse { x, y } => {
                         self.kbd_out.set_mouse(*x, *y)?;
                     }
@@ -1850,6 +2082,32 @@ impl Kanata {
                     CustomAction::ClipboardSaveSwap(id1, id2) => {
                         clpb_save_swap(*id1, *id2, &mut self.saved_clipboard_content);
                     }
+                    CustomAction::StateVar { var, op } => {
+                        let value = &mut layout.state_vars[usize::from(*var)];
+                        *value = op.apply(*value);
+                        let value = *value;
+                        log::debug!(
+                            "state variable {} is now {value}",
+                            self.state_var_names[usize::from(*var)]
+                        );
+                    }
+                    CustomAction::AutoShiftToggle => {
+                        if let Some(auto_shift) = layout.auto_shift.as_mut() {
+                            auto_shift.enabled = !auto_shift.enabled;
+                            log::info!("auto-shift enabled: {}", auto_shift.enabled);
+                        }
+                    }
+                    CustomAction::Autocorrect(mode) => {
+                        self.autocorrect_state.set_mode(*mode);
+                    }
+                    CustomAction::SwapHands(mode) => {
+                        layout.swap_hands = match (mode, layout.swap_hands) {
+                            (SwapHandsMode::Toggle, SwapHands::On) => SwapHands::Off,
+                            (SwapHandsMode::Hold | SwapHandsMode::Toggle, _) => SwapHands::On,
+                            (SwapHandsMode::OneShot, _) => SwapHands::OneShot,
+                        };
+                        log::debug!("swap hands: {:?}", layout.swap_hands);
+                    }
                     CustomAction::FakeKeyOnRelease { .. }
                     | CustomAction::DelayOnRelease(_)
                     | CustomAction::Unmodded { .. }
@@ -1858,6 +2116,14 @@ impl Kanata {
                     | CustomAction::ReverseReleaseOrder
                     | CustomAction::CancelMacroOnRelease => {}
                 }
+                #[cfg(all(
+                    feature = "cmd",
+                    feature = "simulated_output",
+                    not(feature = "simulated_input")
+                ))]
+                for (_, _, cmd) in cmds.iter() {
+                    self.kbd_out.log_cmd(cmd);
+                }
                 #[cfg(feature = "cmd")]
                 run_multi_cmd(cmds);
 
@@ -1904,6 +2170,9 @@ impl Kanata {
                 CustomAction::Mouse(btn) => {
                     self.kbd_out.release_btn(*btn)?;
                 }
+                CustomAction::SwapHands(SwapHandsMode::Hold) => {
+                    layout.swap_hands = SwapHands::Off;
+                }
                 CustomAction::MWheel { direction, .. } => match direction {
                     MWheelDirection::Up | MWheelDirection::Down => {
                         if let Some(ss) = &mut self.scroll_state
@@ -2022,6 +2291,13 @@ impl Kanata {
         );
     }
 
+    /// Reload the current configuration file right away,
+    /// without waiting for keys to be released.
+    /// Used by the simulator, which has no processing loop to handle reload requests.
+    pub fn live_reload_now(&mut self) -> Result<()> {
+        self.do_live_reload(&None)
+    }
+
     /// Handle a client command from TCP server and return a result.
     /// This centralizes validation logic and provides proper error messages.
     #[cfg(feature = "tcp_server")]
@@ -2138,6 +2414,23 @@ impl Kanata {
             #[cfg(all(target_os = "windows", feature = "gui"))]
             send_gui_notice();
         }
+
+        let locked_layer = self.layout.b().locked_layer;
+        if locked_layer != self.prev_locked_layer {
+            self.prev_locked_layer = locked_layer;
+            let locked = locked_layer.map(|layer| self.layer_info[usize::from(layer)].name.clone());
+            log::info!("Locked layer: {}", locked.as_deref().unwrap_or("none"));
+
+            #[cfg(feature = "tcp_server")]
+            if let Some(tx) = tx {
+                match tx.try_send(ServerMessage::LayerLock { locked }) {
+                    Ok(_) => {}
+                    Err(error) => {
+                        log::error!("could not send event notification: {}", error);
+                    }
+                }
+            }
+        }
     }
 
     fn print_layer(&self, layer: usize) {
@@ -2221,6 +2514,7 @@ impl Kanata {
         nodelay: bool,
     ) {
         info!("entering the processing loop");
+        *RUNNING_KANATA.lock() = Some(Arc::downgrade(&kanata));
         std::thread::spawn(move || {
             // Elevate the processing thread to the highest QoS class so that
             // CPU-intensive background work (compilation, indexing, etc.) does
@@ -2554,6 +2848,7 @@ impl Kanata {
             && self.move_mouse_state_horizontal.is_none()
             && self.dynamic_macro_replay_state.is_none()
             && self.caps_word.is_none()
+            && self.layer_word.is_none()
             && self.vkeys_pending_release.is_empty()
             && !layout.states.iter().any(|s| {
                 matches!(s, State::SeqCustomPending(_) | State::SeqCustomActive(_))
@@ -2675,6 +2970,7 @@ fn check_for_exit(_event: &KeyEvent) {
         if IS_ESC_PRESSED.load(SeqCst) && IS_SPC_PRESSED.load(SeqCst) && IS_LCL_PRESSED.load(SeqCst)
         {
             log::info!("{EXIT_MSG}");
+            save_state_before_exit();
             #[cfg(all(target_os = "windows", feature = "gui"))]
             {
                 #[cfg(not(feature = "interception_driver"))]
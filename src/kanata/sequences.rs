@@ -30,6 +30,8 @@ pub struct SequenceState {
     pub activity: SequenceActivity,
     /// Counter to reduce number of backspaces typed.
     noerase_count: u16,
+    /// Virtual key of the most recently completed sequence, for statistics.
+    pub completed: Option<u16>,
 }
 
 impl SequenceState {
@@ -43,6 +45,7 @@ impl SequenceState {
             sequence_timeout: 0,
             activity: Inactive,
             noerase_count: 0,
+            completed: None,
         }
     }
 
@@ -281,6 +284,7 @@ pub(super) fn do_successful_sequence_termination(
 ) -> Result<(), anyhow::Error> {
     log::debug!("sequence complete; tapping fake key");
     state.activity = Inactive;
+    state.completed = Some(j);
     let sequence = match seq_type {
         EndSequenceType::Standard => &state.sequence,
         EndSequenceType::Overlap => &state.overlapped_sequence,
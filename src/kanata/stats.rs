@@ -0,0 +1,287 @@
+//! Statistics of key usage from the `stats` option of `defcfg`, for tuning layouts.
+//!
+//! Counts are kept since kanata started, across live reloads. They are written to `stats-file`
+//! every `stats-save-interval` seconds if they changed, and on live reload and exit: as CSV if the
+//! file extension is `csv`, otherwise as JSON.
+
+use super::*;
+use kanata_tcp_protocol::{KeyStats, UsageCount, UsageStats};
+use std::collections::BTreeMap;
+use std::path::Path;
+use web_time::{Duration, Instant};
+
+/// Name that replaces letter keys when `stats-exclude` contains `letters`.
+const LETTER: &str = "letter";
+
+#[derive(Debug, Default)]
+struct KeyCounts {
+    presses: u64,
+    taps: u64,
+    holds: u64,
+    hold_ms: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct StatsCollector {
+    file: Option<PathBuf>,
+    save_interval: Duration,
+    exclude_letters: bool,
+    exclude_bigrams: bool,
+    last_save: Instant,
+    changed: bool,
+    /// Counts by `defsrc` key, i.e. the code of the input key before any layer mapping.
+    keys: BTreeMap<u16, KeyCounts>,
+    layers: BTreeMap<String, u64>,
+    bigrams: BTreeMap<String, u64>,
+    chords: BTreeMap<String, u64>,
+    sequences: BTreeMap<String, u64>,
+    prev_key: Option<String>,
+    /// Keys that resolved to hold, with the time of the hold.
+    holds: Vec<(OsCode, Instant)>,
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        Self {
+            file: None,
+            save_interval: Duration::ZERO,
+            exclude_letters: false,
+            exclude_bigrams: false,
+            last_save: Instant::now(),
+            changed: false,
+            keys: BTreeMap::new(),
+            layers: BTreeMap::new(),
+            bigrams: BTreeMap::new(),
+            chords: BTreeMap::new(),
+            sequences: BTreeMap::new(),
+            prev_key: None,
+            holds: vec![],
+        }
+    }
+}
+
+impl StatsCollector {
+    /// The collector for the options of a configuration, or `None` if `stats` is disabled.
+    /// Counts of `prev` are kept, e.g. on a live reload.
+    pub(crate) fn from_cfg(opts: &CfgOptions, cfg_path: &Path, prev: Option<Self>) -> Option<Self> {
+        if !opts.stats {
+            return None;
+        }
+        if !cfg!(feature = "stats") {
+            log::warn!("stats in defcfg is ignored: kanata was compiled without the stats feature");
+            return None;
+        }
+        let mut stats = prev.unwrap_or_default();
+        stats.file = opts
+            .stats_file
+            .as_deref()
+            .map(|file| match cfg_path.parent() {
+                Some(dir) => dir.join(file),
+                None => PathBuf::from(file),
+            });
+        stats.save_interval = Duration::from_secs(opts.stats_save_interval.into());
+        stats.exclude_letters = opts.stats_exclude_letters;
+        stats.exclude_bigrams = opts.stats_exclude_bigrams;
+        Some(stats)
+    }
+
+    /// Name of the `defsrc` key for counting chords and bigrams and for the saved statistics.
+    fn key_name(&self, osc: OsCode) -> String {
+        let name = kanata_key_name(osc.into());
+        match self.exclude_letters && name.len() == 1 && name.as_bytes()[0].is_ascii_alphabetic() {
+            true => LETTER.to_owned(),
+            false => name,
+        }
+    }
+
+    pub(crate) fn press(&mut self, osc: OsCode, layer: &str) {
+        self.keys.entry(osc.into()).or_default().presses += 1;
+        let name = self.key_name(osc);
+        *self.layers.entry(layer.to_owned()).or_default() += 1;
+        if !self.exclude_bigrams
+            && let Some(prev) = &self.prev_key
+        {
+            *self.bigrams.entry(format!("{prev} {name}")).or_default() += 1;
+        }
+        self.prev_key = Some(name);
+        self.changed = true;
+    }
+
+    pub(crate) fn release(&mut self, osc: OsCode) {
+        let Some(idx) = self.holds.iter().position(|(held, _)| *held == osc) else {
+            return;
+        };
+        let (_, since) = self.holds.swap_remove(idx);
+        let held_ms = u64::try_from(since.elapsed().as_millis()).unwrap_or(u64::MAX);
+        self.keys.entry(osc.into()).or_default().hold_ms += held_ms;
+    }
+
+    pub(crate) fn tap(&mut self, osc: OsCode) {
+        self.keys.entry(osc.into()).or_default().taps += 1;
+        self.changed = true;
+    }
+
+    pub(crate) fn hold(&mut self, osc: OsCode) {
+        self.keys.entry(osc.into()).or_default().holds += 1;
+        self.holds.retain(|(held, _)| *held != osc);
+        self.holds.push((osc, Instant::now()));
+        self.changed = true;
+    }
+
+    pub(crate) fn chord(&mut self, keys: &[u16]) {
+        let mut names = keys
+            .iter()
+            .map(|&key| self.key_name(OsCode::from(key)))
+            .collect::<Vec<_>>();
+        names.sort();
+        let name = names.join("+");
+        *self.chords.entry(name).or_default() += 1;
+        self.changed = true;
+    }
+
+    pub(crate) fn sequence(&mut self, name: &str) {
+        *self.sequences.entry(name.to_owned()).or_default() += 1;
+        self.changed = true;
+    }
+
+    /// Save the statistics if they changed and the save interval elapsed.
+    pub(crate) fn tick(&mut self) {
+        if self.last_save.elapsed() >= self.save_interval {
+            self.flush();
+        }
+    }
+
+    /// Save the statistics if they changed since the previous save.
+    pub(crate) fn flush(&mut self) {
+        if !self.changed {
+            return;
+        }
+        self.last_save = Instant::now();
+        self.changed = false;
+        if let Some(file) = &self.file {
+            self.save(file);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> UsageStats {
+        let counts = |counts: &BTreeMap<String, u64>| {
+            counts
+                .iter()
+                .map(|(name, &count)| UsageCount {
+                    name: name.clone(),
+                    count,
+                })
+                .collect()
+        };
+        // Keys are named only here, since `stats-exclude letters` merges the counts of letters.
+        let mut keys = BTreeMap::<String, KeyCounts>::new();
+        for (&code, counts) in &self.keys {
+            let merged = keys.entry(self.key_name(OsCode::from(code))).or_default();
+            merged.presses += counts.presses;
+            merged.taps += counts.taps;
+            merged.holds += counts.holds;
+            merged.hold_ms += counts.hold_ms;
+        }
+        UsageStats {
+            keys: keys
+                .into_iter()
+                .map(|(defsrc_key, counts)| KeyStats {
+                    defsrc_key,
+                    presses: counts.presses,
+                    taps: counts.taps,
+                    holds: counts.holds,
+                    avg_hold_ms: counts.hold_ms.checked_div(counts.holds).unwrap_or(0),
+                })
+                .collect(),
+            layers: counts(&self.layers),
+            bigrams: counts(&self.bigrams),
+            chords: counts(&self.chords),
+            sequences: counts(&self.sequences),
+        }
+    }
+
+    fn save(&self, path: &Path) {
+        let stats = self.snapshot();
+        let content = match path.extension().is_some_and(|ext| ext == "csv") {
+            true => to_csv(&stats),
+            #[cfg(feature = "stats")]
+            false => match serde_json::to_string_pretty(&stats) {
+                Ok(json) => json,
+                Err(e) => {
+                    log::error!("could not serialize stats: {e}");
+                    return;
+                }
+            },
+            // Not reached: `from_cfg` disables stats without the feature.
+            #[cfg(not(feature = "stats"))]
+            false => return,
+        };
+        if let Err(e) = std::fs::write(path, content) {
+            log::error!("could not write stats file {path:?}: {e}");
+        }
+    }
+}
+
+/// Statistics as CSV, with a row for each key, layer, bigram, chord and sequence.
+fn to_csv(stats: &UsageStats) -> String {
+    let mut csv = String::from("kind,name,count,taps,holds,avg_hold_ms\n");
+    for key in &stats.keys {
+        csv.push_str(&format!(
+            "key,{},{},{},{},{}\n",
+            csv_field(&key.defsrc_key),
+            key.presses,
+            key.taps,
+            key.holds,
+            key.avg_hold_ms
+        ));
+    }
+    for (kind, counts) in [
+        ("layer", &stats.layers),
+        ("bigram", &stats.bigrams),
+        ("chord", &stats.chords),
+        ("sequence", &stats.sequences),
+    ] {
+        for usage in counts {
+            csv.push_str(&format!(
+                "{kind},{},{},,,\n",
+                csv_field(&usage.name),
+                usage.count
+            ));
+        }
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
+
+#[test]
+fn stats_csv_quotes_fields() {
+    let mut stats = StatsCollector {
+        exclude_letters: true,
+        ..Default::default()
+    };
+    stats.press(OsCode::KEY_A, "base");
+    stats.press(OsCode::KEY_COMMA, "base");
+    stats.hold(OsCode::KEY_COMMA);
+    stats.release(OsCode::KEY_COMMA);
+    stats
+        .keys
+        .get_mut(&OsCode::KEY_COMMA.into())
+        .unwrap()
+        .hold_ms = 200;
+    stats.sequence("say \"hi, there\"");
+    assert_eq!(
+        to_csv(&stats.snapshot()),
+        "kind,name,count,taps,holds,avg_hold_ms\n\
+         key,comm,1,0,1,200\n\
+         key,letter,1,0,0,0\n\
+         layer,base,2,,,\n\
+         bigram,letter comm,1,,,\n\
+         sequence,\"say \"\"hi, there\"\"\",1,,,\n"
+    );
+}
@@ -11,7 +11,7 @@ use kanata_parser::cfg;
 #[cfg(not(feature = "gui"))]
 use kanata_state_machine::*;
 #[cfg(not(feature = "gui"))]
-use main_lib::args::Args;
+use main_lib::args::{Args, MessageFormat};
 #[cfg(not(feature = "gui"))]
 use simplelog::{format_description, *};
 
@@ -41,6 +41,44 @@ mod cli {
             std::process::exit(0);
         }
 
+        // The converted configuration is the only output, so this is done before logging starts.
+        if let Some(path) = args.import_kmonad {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|e| anyhow::anyhow!("could not read {}: {e}", path.display()))?;
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            print!("{}", main_lib::kmonad::convert(&source, &file_name)?);
+            std::process::exit(0);
+        }
+        #[cfg(not(feature = "json"))]
+        if args.import_qmk.is_some() {
+            bail!("--import-qmk requires kanata to be compiled with the json feature");
+        }
+        #[cfg(feature = "json")]
+        if let (Some(path), Some(map_path)) = (&args.import_qmk, &args.qmk_map) {
+            let read = |p: &std::path::Path| {
+                std::fs::read_to_string(p)
+                    .map_err(|e| anyhow::anyhow!("could not read {}: {e}", p.display()))
+            };
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let converted = main_lib::qmk::convert(&read(path)?, &read(map_path)?, &file_name)?;
+            print!("{converted}");
+            std::process::exit(0);
+        }
+
+        if let Some([format, path]) = args.gen_localkeys.as_deref() {
+            if format != "xkb" {
+                bail!("unknown --gen-localkeys format {format}, the only format is xkb");
+            }
+            let keymap = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("could not read {path}: {e}"))?;
+            let file_name = std::path::Path::new(path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            print!("{}", main_lib::xkb::convert(&keymap, &file_name)?);
+            std::process::exit(0);
+        }
+
         let log_lvl = match (args.debug, args.trace, args.quiet) {
             (_, true, false) => LevelFilter::Trace,
             (true, false, false) => LevelFilter::Debug,
@@ -56,10 +94,15 @@ mod cli {
             version = 2,
             "[hour]:[minute]:[second].[subsecond digits:4]"
         ));
+        // Keep stdout for the messages when they are printed as JSON.
+        let log_mode = match args.message_format {
+            MessageFormat::Human => TerminalMode::Mixed,
+            MessageFormat::Json => TerminalMode::Stderr,
+        };
         CombinedLogger::init(vec![TermLogger::new(
             log_lvl,
             log_cfg.build(),
-            TerminalMode::Mixed,
+            log_mode,
             ColorChoice::AlwaysAnsi,
         )])
         .expect("logger can init");
@@ -120,7 +163,14 @@ mod cli {
 
         if args.check {
             log::info!("validating config only and exiting");
-            let status = if let Some(ref cfg_str) = config_string {
+            let status = if args.message_format == MessageFormat::Json {
+                #[cfg(feature = "json")]
+                {
+                    main_lib::check::check_json(config_string.as_deref(), cfg_paths.first())
+                }
+                #[cfg(not(feature = "json"))]
+                bail!("--message-format json requires kanata to be compiled with the json feature");
+            } else if let Some(ref cfg_str) = config_string {
                 use rustc_hash::FxHashMap;
                 match cfg::new_from_str(cfg_str, FxHashMap::default()) {
                     Ok(_) => 0,
@@ -141,6 +191,23 @@ mod cli {
             std::process::exit(status);
         }
 
+        if let Some(out_dir) = args.render_layers {
+            use rustc_hash::FxHashMap;
+            let cfg = match config_string {
+                Some(ref cfg_str) => cfg::new_from_str(cfg_str, FxHashMap::default()),
+                None => cfg::new_from_file(&cfg_paths[0]),
+            };
+            let cfg = match cfg {
+                Ok(cfg) => cfg,
+                Err(e) => {
+                    log::error!("{e:?}");
+                    std::process::exit(1);
+                }
+            };
+            main_lib::render::render_layers(&cfg, args.render_layout.as_deref(), &out_dir)?;
+            std::process::exit(0);
+        }
+
         #[cfg(any(target_os = "linux", target_os = "android"))]
         if let Some(wait) = args.wait_device_ms {
             use std::sync::atomic::Ordering;
@@ -96,6 +96,68 @@ kanata.kbd in the current working directory and
     #[arg(long, verbatim_doc_comment)]
     pub check: bool,
 
+    /// Format of the errors and warnings printed by --check. With json, each
+    /// one is printed to stdout as a JSON object on its own line.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MessageFormat::Human,
+        requires = "check",
+        verbatim_doc_comment
+    )]
+    pub message_format: MessageFormat,
+
+    /// Draw each layer of the configuration as an SVG in the given directory,
+    /// along with an index.html showing all of them, and exit. The keys are
+    /// placed the way they are written in defsrc unless --render-layout is
+    /// used.
+    #[arg(long, value_name = "OUT_DIR", verbatim_doc_comment)]
+    pub render_layers: Option<PathBuf>,
+
+    /// Physical layout to use with --render-layers: a JSON list of
+    /// {"x", "y", "w", "h"} key positions in defsrc order, or a QMK info.json.
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "render_layers",
+        verbatim_doc_comment
+    )]
+    pub render_layout: Option<PathBuf>,
+
+    /// Convert a KMonad configuration file to a kanata configuration, print it
+    /// to stdout and exit. Anything that could not be translated is kept in a
+    /// comment containing "kmonad-import:".
+    #[arg(long, value_name = "FILE", verbatim_doc_comment)]
+    pub import_kmonad: Option<PathBuf>,
+
+    /// Convert a QMK keymap.json or a Vial .vil file to a kanata configuration,
+    /// print it to stdout and exit. Requires --qmk-map.
+    #[arg(long, value_name = "FILE", requires = "qmk_map", verbatim_doc_comment)]
+    pub import_qmk: Option<PathBuf>,
+
+    /// Position map for --import-qmk, giving the defsrc key for each position
+    /// of the keymap as position:key entries, e.g. "0:esc 1:1" for keymap.json
+    /// or "0,0:esc 0,1:1" for the row,col of a Vial matrix. Each line of the
+    /// map is a line of defsrc.
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "import_qmk",
+        verbatim_doc_comment
+    )]
+    pub qmk_map: Option<PathBuf>,
+
+    /// Generate deflocalkeys-linux and defoslayout from a keyboard layout,
+    /// print them to stdout and exit. The only format is xkb, for the text
+    /// output of xkbcomp, e.g. from `xkbcomp -xkb $DISPLAY keymap.xkb`.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["FORMAT", "FILE"],
+        verbatim_doc_comment
+    )]
+    pub gen_localkeys: Option<Vec<String>>,
+
     /// Log layer changes even if the configuration file has set the defcfg
     /// option to false. Useful if you are experimenting with a new
     /// configuration but want to default to no logging.
@@ -130,6 +192,12 @@ kanata.kbd in the current working directory and
     pub macos_request_permissions: bool,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,6 +222,61 @@ mod tests {
         assert!(args.nodelay);
     }
 
+    #[test]
+    fn message_format_requires_check() {
+        let args = Args::try_parse_from(["kanata", "--check", "--message-format", "json"]).unwrap();
+        assert_eq!(args.message_format, MessageFormat::Json);
+        assert!(Args::try_parse_from(["kanata", "--message-format", "json"]).is_err());
+    }
+
+    #[test]
+    fn render_layout_requires_render_layers() {
+        let args = Args::try_parse_from([
+            "kanata",
+            "--render-layers",
+            "out",
+            "--render-layout",
+            "info.json",
+        ])
+        .unwrap();
+        assert_eq!(args.render_layers, Some(PathBuf::from("out")));
+        assert_eq!(args.render_layout, Some(PathBuf::from("info.json")));
+        assert!(Args::try_parse_from(["kanata", "--render-layout", "info.json"]).is_err());
+    }
+
+    #[test]
+    fn import_kmonad_takes_a_file() {
+        let args = Args::try_parse_from(["kanata", "--import-kmonad", "my.kbd"]).unwrap();
+        assert_eq!(args.import_kmonad, Some(PathBuf::from("my.kbd")));
+    }
+
+    #[test]
+    fn gen_localkeys_takes_a_format_and_a_file() {
+        let args =
+            Args::try_parse_from(["kanata", "--gen-localkeys", "xkb", "keymap.xkb"]).unwrap();
+        assert_eq!(
+            args.gen_localkeys,
+            Some(vec!["xkb".to_string(), "keymap.xkb".to_string()])
+        );
+        assert!(Args::try_parse_from(["kanata", "--gen-localkeys", "xkb"]).is_err());
+    }
+
+    #[test]
+    fn import_qmk_requires_a_position_map() {
+        let args = Args::try_parse_from([
+            "kanata",
+            "--import-qmk",
+            "keymap.json",
+            "--qmk-map",
+            "map.txt",
+        ])
+        .unwrap();
+        assert_eq!(args.import_qmk, Some(PathBuf::from("keymap.json")));
+        assert_eq!(args.qmk_map, Some(PathBuf::from("map.txt")));
+        assert!(Args::try_parse_from(["kanata", "--import-qmk", "keymap.json"]).is_err());
+        assert!(Args::try_parse_from(["kanata", "--qmk-map", "map.txt"]).is_err());
+    }
+
     #[test]
     fn emergency_exit_code_default() {
         let args = Args::try_parse_from(["kanata"]).unwrap();
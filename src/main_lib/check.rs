@@ -0,0 +1,157 @@
+//! Machine-readable output for `--check`.
+
+use kanata_parser::cfg::{self, Lint, sexpr::Span};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+/// Check the configuration and print each error and warning to stdout as a JSON object on its
+/// own line. Returns the exit status.
+pub(crate) fn check_json(config_string: Option<&str>, cfg_path: Option<&PathBuf>) -> i32 {
+    let (res, warnings, main_path) = match (config_string, cfg_path) {
+        (Some(cfg_str), _) => {
+            let (res, warnings) = cfg::check_str(cfg_str, Default::default());
+            (res, warnings, None)
+        }
+        (None, Some(p)) => {
+            let (res, warnings) = cfg::check_file(p);
+            (res, warnings, Some(p.as_path()))
+        }
+        (None, None) => unreachable!("checked that a configuration is provided"),
+    };
+    for warning in warnings.iter() {
+        println!(
+            "{}",
+            message(
+                "warning",
+                &warning.msg,
+                warning.span.as_ref(),
+                warning.lint,
+                main_path
+            )
+        );
+    }
+    match res {
+        Ok(()) => 0,
+        Err(e) => {
+            for e in e.errors() {
+                println!(
+                    "{}",
+                    message("error", &e.msg, e.span.as_ref(), None, main_path)
+                );
+            }
+            1
+        }
+    }
+}
+
+fn message(
+    severity: &str,
+    msg: &str,
+    span: Option<&Span>,
+    lint: Option<Lint>,
+    main_path: Option<&Path>,
+) -> Value {
+    // Multi-line messages are a summary followed by details.
+    let (summary, details) = msg.split_once('\n').unwrap_or((msg, ""));
+    let details = details.trim();
+    let mut msg = json!({
+        "severity": severity,
+        "message": summary.trim(),
+        "help": (!details.is_empty()).then_some(details),
+        "lint": lint.map(Lint::name),
+        "file": main_path.map(real_path),
+        "span": null,
+        "start": null,
+        "end": null,
+    });
+    if let Some(span) = span {
+        msg["file"] = json!(file_path(span, main_path));
+        msg["span"] = json!({ "start": span.start(), "end": span.end() });
+        msg["start"] = line_column(&span.file_content, span.start());
+        msg["end"] = line_column(&span.file_content, span.end());
+    }
+    msg
+}
+
+/// The path of the file that the span is in. Spans in included files are named by the path
+/// written in the include, which is relative to the main configuration file.
+fn file_path(span: &Span, main_path: Option<&Path>) -> String {
+    let Some(main_path) = main_path else {
+        return span.file_name();
+    };
+    if *span.file_name == *main_path.to_string_lossy() {
+        return real_path(main_path);
+    }
+    let dir = main_path.parent().unwrap_or(Path::new(""));
+    real_path(&dir.join(&*span.file_name))
+}
+
+fn real_path(p: &Path) -> String {
+    p.canonicalize()
+        .unwrap_or_else(|_| p.to_owned())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// One-based line and column of a byte offset. The column counts characters.
+fn line_column(content: &str, offset: usize) -> Value {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    json!({
+        "line": before.matches('\n').count() + 1,
+        "column": before[line_start..].chars().count() + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_locate_errors_in_included_files() {
+        let dir = std::env::temp_dir().join(format!("kanata-check-json-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.kbd");
+        std::fs::write(
+            &main,
+            "(defsrc a b)\n(include inc.kbd)\n(deflayer base a b)\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("inc.kbd"), "(defalias\n  x (tap-hold 1))\n").unwrap();
+
+        let (res, warnings) = cfg::check_file(&main);
+        let err = res.expect_err("tap-hold is missing parameters");
+        let msg = message("error", &err.msg, err.span.as_ref(), None, Some(&main));
+        assert_eq!(msg["file"], json!(real_path(&dir.join("inc.kbd"))), "{msg}");
+        assert_eq!(msg["start"], json!({ "line": 2, "column": 5 }));
+        assert_eq!(msg["end"], json!({ "line": 2, "column": 17 }));
+        assert_eq!(msg["span"], json!({ "start": 14, "end": 26 }));
+        assert_eq!(
+            msg["message"],
+            json!("tap-hold expects 4 items after it, got 1.")
+        );
+        assert!(msg["help"].as_str().unwrap().contains("<hold-timeout>"));
+
+        // No defcfg is defined.
+        assert_eq!(warnings.len(), 1);
+        let msg = message("warning", &warnings[0].msg, None, None, Some(&main));
+        assert_eq!(msg["file"], json!(real_path(&main)));
+        assert_eq!(msg["span"], Value::Null);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lint_warnings_are_named() {
+        let (res, warnings) = cfg::check_str(
+            "(defcfg process-unmapped-keys no)\n(defsrc a)\n(defalias x b)\n(deflayer base a)\n",
+            Default::default(),
+        );
+        res.expect("config is valid");
+        assert_eq!(warnings.len(), 1);
+        let w = &warnings[0];
+        let msg = message("warning", &w.msg, w.span.as_ref(), w.lint, None);
+        assert_eq!(msg["lint"], json!("unused-alias"));
+        assert_eq!(msg["start"], json!({ "line": 3, "column": 11 }));
+    }
+}
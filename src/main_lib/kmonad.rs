@@ -0,0 +1,724 @@
+//! Conversion of KMonad configuration files for `--import-kmonad`.
+//!
+//! The translation works on the text of the configuration rather than its meaning, so that the
+//! output keeps the layout of `defsrc` and `deflayer` along with the comments between items.
+//! Anything without a kanata equivalent is kept as a comment starting with [`MARKER`].
+
+use anyhow::{Result, bail};
+use kanata_parser::cfg::parse_mod_prefix;
+use kanata_parser::keys::str_to_oscode;
+
+/// Prefix of the comments for everything that could not be translated.
+const MARKER: &str = "kmonad-import:";
+
+/// Timeout used for KMonad buttons that wait for the next key without a time limit.
+const NO_TIMEOUT: &str = "65535";
+
+/// KMonad's names for shifted keys.
+const SHIFTED_KEYS: &[(&str, &str)] = &[
+    ("~", "S-grv"),
+    ("!", "S-1"),
+    ("@", "S-2"),
+    ("#", "S-3"),
+    ("$", "S-4"),
+    ("%", "S-5"),
+    ("^", "S-6"),
+    ("&", "S-7"),
+    ("*", "S-8"),
+    ("\\(", "S-9"),
+    ("\\)", "S-0"),
+    ("\\_", "S-min"),
+    ("+", "S-eql"),
+    ("{", "S-lbrc"),
+    ("}", "S-rbrc"),
+    ("|", "S-bksl"),
+    (":", "S-scln"),
+    ("<", "S-comm"),
+    (">", "S-."),
+    ("?", "S-/"),
+    ("\\\\", "\\"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Atom(String),
+    List(Vec<Expr>),
+    /// A `#(...)` list, which is a macro.
+    HashList(Vec<Expr>),
+    /// A line or block comment, including its delimiters.
+    Comment(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Expr {
+    item: Item,
+    /// Zero-based line of the start of the expression.
+    line: usize,
+    /// Zero-based column of the start of the expression, in characters.
+    column: usize,
+    /// Byte range of the expression in the source.
+    start: usize,
+    end: usize,
+}
+
+/// Convert the text of a KMonad configuration to a kanata configuration.
+pub(crate) fn convert(source: &str, file_name: &str) -> Result<String> {
+    let exprs = Parser::new(source).parse()?;
+    let conv = Converter {
+        source,
+        untranslated: Default::default(),
+    };
+    let mut body = String::new();
+    for (i, expr) in exprs.iter().enumerate() {
+        // Keep blank lines between forms.
+        if i > 0 && source[exprs[i - 1].end..expr.start].matches('\n').count() > 1 {
+            body.push('\n');
+        }
+        match &expr.item {
+            Item::Comment(c) => {
+                body.push_str(c);
+                body.push('\n');
+            }
+            Item::List(items) => {
+                let head = items.first().and_then(|e| e.atom()).unwrap_or_default();
+                let converted = match head {
+                    "defcfg" => conv.defcfg(&items[1..]),
+                    "defsrc" => conv.layout_form(expr, items, |_, e| conv.src_key(e)),
+                    "deflayer" => conv.layout_form(expr, items, |i, e| match i {
+                        // Keep the layer name.
+                        1 => e.atom().unwrap_or_default().to_string(),
+                        _ => conv.button(e),
+                    }),
+                    "defalias" => {
+                        let mut pairs = items[1..].iter().filter(|e| !e.is_comment());
+                        let mut values = vec![];
+                        while let (Some(_), Some(value)) = (pairs.next(), pairs.next()) {
+                            values.push(value.start);
+                        }
+                        conv.layout_form(expr, items, |_, e| match values.contains(&e.start) {
+                            true => conv.button(e),
+                            false => e.atom().unwrap_or_default().to_string(),
+                        })
+                    }
+                    _ => conv.untranslated_form(expr),
+                };
+                body.push_str(&converted);
+                body.push('\n');
+            }
+            _ => body.push_str(&conv.untranslated_form(expr)),
+        }
+    }
+    let mut out = format!(";; Converted from the KMonad configuration {file_name}.\n");
+    if conv.untranslated.get() > 0 {
+        out.push_str(&format!(
+            ";; {} item(s) could not be translated; search for \"{MARKER}\".\n",
+            conv.untranslated.get()
+        ));
+    }
+    out.push('\n');
+    out.push_str(&body);
+    Ok(out)
+}
+
+struct Converter<'a> {
+    source: &'a str,
+    untranslated: std::cell::Cell<usize>,
+}
+
+impl Converter<'_> {
+    fn text(&self, expr: &Expr) -> &str {
+        &self.source[expr.start..expr.end]
+    }
+
+    /// Keep a form that has no translation as a block comment.
+    fn untranslated_form(&self, expr: &Expr) -> String {
+        self.untranslated.set(self.untranslated.get() + 1);
+        format!(
+            "#| {MARKER} not translated\n{}\n|#\n",
+            self.text(expr).replace("|#", "| #")
+        )
+    }
+
+    /// A button that has no translation does nothing, with the original kept in a comment.
+    fn untranslated_button(&self, expr: &Expr) -> String {
+        self.untranslated.set(self.untranslated.get() + 1);
+        format!("XX #| {MARKER} {} |#", one_line(expr).replace("|#", "| #"))
+    }
+
+    fn defcfg(&self, items: &[Expr]) -> String {
+        let mut out = String::from("(defcfg\n");
+        // KMonad drops keys that are not in defsrc unless fallthrough is enabled, while kanata
+        // passes them through by default.
+        let mut fallthrough = false;
+        let mut items = items.iter().filter(|e| !e.is_comment());
+        while let (Some(key), Some(val)) = (items.next(), items.next()) {
+            let key_text = key.atom().unwrap_or_default();
+            let not_translated = || {
+                self.untranslated.set(self.untranslated.get() + 1);
+                format!(
+                    "  ;; {MARKER} not translated: {key_text} {}\n",
+                    one_line(val)
+                )
+            };
+            let line = match (key_text, val.list_with_head()) {
+                ("input", Some(("device-file", [path]))) => {
+                    format!("  linux-dev ({})\n", quoted(self.text(path)))
+                }
+                ("input", Some(("iokit-name", [name]))) => {
+                    format!("  macos-dev-names-include ({})\n", quoted(self.text(name)))
+                }
+                // kanata intercepts every keyboard by default on Windows and macOS.
+                ("input", Some(("low-level-hook" | "iokit-name", []))) => String::new(),
+                ("output", _) => format!(
+                    "  ;; kanata creates its own output device; KMonad used: {}\n",
+                    one_line(val)
+                ),
+                ("fallthrough", _) => {
+                    fallthrough = val.atom() == Some("true");
+                    String::new()
+                }
+                ("allow-cmd", _) if val.atom() == Some("true") => {
+                    "  danger-enable-cmd yes\n".to_string()
+                }
+                ("allow-cmd", _) => String::new(),
+                _ => not_translated(),
+            };
+            out.push_str(&line);
+        }
+        if !fallthrough {
+            out.push_str("  block-unmapped-keys yes\n");
+        }
+        out.push(')');
+        out
+    }
+
+    /// Convert a form whose items are laid out like a keyboard, keeping each item on its line and
+    /// at its column where possible.
+    fn layout_form(
+        &self,
+        form: &Expr,
+        items: &[Expr],
+        mut convert: impl FnMut(usize, &Expr) -> String,
+    ) -> String {
+        let mut out = String::from("(");
+        let mut line = form.line;
+        let mut line_len = form.column + 1;
+        let mut ends_in_comment = false;
+        for (i, expr) in items.iter().enumerate() {
+            let text = match &expr.item {
+                Item::Comment(c) => c.clone(),
+                _ if i == 0 => expr.atom().unwrap_or_default().to_string(),
+                _ => convert(i, expr),
+            };
+            if expr.line != line || ends_in_comment {
+                out.push('\n');
+                line = expr.line;
+                line_len = 0;
+            }
+            let pad = match line_len {
+                _ if i == 0 => 0,
+                0 => expr.column,
+                len => expr.column.saturating_sub(len).max(1),
+            };
+            out.extend(std::iter::repeat_n(' ', pad));
+            line_len += pad + text.chars().count();
+            ends_in_comment = text.starts_with(";;");
+            out.push_str(&text);
+        }
+        // Keep the closing parenthesis on its own line if it was.
+        let end_on_new_line = items
+            .last()
+            .is_some_and(|last| self.source[last.end..form.end].contains('\n'));
+        if ends_in_comment || end_on_new_line {
+            out.push('\n');
+        }
+        out.push(')');
+        out
+    }
+
+    fn src_key(&self, expr: &Expr) -> String {
+        match expr.atom().map(key) {
+            Some(Some(k)) if str_to_oscode(&k).is_some() => k,
+            // defsrc must only contain keys, so a placeholder would not be valid. Keep the key so
+            // that kanata reports it.
+            _ => {
+                self.untranslated.set(self.untranslated.get() + 1);
+                format!("{} #| {MARKER} unknown key |#", self.text(expr))
+            }
+        }
+    }
+
+    fn button(&self, expr: &Expr) -> String {
+        match &expr.item {
+            Item::Atom(a) => match button_atom(a) {
+                Some(b) => b,
+                None => self.untranslated_button(expr),
+            },
+            Item::HashList(items) => self.tap_macro(expr, items),
+            Item::List(_) => self.button_list(expr),
+            Item::Comment(c) => c.clone(),
+        }
+    }
+
+    fn button_list(&self, expr: &Expr) -> String {
+        let Some((head, args)) = expr.list_with_head() else {
+            return self.untranslated_button(expr);
+        };
+        let (args, timeout_button) = match args {
+            [rest @ .., kw, button] if kw.atom() == Some(":timeout-button") => {
+                (rest, Some(self.button(button)))
+            }
+            _ => (args, None),
+        };
+        let buttons = |args: &[Expr]| {
+            args.iter()
+                .map(|e| self.button(e))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        fn ms(e: &Expr) -> Option<&str> {
+            e.atom().filter(|a| a.parse::<u16>().is_ok())
+        }
+        match (head, args, timeout_button) {
+            ("tap-hold", [t, tap, hold], None) if ms(t).is_some() => {
+                let t = ms(t).unwrap();
+                format!(
+                    "(tap-hold {t} {t} {})",
+                    buttons(&[tap.clone(), hold.clone()])
+                )
+            }
+            ("tap-hold-next" | "tap-hold-next-release", [t, tap, hold], timeout_button)
+                if ms(t).is_some() =>
+            {
+                let t = ms(t).unwrap();
+                let kind = match head {
+                    "tap-hold-next" => "tap-hold-press",
+                    _ => "tap-hold-release",
+                };
+                let tap_hold = buttons(&[tap.clone(), hold.clone()]);
+                match timeout_button {
+                    Some(b) => format!("({kind}-timeout {t} {t} {tap_hold} {b})"),
+                    None => format!("({kind} {t} {t} {tap_hold})"),
+                }
+            }
+            ("tap-next" | "tap-next-press" | "tap-next-release", [tap, hold], None) => {
+                let kind = match head {
+                    "tap-next-release" => "tap-hold-release",
+                    _ => "tap-hold-press",
+                };
+                format!(
+                    "({kind} 0 {NO_TIMEOUT} {})",
+                    buttons(&[tap.clone(), hold.clone()])
+                )
+            }
+            ("layer-toggle" | "layer-while-held", [name], None) => {
+                format!("(layer-while-held {})", self.text(name))
+            }
+            ("layer-switch", [name], None) => format!("(layer-switch {})", self.text(name)),
+            ("layer-next", [name], None) => {
+                format!(
+                    "(one-shot-press {NO_TIMEOUT} (layer-while-held {}))",
+                    self.text(name)
+                )
+            }
+            ("around", [_, _], None) => format!("(multi {})", buttons(args)),
+            ("sticky-key", [t, button], None) if ms(t).is_some() => {
+                format!("(one-shot {} {})", ms(t).unwrap(), self.button(button))
+            }
+            ("tap-macro", _, None) => self.tap_macro(expr, args),
+            ("multi-tap", [.., _], None) if args.len() % 2 == 1 => {
+                // kanata uses one timeout for all taps, so use the longest.
+                let timeouts = args.chunks_exact(2).map(|pair| ms(&pair[0]));
+                let Some(timeout) = timeouts
+                    .map(|t| t.and_then(|t| t.parse::<u16>().ok()))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|t| t.into_iter().max())
+                else {
+                    return self.untranslated_button(expr);
+                };
+                let taps = args
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| i % 2 == 1 || *i == args.len() - 1)
+                    .map(|(_, e)| self.button(e))
+                    .collect::<Vec<_>>();
+                format!("(tap-dance {timeout} ({}))", taps.join(" "))
+            }
+            ("cmd-button", [cmd], None) if cmd.is_string() => {
+                format!("(cmd sh -c {})", self.text(cmd))
+            }
+            _ => self.untranslated_button(expr),
+        }
+    }
+
+    /// Convert `(tap-macro ...)` or `#(...)`. Pauses written as `P<ms>` become delays, and the
+    /// `:delay` option becomes a delay between every key.
+    fn tap_macro(&self, expr: &Expr, args: &[Expr]) -> String {
+        let (args, delay) = match args {
+            [rest @ .., kw, delay] if kw.atom() == Some(":delay") => (rest, delay.atom()),
+            _ => (args, None),
+        };
+        let mut items = vec![];
+        for arg in args.iter().filter(|e| !e.is_comment()) {
+            let item = match arg.atom() {
+                Some(a) if a.starts_with('P') && a[1..].parse::<u16>().is_ok() => {
+                    a[1..].to_string()
+                }
+                // Nested lists in macros are not supported by kanata.
+                Some(a) => match button_atom(a).filter(|b| !b.starts_with(['@', '(', '_'])) {
+                    // A bare number is a delay in kanata macros, so use the long key name.
+                    Some(b) if b.bytes().all(|c| c.is_ascii_digit()) => format!("Digit{b}"),
+                    Some(b) => b,
+                    None => return self.untranslated_button(expr),
+                },
+                None => return self.untranslated_button(expr),
+            };
+            if let (Some(delay), false) = (delay, items.is_empty()) {
+                items.push(delay.to_string());
+            }
+            items.push(item);
+        }
+        format!("(macro {})", items.join(" "))
+    }
+}
+
+/// Translate a KMonad key name, including modifier prefixes like `C-`.
+fn key(name: &str) -> Option<String> {
+    if let Some((_, kanata)) = SHIFTED_KEYS.iter().find(|(kmonad, _)| *kmonad == name) {
+        return Some(kanata.to_string());
+    }
+    let (mods, rest) = parse_mod_prefix(name).ok()?;
+    match mods.is_empty() {
+        true => str_to_oscode(name).map(|_| name.to_string()),
+        false if str_to_oscode(rest).is_some() => Some(name.to_string()),
+        // The modifiers also apply to KMonad's shifted names, e.g. `C-!`.
+        false => {
+            let (_, shifted) = SHIFTED_KEYS.iter().find(|(kmonad, _)| *kmonad == rest)?;
+            let prefix = &name[..name.len() - rest.len()];
+            match shifted.strip_prefix("S-") {
+                Some(_) if prefix.contains("S-") => None,
+                _ => Some(format!("{prefix}{shifted}")),
+            }
+        }
+    }
+}
+
+fn button_atom(a: &str) -> Option<String> {
+    match a {
+        "_" | "XX" => Some(a.to_string()),
+        _ if a.len() > 1 && a.starts_with('@') => Some(a.to_string()),
+        _ => key(a).or_else(|| {
+            // KMonad types other characters with its compose key sequences.
+            let mut chars = a.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_ascii() => Some(format!("(unicode {c})")),
+                _ => None,
+            }
+        }),
+    }
+}
+
+/// The expression on one line without comments.
+fn one_line(expr: &Expr) -> String {
+    let items = |items: &[Expr]| {
+        items
+            .iter()
+            .filter(|e| !e.is_comment())
+            .map(one_line)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match &expr.item {
+        Item::Atom(a) => a.clone(),
+        Item::List(l) => format!("({})", items(l)),
+        Item::HashList(l) => format!("#({})", items(l)),
+        Item::Comment(_) => String::new(),
+    }
+}
+
+fn quoted(s: &str) -> String {
+    match s.starts_with('"') {
+        true => s.to_string(),
+        false => format!("\"{s}\""),
+    }
+}
+
+impl Expr {
+    fn atom(&self) -> Option<&str> {
+        match &self.item {
+            Item::Atom(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    fn is_comment(&self) -> bool {
+        matches!(self.item, Item::Comment(_))
+    }
+
+    fn is_string(&self) -> bool {
+        self.atom().is_some_and(|a| a.starts_with('"'))
+    }
+
+    /// The head atom and the remaining items of a list, skipping comments.
+    fn list_with_head(&self) -> Option<(&str, &[Expr])> {
+        let Item::List(items) = &self.item else {
+            return None;
+        };
+        if items.iter().any(Expr::is_comment) {
+            return None;
+        }
+        Some((items.first()?.atom()?, &items[1..]))
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            pos: 0,
+            line: 0,
+            line_start: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<Expr>> {
+        let mut stack: Vec<(Expr, Vec<Expr>)> = vec![];
+        let mut top = vec![];
+        while let Some(c) = self.skip_whitespace() {
+            let start = self.pos;
+            let (line, column) = (
+                self.line,
+                self.source[self.line_start..start].chars().count(),
+            );
+            let rest = &self.source[start..];
+            let expr = |item, end| Expr {
+                item,
+                line,
+                column,
+                start,
+                end,
+            };
+            let done = if rest.starts_with(";;") {
+                let end = rest
+                    .find('\n')
+                    .map(|i| start + i)
+                    .unwrap_or(self.source.len());
+                self.pos = end;
+                expr(
+                    Item::Comment(self.source[start..end].trim_end().into()),
+                    end,
+                )
+            } else if rest.starts_with("#|") {
+                let Some(len) = rest.find("|#") else {
+                    bail!("line {}: unterminated block comment", line + 1);
+                };
+                self.advance(len + 2);
+                expr(Item::Comment(rest[..len + 2].into()), self.pos)
+            } else if c == '(' || rest.starts_with("#(") {
+                self.advance(if c == '(' { 1 } else { 2 });
+                let item = match c {
+                    '(' => Item::List(vec![]),
+                    _ => Item::HashList(vec![]),
+                };
+                stack.push((expr(item, 0), vec![]));
+                continue;
+            } else if c == ')' {
+                self.advance(1);
+                let Some((mut list, items)) = stack.pop() else {
+                    bail!("line {}: unexpected ')'", line + 1);
+                };
+                list.end = self.pos;
+                list.item = match list.item {
+                    Item::HashList(_) => Item::HashList(items),
+                    _ => Item::List(items),
+                };
+                list
+            } else if c == '"' {
+                let mut escaped = false;
+                let len = rest[1..]
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        let end = c == '"' && !escaped;
+                        escaped = c == '\\' && !escaped;
+                        end
+                    })
+                    .map(|(i, _)| i + 2);
+                let Some(len) = len else {
+                    bail!("line {}: unterminated string", line + 1);
+                };
+                self.advance(len);
+                expr(Item::Atom(rest[..len].into()), self.pos)
+            } else {
+                // A backslash escapes the next character, e.g. `\(`.
+                let mut escaped = false;
+                let len = rest
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        let end = !escaped && (c.is_whitespace() || c == '(' || c == ')');
+                        escaped = c == '\\' && !escaped;
+                        end
+                    })
+                    .map(|(i, _)| i)
+                    .unwrap_or(rest.len());
+                self.advance(len);
+                expr(Item::Atom(rest[..len].into()), self.pos)
+            };
+            match stack.last_mut() {
+                Some((_, items)) => items.push(done),
+                None => top.push(done),
+            }
+        }
+        if let Some((list, _)) = stack.last() {
+            bail!("line {}: unclosed '('", list.line + 1);
+        }
+        Ok(top)
+    }
+
+    fn advance(&mut self, len: usize) {
+        for (i, c) in self.source[self.pos..self.pos + len].char_indices() {
+            if c == '\n' {
+                self.line += 1;
+                self.line_start = self.pos + i + 1;
+            }
+        }
+        self.pos += len;
+    }
+
+    /// Skip whitespace and return the next character.
+    fn skip_whitespace(&mut self) -> Option<char> {
+        let rest = &self.source[self.pos..];
+        let len = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        self.advance(len);
+        self.source[self.pos..].chars().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanata_parser::cfg;
+
+    fn convert_valid(source: &str) -> String {
+        let converted = convert(source, "test.kbd").unwrap();
+        if let Err(e) = cfg::new_from_str(&converted, Default::default()) {
+            panic!("{e:?}\n{converted}");
+        }
+        converted
+    }
+
+    #[test]
+    fn converts_layers_and_aliases() {
+        let converted = convert_valid(
+            r#"
+;; My keyboard
+(defcfg
+  input  (device-file "/dev/input/by-id/usb-kbd-event-kbd")
+  output (uinput-sink "KMonad output")
+  fallthrough true
+  allow-cmd false)
+
+(defsrc
+  grv  1    2
+  caps a    s)
+
+(defalias
+  nav  (layer-toggle nav)
+  ctl  (tap-hold 200 esc lctl) ;; control on hold
+  sft  (tap-next a lsft)
+  mac  #(h e l l o P50 spc)
+  arnd (around lsft 1)
+  tm   (tap-macro a b :delay 5)
+  tn   (tap-hold-next-release 180 s lalt :timeout-button x))
+
+(deflayer base
+  @mac @arnd @tm
+  @ctl @sft  @nav)
+
+(deflayer nav
+  !    \(   ü
+  _    XX   (layer-switch base))
+"#,
+        );
+        let expected = r#"
+;; My keyboard
+(defcfg
+  linux-dev ("/dev/input/by-id/usb-kbd-event-kbd")
+  ;; kanata creates its own output device; KMonad used: (uinput-sink "KMonad output")
+)
+
+(defsrc
+  grv  1    2
+  caps a    s)
+
+(defalias
+  nav  (layer-while-held nav)
+  ctl  (tap-hold 200 200 esc lctl) ;; control on hold
+  sft  (tap-hold-press 0 65535 a lsft)
+  mac  (macro h e l l o 50 spc)
+  arnd (multi lsft 1)
+  tm   (macro a 5 b)
+  tn   (tap-hold-release-timeout 180 180 s lalt x))
+
+(deflayer base
+  @mac @arnd @tm
+  @ctl @sft  @nav)
+
+(deflayer nav
+  S-1  S-9  (unicode ü)
+  _    XX   (layer-switch base))
+"#;
+        assert_eq!(
+            converted,
+            format!(";; Converted from the KMonad configuration test.kbd.\n{expected}")
+        );
+    }
+
+    #[test]
+    fn marks_untranslated_items() {
+        let converted = convert_valid(
+            "
+(defcfg cmp-seq ralt)
+(defsrc a b)
+(deflayer base (layer-add other) (multi-tap 100 a 300 b c))
+(defunknown x)
+",
+        );
+        assert!(
+            converted
+                .contains(";; 3 item(s) could not be translated; search for \"kmonad-import:\"")
+        );
+        assert!(converted.contains(";; kmonad-import: not translated: cmp-seq ralt"));
+        assert!(converted.contains("  block-unmapped-keys yes\n"));
+        assert!(converted.contains("XX #| kmonad-import: (layer-add other) |#"));
+        assert!(converted.contains("(tap-dance 300 (a b c))"));
+        assert!(converted.contains("#| kmonad-import: not translated\n(defunknown x)\n|#"));
+    }
+
+    #[test]
+    fn digits_in_macros_are_keys() {
+        let converted = convert_valid(
+            "
+(defsrc a b)
+(deflayer base (tap-macro 1 2 P10 3) #(a 0))
+",
+        );
+        assert!(converted.contains("(macro Digit1 Digit2 10 Digit3)"));
+        assert!(converted.contains("(macro a Digit0)"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(convert("(defsrc a", "x").is_err());
+        assert!(convert("(defsrc a))", "x").is_err());
+        assert!(convert("(defsrc \"a)", "x").is_err());
+    }
+}
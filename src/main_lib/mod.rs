@@ -1,4 +1,14 @@
 pub(crate) mod args;
+#[cfg(all(not(feature = "gui"), feature = "json"))]
+pub(crate) mod check;
+#[cfg(not(feature = "gui"))]
+pub(crate) mod kmonad;
+#[cfg(all(not(feature = "gui"), feature = "json"))]
+pub(crate) mod qmk;
+#[cfg(not(feature = "gui"))]
+pub(crate) mod render;
+#[cfg(not(feature = "gui"))]
+pub(crate) mod xkb;
 
 #[cfg(all(target_os = "windows", feature = "gui"))]
 pub(crate) mod win_gui;
@@ -0,0 +1,765 @@
+//! Conversion of QMK `keymap.json` files and Vial `.vil` exports for `--import-qmk`.
+//!
+//! The exports only have the keycodes of matrix or layout positions, so a position map written by
+//! the user gives the `defsrc` key for each position. The lines of the position map become the
+//! lines of `defsrc` and of every layer.
+
+use anyhow::{Result, anyhow, bail};
+use kanata_parser::keys::str_to_oscode;
+use rustc_hash::FxHashMap as HashMap;
+use serde_json::Value;
+
+/// Prefix of the comments for everything that could not be translated.
+const MARKER: &str = "qmk-import:";
+
+/// QMK's default `TAPPING_TERM`.
+const DEFAULT_TAPPING_TERM: u16 = 200;
+/// QMK's default `COMBO_TERM`.
+const DEFAULT_COMBO_TERM: u16 = 50;
+/// QMK one-shot keys have no timeout by default, so use a long one.
+const ONE_SHOT_TIMEOUT: u16 = 5000;
+/// Vial's QMK setting IDs for the tapping and combo terms.
+const VIAL_TAPPING_TERM: &str = "7";
+const VIAL_COMBO_TERM: &str = "2";
+
+/// QMK basic keycodes, without the `KC_` prefix, and their kanata names.
+const KEYS: &[(&[&str], &str)] = &[
+    (&["ENTER", "ENT"], "ret"),
+    (&["ESCAPE", "ESC"], "esc"),
+    (&["BACKSPACE", "BSPC"], "bspc"),
+    (&["TAB"], "tab"),
+    (&["SPACE", "SPC"], "spc"),
+    (&["MINUS", "MINS"], "min"),
+    (&["EQUAL", "EQL"], "eql"),
+    (&["LEFT_BRACKET", "LBRC"], "lbrc"),
+    (&["RIGHT_BRACKET", "RBRC"], "rbrc"),
+    (&["BACKSLASH", "BSLS"], "bksl"),
+    (&["SEMICOLON", "SCLN"], "scln"),
+    (&["QUOTE", "QUOT"], "apos"),
+    (&["GRAVE", "GRV"], "grv"),
+    (&["COMMA", "COMM"], "comm"),
+    (&["DOT"], "."),
+    (&["SLASH", "SLSH"], "/"),
+    (&["NONUS_BACKSLASH", "NUBS"], "102d"),
+    (&["CAPS_LOCK", "CAPS"], "caps"),
+    (&["PRINT_SCREEN", "PSCR"], "prnt"),
+    (&["SCROLL_LOCK", "SCRL", "SLCK"], "slck"),
+    (&["PAUSE", "PAUS", "BRK"], "pause"),
+    (&["INSERT", "INS"], "ins"),
+    (&["HOME"], "home"),
+    (&["PAGE_UP", "PGUP"], "pgup"),
+    (&["DELETE", "DEL"], "del"),
+    (&["END"], "end"),
+    (&["PAGE_DOWN", "PGDN"], "pgdn"),
+    (&["RIGHT", "RGHT"], "rght"),
+    (&["LEFT"], "left"),
+    (&["DOWN"], "down"),
+    (&["UP"], "up"),
+    (&["NUM_LOCK", "NUM", "NLCK"], "nlck"),
+    (&["KP_SLASH", "PSLS"], "kp/"),
+    (&["KP_ASTERISK", "PAST"], "kp*"),
+    (&["KP_MINUS", "PMNS"], "kp-"),
+    (&["KP_PLUS", "PPLS"], "kp+"),
+    (&["KP_ENTER", "PENT"], "kprt"),
+    (&["KP_1", "P1"], "kp1"),
+    (&["KP_2", "P2"], "kp2"),
+    (&["KP_3", "P3"], "kp3"),
+    (&["KP_4", "P4"], "kp4"),
+    (&["KP_5", "P5"], "kp5"),
+    (&["KP_6", "P6"], "kp6"),
+    (&["KP_7", "P7"], "kp7"),
+    (&["KP_8", "P8"], "kp8"),
+    (&["KP_9", "P9"], "kp9"),
+    (&["KP_0", "P0"], "kp0"),
+    (&["KP_DOT", "PDOT"], "kp."),
+    (&["KP_EQUAL", "PEQL"], "kp="),
+    (&["APPLICATION", "APP"], "menu"),
+    (&["LEFT_CTRL", "LCTL"], "lctl"),
+    (&["LEFT_SHIFT", "LSFT"], "lsft"),
+    (&["LEFT_ALT", "LALT", "LOPT"], "lalt"),
+    (&["LEFT_GUI", "LGUI", "LCMD", "LWIN"], "lmet"),
+    (&["RIGHT_CTRL", "RCTL"], "rctl"),
+    (&["RIGHT_SHIFT", "RSFT"], "rsft"),
+    (&["RIGHT_ALT", "RALT", "ROPT", "ALGR"], "ralt"),
+    (&["RIGHT_GUI", "RGUI", "RCMD", "RWIN"], "rmet"),
+    (&["AUDIO_MUTE", "MUTE"], "mute"),
+    (&["AUDIO_VOL_UP", "VOLU"], "volu"),
+    (&["AUDIO_VOL_DOWN", "VOLD"], "vold"),
+    (&["MEDIA_NEXT_TRACK", "MNXT"], "next"),
+    (&["MEDIA_PREV_TRACK", "MPRV"], "prev"),
+    (&["MEDIA_PLAY_PAUSE", "MPLY"], "pp"),
+    (&["BRIGHTNESS_UP", "BRIU"], "brup"),
+    (&["BRIGHTNESS_DOWN", "BRID"], "brdn"),
+    (&["MS_BTN1", "BTN1"], "mlft"),
+    (&["MS_BTN2", "BTN2"], "mrgt"),
+    (&["MS_BTN3", "BTN3"], "mmid"),
+    (&["MS_BTN4", "BTN4"], "mbck"),
+    (&["MS_BTN5", "BTN5"], "mfwd"),
+    (&["MS_UP", "MS_U"], "(movemouse-up 4 2)"),
+    (&["MS_DOWN", "MS_D"], "(movemouse-down 4 2)"),
+    (&["MS_LEFT", "MS_L"], "(movemouse-left 4 2)"),
+    (&["MS_RGHT", "MS_R"], "(movemouse-right 4 2)"),
+    (&["MS_WHLU", "WH_U"], "(mwheel-up 50 120)"),
+    (&["MS_WHLD", "WH_D"], "(mwheel-down 50 120)"),
+    (&["MS_WHLL", "WH_L"], "(mwheel-left 50 120)"),
+    (&["MS_WHLR", "WH_R"], "(mwheel-right 50 120)"),
+    // Shifted keys.
+    (&["TILDE", "TILD"], "S-grv"),
+    (&["EXCLAIM", "EXLM"], "S-1"),
+    (&["AT"], "S-2"),
+    (&["HASH"], "S-3"),
+    (&["DOLLAR", "DLR"], "S-4"),
+    (&["PERCENT", "PERC"], "S-5"),
+    (&["CIRCUMFLEX", "CIRC"], "S-6"),
+    (&["AMPERSAND", "AMPR"], "S-7"),
+    (&["ASTERISK", "ASTR"], "S-8"),
+    (&["LEFT_PAREN", "LPRN"], "S-9"),
+    (&["RIGHT_PAREN", "RPRN"], "S-0"),
+    (&["UNDERSCORE", "UNDS"], "S-min"),
+    (&["PLUS"], "S-eql"),
+    (&["LEFT_CURLY_BRACE", "LCBR"], "S-lbrc"),
+    (&["RIGHT_CURLY_BRACE", "RCBR"], "S-rbrc"),
+    (&["PIPE"], "S-bksl"),
+    (&["COLON", "COLN"], "S-scln"),
+    (&["DOUBLE_QUOTE", "DQUO", "DQT"], "S-apos"),
+    (&["LEFT_ANGLE_BRACKET", "LABK", "LT"], "S-comm"),
+    (&["RIGHT_ANGLE_BRACKET", "RABK", "GT"], "S-."),
+    (&["QUESTION", "QUES"], "S-/"),
+];
+
+/// QMK modifier names, as used in `MOD_*` and the modifier functions, and the kanata keys and
+/// prefixes for them.
+const MODS: &[(&[&str], &str, &str)] = &[
+    (&["LCTL", "CTL", "C"], "lctl", "C-"),
+    (&["LSFT", "SFT", "S"], "lsft", "S-"),
+    (&["LALT", "ALT", "A", "LOPT", "OPT"], "lalt", "A-"),
+    (
+        &["LGUI", "GUI", "G", "LCMD", "CMD", "LWIN", "WIN"],
+        "lmet",
+        "M-",
+    ),
+    (&["RCTL"], "rctl", "RC-"),
+    (&["RSFT"], "rsft", "RS-"),
+    (&["RALT", "ROPT", "ALGR"], "ralt", "RA-"),
+    (&["RGUI", "RCMD", "RWIN"], "rmet", "RM-"),
+];
+
+/// A keyboard export: the keycodes of each layer by position.
+struct Export {
+    kind: &'static str,
+    layers: Vec<HashMap<String, String>>,
+    tap_dances: Vec<Value>,
+    combos: Vec<Value>,
+    tapping_term: u16,
+    combo_term: u16,
+}
+
+/// Convert a QMK `keymap.json` or a Vial `.vil`, using the position map to lay out `defsrc`.
+pub(crate) fn convert(export: &str, position_map: &str, file_name: &str) -> Result<String> {
+    let export = parse_export(export)?;
+    let map = parse_position_map(position_map)?;
+    for (pos, _) in map.iter().flatten() {
+        if !export.layers[0].contains_key(pos) {
+            bail!(
+                "position {pos} of the position map is not in the {}",
+                export.kind
+            );
+        }
+    }
+    let mapped = map.iter().flatten().map(|(pos, _)| pos).collect::<Vec<_>>();
+    let unmapped = export.layers[0]
+        .iter()
+        .filter(|(pos, kc)| {
+            !mapped.contains(pos) && !matches!(kc.as_str(), "KC_NO" | "XXXXXXX" | "-1")
+        })
+        .count();
+
+    let layer_names = (0..export.layers.len())
+        .map(|i| format!("layer{i}"))
+        .collect::<Vec<_>>();
+    let mut untranslated = 0;
+    let mut layers = vec![];
+    let mut notes = vec![];
+    for (i, layer) in export.layers.iter().enumerate() {
+        let translator = Translator {
+            layer: i,
+            layer_names: &layer_names,
+            tapping_term: export.tapping_term,
+            tap_dances: &export.tap_dances,
+        };
+        let mut failed = vec![];
+        let rows = map
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(pos, _)| {
+                        // Only the first layer is checked against the position map.
+                        let Some(kc) = layer.get(pos) else {
+                            failed.push(format!("no key at {pos}"));
+                            return "XX".into();
+                        };
+                        translator.translate(kc).unwrap_or_else(|| {
+                            failed.push(format!("{kc} at {pos}"));
+                            "XX".into()
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        untranslated += failed.len();
+        notes.push(failed);
+        layers.push(rows);
+    }
+
+    let mut chords = vec![];
+    for combo in export.combos.iter() {
+        match combo_chord(combo, &export, &map, &layer_names) {
+            Ok(Some(chord)) => chords.push(chord),
+            Ok(None) => {}
+            Err(e) => {
+                untranslated += 1;
+                chords.push(format!(";; {MARKER} combo {combo} not translated: {e}"));
+            }
+        }
+    }
+
+    let mut out = format!(";; Converted from the {} {file_name}.\n", export.kind);
+    if untranslated > 0 {
+        out.push_str(&format!(
+            ";; {untranslated} item(s) could not be translated; search for \"{MARKER}\".\n"
+        ));
+    }
+    if unmapped > 0 {
+        out.push_str(&format!(
+            ";; {MARKER} {unmapped} position(s) of the {} are not in the position map.\n",
+            export.kind
+        ));
+    }
+    // Transparent keys fall through to the QMK base layer, also on layers reached by `TG`/`TO`.
+    out.push_str("\n(defcfg\n  process-unmapped-keys no\n  delegate-to-first-layer yes\n");
+    if chords.iter().any(|c| !c.starts_with(";;")) {
+        out.push_str("  concurrent-tap-hold yes\n");
+    }
+    out.push_str(")\n\n");
+
+    let src = map
+        .iter()
+        .map(|row| row.iter().map(|(_, key)| key.clone()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    // Align the columns of defsrc and every layer.
+    let mut widths: Vec<Vec<usize>> = src
+        .iter()
+        .map(|row| row.iter().map(|k| k.chars().count()).collect())
+        .collect();
+    for rows in layers.iter() {
+        for (r, row) in rows.iter().enumerate() {
+            for (c, item) in row.iter().enumerate() {
+                widths[r][c] = widths[r][c].max(item.chars().count());
+            }
+        }
+    }
+    let block = |head: &str, rows: &[Vec<String>]| {
+        let mut block = format!("({head}\n");
+        for (r, row) in rows.iter().enumerate() {
+            let mut line = String::from(" ");
+            for (c, item) in row.iter().enumerate() {
+                line.push(' ');
+                line.push_str(item);
+                line.extend(std::iter::repeat_n(
+                    ' ',
+                    widths[r][c] - item.chars().count(),
+                ));
+            }
+            block.push_str(line.trim_end());
+            block.push('\n');
+        }
+        block.push_str(")\n");
+        block
+    };
+    out.push_str(&block("defsrc", &src));
+    for ((rows, name), failed) in layers.iter().zip(layer_names.iter()).zip(notes) {
+        out.push('\n');
+        if !failed.is_empty() {
+            out.push_str(&format!(
+                ";; {MARKER} not translated: {}\n",
+                failed.join(", ")
+            ));
+        }
+        out.push_str(&block(&format!("deflayer {name}"), rows));
+    }
+    if !chords.is_empty() {
+        out.push_str("\n(defchordsv2\n");
+        for chord in chords {
+            out.push_str(&format!("  {chord}\n"));
+        }
+        out.push_str(")\n");
+    }
+    Ok(out)
+}
+
+/// A Vial combo as a `defchordsv2` entry. Combos are triggered by keycodes, so each one is found
+/// on the first layer to get its position.
+fn combo_chord(
+    combo: &Value,
+    export: &Export,
+    map: &[Vec<(String, String)>],
+    layer_names: &[String],
+) -> Result<Option<String>> {
+    let items = combo
+        .as_array()
+        .filter(|items| items.len() == 5)
+        .ok_or_else(|| anyhow!("expected 4 keys and an output"))?;
+    let unused = |kc: &String| matches!(kc.as_str(), "KC_NO" | "-1");
+    let output = keycode(&items[4]);
+    let inputs = items[..4]
+        .iter()
+        .map(keycode)
+        .filter(|kc| !unused(kc))
+        .collect::<Vec<_>>();
+    if inputs.is_empty() && unused(&output) {
+        // Unused combo slots are all KC_NO.
+        return Ok(None);
+    }
+    if inputs.len() < 2 {
+        bail!("a combo needs at least two keys");
+    }
+    let keys = inputs
+        .iter()
+        .map(|input| {
+            map.iter()
+                .flatten()
+                .find(|(pos, _)| export.layers[0].get(pos) == Some(input))
+                .map(|(_, key)| key.as_str())
+                .ok_or_else(|| anyhow!("{input} is not on the first layer"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let translator = Translator {
+        layer: 0,
+        layer_names,
+        tapping_term: export.tapping_term,
+        tap_dances: &export.tap_dances,
+    };
+    let action = translator
+        .translate(&output)
+        .ok_or_else(|| anyhow!("{output} has no translation"))?;
+    Ok(Some(format!(
+        "({}) {action} {} all-released ()",
+        keys.join(" "),
+        export.combo_term
+    )))
+}
+
+#[derive(Clone, Copy)]
+struct Translator<'a> {
+    /// The layer containing the keycode.
+    layer: usize,
+    layer_names: &'a [String],
+    tapping_term: u16,
+    tap_dances: &'a [Value],
+}
+
+impl Translator<'_> {
+    /// The kanata action for a QMK keycode, if there is one.
+    fn translate(&self, kc: &str) -> Option<String> {
+        let kc = kc.trim();
+        match kc {
+            "KC_NO" | "XXXXXXX" => return Some("XX".into()),
+            "KC_TRNS" | "KC_TRANSPARENT" | "_______" => return Some("_".into()),
+            _ => {}
+        }
+        if let Some((func, args)) = split_call(kc) {
+            return self.translate_call(func, &args);
+        }
+        let name = kc
+            .strip_prefix("KC_")
+            .or_else(|| kc.strip_prefix("QK_"))
+            .unwrap_or(kc);
+        let simple = match name.len() {
+            1 if name.chars().all(|c| c.is_ascii_alphanumeric()) => Some(name.to_lowercase()),
+            2 | 3 if name.starts_with('F') && name[1..].parse::<u8>().is_ok() => {
+                Some(name.to_lowercase())
+            }
+            _ => None,
+        };
+        simple.or_else(|| {
+            KEYS.iter()
+                .find(|(qmk, _)| qmk.contains(&name))
+                .map(|(_, kanata)| kanata.to_string())
+        })
+    }
+
+    fn translate_call(&self, func: &str, args: &[&str]) -> Option<String> {
+        let term = self.tapping_term;
+        let layer = |arg: &str| {
+            arg.parse::<usize>()
+                .ok()
+                .and_then(|i| self.layer_names.get(i))
+        };
+        match (func, args) {
+            ("MO", [n]) => Some(format!("(layer-while-held {})", layer(n)?)),
+            // QMK toggles the layer on and off, so a toggle on its own layer goes back to the
+            // first layer.
+            ("TG", [n]) => match n.parse::<usize>().ok()? == self.layer {
+                true => Some(format!("(layer-switch {})", self.layer_names[0])),
+                false => Some(format!("(layer-switch {})", layer(n)?)),
+            },
+            ("TO" | "DF" | "PDF", [n]) => Some(format!("(layer-switch {})", layer(n)?)),
+            ("OSL", [n]) => Some(format!(
+                "(one-shot {ONE_SHOT_TIMEOUT} (layer-while-held {}))",
+                layer(n)?
+            )),
+            ("OSM", [mods]) => Some(format!(
+                "(one-shot {ONE_SHOT_TIMEOUT} {})",
+                mods_action(&parse_mods(mods)?)
+            )),
+            ("LT", [n, kc]) => Some(format!(
+                "(tap-hold {term} {term} {} (layer-while-held {}))",
+                self.translate(kc)?,
+                layer(n)?
+            )),
+            ("MT", [mods, kc]) => Some(format!(
+                "(tap-hold {term} {term} {} {})",
+                self.translate(kc)?,
+                mods_action(&parse_mods(mods)?)
+            )),
+            ("TD", [n]) => self.tap_dance(n.parse().ok()?),
+            (func, [kc]) => {
+                if let Some(mod_name) = func.strip_suffix("_T") {
+                    return Some(format!(
+                        "(tap-hold {term} {term} {} {})",
+                        self.translate(kc)?,
+                        mods_action(&mods_for_name(mod_name)?)
+                    ));
+                }
+                // Keycodes with modifiers, e.g. LCTL(KC_C).
+                let prefixes = mods_for_name(func)?
+                    .iter()
+                    .map(|m| modifier(m).2)
+                    .collect::<String>();
+                let key = self.translate(kc)?;
+                if key.starts_with('(') || key == "_" || key == "XX" {
+                    return None;
+                }
+                // Shifted keys like KC_EXLM already have the shift prefix.
+                let prefixes = prefixes
+                    .split_inclusive('-')
+                    .filter(|p| !key.contains(p))
+                    .collect::<String>();
+                Some(format!("{prefixes}{key}"))
+            }
+            _ => None,
+        }
+    }
+
+    /// A Vial tap dance: tap, hold, double tap, tap then hold, and the tapping term.
+    fn tap_dance(&self, idx: usize) -> Option<String> {
+        let td = self.tap_dances.get(idx)?.as_array()?;
+        let [tap, hold, double_tap, tap_hold, term] = td.as_slice() else {
+            return None;
+        };
+        let term = term.as_u64().and_then(|t| u16::try_from(t).ok())?;
+        // Tap dances cannot refer to other tap dances.
+        let inner = Translator {
+            tap_dances: &[],
+            ..*self
+        };
+        let action = |tap: &Value, hold: &Value| -> Option<String> {
+            let tap = inner.translate(&keycode(tap))?;
+            match keycode(hold).as_str() {
+                "KC_NO" => Some(tap),
+                hold => Some(format!(
+                    "(tap-hold {term} {term} {tap} {})",
+                    inner.translate(hold)?
+                )),
+            }
+        };
+        let first = action(tap, hold)?;
+        if keycode(double_tap) == "KC_NO" && keycode(tap_hold) == "KC_NO" {
+            return Some(first);
+        }
+        Some(format!(
+            "(tap-dance {term} ({first} {}))",
+            action(double_tap, tap_hold)?
+        ))
+    }
+}
+
+/// Split `LT(1, KC_A)` into `LT` and its arguments.
+fn split_call(kc: &str) -> Option<(&str, Vec<&str>)> {
+    let (func, rest) = kc.split_once('(')?;
+    let inner = rest.strip_suffix(')')?;
+    let mut args = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(inner[start..].trim());
+    Some((func.trim(), args))
+}
+
+fn modifier(key: &str) -> &'static (&'static [&'static str], &'static str, &'static str) {
+    MODS.iter()
+        .find(|(_, k, _)| *k == key)
+        .expect("key is from MODS")
+}
+
+/// The modifier keys of a name such as `LCTL`, `MEH` or `HYPR`.
+fn mods_for_name(name: &str) -> Option<Vec<&'static str>> {
+    match name {
+        "MEH" => Some(vec!["lctl", "lsft", "lalt"]),
+        "HYPR" | "ALL" => Some(vec!["lctl", "lsft", "lalt", "lmet"]),
+        _ => MODS
+            .iter()
+            .find(|(names, _, _)| names.contains(&name))
+            .map(|(_, key, _)| vec![*key]),
+    }
+}
+
+/// Parse modifiers such as `MOD_LCTL | MOD_LSFT`.
+fn parse_mods(mods: &str) -> Option<Vec<&'static str>> {
+    let mut keys = vec![];
+    for m in mods.split('|') {
+        for key in mods_for_name(m.trim().strip_prefix("MOD_")?)? {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    Some(keys)
+}
+
+fn mods_action(keys: &[&str]) -> String {
+    match keys {
+        [key] => key.to_string(),
+        keys => format!("(multi {})", keys.join(" ")),
+    }
+}
+
+/// Keycodes are usually strings, but Vial writes unused positions as -1.
+fn keycode(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn parse_export(text: &str) -> Result<Export> {
+    let value: Value = serde_json::from_str(text)?;
+    let setting = |id: &str, default| {
+        value
+            .get("settings")
+            .and_then(|s| s.get(id))
+            .and_then(Value::as_u64)
+            .and_then(|t| u16::try_from(t).ok())
+            .unwrap_or(default)
+    };
+    let list = |name: &str| {
+        value
+            .get(name)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    if let Some(layers) = value.get("layers").and_then(Value::as_array) {
+        let layers = layers
+            .iter()
+            .map(|layer| {
+                let keys = layer
+                    .as_array()
+                    .ok_or_else(|| anyhow!("each layer must be a list of keycodes"))?;
+                Ok(keys
+                    .iter()
+                    .enumerate()
+                    .map(|(i, kc)| (i.to_string(), keycode(kc)))
+                    .collect())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if layers.is_empty() {
+            bail!("the keymap has no layers");
+        }
+        return Ok(Export {
+            kind: "QMK keymap",
+            layers,
+            tap_dances: vec![],
+            combos: vec![],
+            tapping_term: DEFAULT_TAPPING_TERM,
+            combo_term: DEFAULT_COMBO_TERM,
+        });
+    }
+    let Some(layout) = value.get("layout").and_then(Value::as_array) else {
+        bail!("expected a QMK keymap.json with \"layers\" or a Vial .vil with \"layout\"");
+    };
+    let layers = layout
+        .iter()
+        .map(|layer| {
+            let rows = layer
+                .as_array()
+                .ok_or_else(|| anyhow!("each layer must be a list of matrix rows"))?;
+            let mut keys = HashMap::default();
+            for (r, row) in rows.iter().enumerate() {
+                let row = row
+                    .as_array()
+                    .ok_or_else(|| anyhow!("each matrix row must be a list of keycodes"))?;
+                for (c, kc) in row.iter().enumerate() {
+                    keys.insert(format!("{r},{c}"), keycode(kc));
+                }
+            }
+            Ok(keys)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if layers.is_empty() {
+        bail!("the layout has no layers");
+    }
+    Ok(Export {
+        kind: "Vial layout",
+        layers,
+        tap_dances: list("tap_dance"),
+        combos: list("combo"),
+        tapping_term: setting(VIAL_TAPPING_TERM, DEFAULT_TAPPING_TERM),
+        combo_term: setting(VIAL_COMBO_TERM, DEFAULT_COMBO_TERM),
+    })
+}
+
+/// Parse the position map: whitespace-separated `position:key` entries, where the position is the
+/// index in a `keymap.json` layer or the `row,col` of a Vial matrix. Text after `;;` is ignored.
+fn parse_position_map(text: &str) -> Result<Vec<Vec<(String, String)>>> {
+    let mut rows = vec![];
+    let mut seen_keys = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split(";;").next().unwrap_or_default();
+        let mut row = vec![];
+        for entry in line.split_whitespace() {
+            let Some((pos, key)) = entry.rsplit_once(':') else {
+                bail!(
+                    "position map line {}: expected position:key, found {entry}",
+                    i + 1
+                );
+            };
+            if str_to_oscode(key).is_none() {
+                bail!("position map line {}: unknown key {key}", i + 1);
+            }
+            if seen_keys.contains(&key) {
+                bail!("position map line {}: key {key} is used twice", i + 1);
+            }
+            seen_keys.push(key);
+            row.push((pos.to_string(), key.to_string()));
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+    }
+    if rows.is_empty() {
+        bail!("the position map is empty");
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanata_parser::cfg;
+
+    fn convert_valid(export: &str, map: &str) -> String {
+        let converted = convert(export, map, "test").unwrap();
+        if let Err(e) = cfg::new_from_str(&converted, Default::default()) {
+            panic!("{e:?}\n{converted}");
+        }
+        converted
+    }
+
+    #[test]
+    fn key_names_exist() {
+        for (_, kanata) in KEYS.iter() {
+            if !kanata.starts_with('(') {
+                let key = kanata.strip_prefix("S-").unwrap_or(kanata);
+                assert!(str_to_oscode(key).is_some(), "{kanata}");
+            }
+        }
+    }
+
+    #[test]
+    fn converts_keymap_json() {
+        let keymap = r#"{
+            "keyboard": "test", "layout": "LAYOUT",
+            "layers": [
+                ["KC_ESC", "LT(1,KC_SPC)", "MT(MOD_LCTL|MOD_LSFT,KC_A)", "LGUI_T(KC_S)", "OSM(MOD_LSFT)", "RGB_TOG"],
+                ["TG(1)", "_______", "LCTL(KC_C)", "LSFT(KC_EXLM)", "OSL(0)", "KC_NO"]
+            ]
+        }"#;
+        let map = "0:esc 1:spc\n2:a 3:s 4:lsft ;; the fifth key is unused\n";
+        let expected = r#";; Converted from the QMK keymap test.
+;; qmk-import: 1 position(s) of the QMK keymap are not in the position map.
+
+(defcfg
+  process-unmapped-keys no
+  delegate-to-first-layer yes
+)
+
+(defsrc
+  esc                   spc
+  a                                      s                         lsft
+)
+
+(deflayer layer0
+  esc                   (tap-hold 200 200 spc (layer-while-held layer1))
+  (tap-hold 200 200 a (multi lctl lsft)) (tap-hold 200 200 s lmet) (one-shot 5000 lsft)
+)
+
+(deflayer layer1
+  (layer-switch layer0) _
+  C-c                                    S-1                       (one-shot 5000 (layer-while-held layer0))
+)
+"#;
+        assert_eq!(convert_valid(keymap, map), expected);
+    }
+
+    #[test]
+    fn converts_vial_layout() {
+        let vil = r#"{
+            "version": 1,
+            "layout": [
+                [["KC_A", "KC_S", "TD(0)"], ["KC_D", -1, "QK_BOOT"]],
+                [["KC_1", "KC_2", "KC_3"], ["KC_4", -1, "MO(0)"]]
+            ],
+            "tap_dance": [["KC_ESC", "KC_LCTL", "KC_TAB", "KC_NO", 250], ["KC_NO", "KC_NO", "KC_NO", "KC_NO", 200]],
+            "combo": [["KC_A", "KC_S", "KC_NO", "KC_NO", "KC_ENT"], ["KC_NO", "KC_NO", "KC_NO", "KC_NO", "KC_NO"], ["KC_A", "KC_Z", "KC_NO", "KC_NO", "KC_B"]],
+            "settings": {"2": 40, "7": 180}
+        }"#;
+        let map = "0,0:a 0,1:s 0,2:esc\n1,0:d 1,2:f\n";
+        let converted = convert_valid(vil, map);
+        assert!(converted.starts_with(";; Converted from the Vial layout test.\n;; 2 item(s)"));
+        assert!(converted.contains("  concurrent-tap-hold yes\n"));
+        assert!(
+            converted.contains("  a s (tap-dance 250 ((tap-hold 250 250 esc lctl) tab))\n  d XX\n")
+        );
+        assert!(converted.contains(";; qmk-import: not translated: QK_BOOT at 1,2\n"));
+        assert!(converted.contains("  (a s) ret 40 all-released ()\n"));
+        assert!(converted.contains(";; qmk-import: combo [\"KC_A\",\"KC_Z\""));
+    }
+
+    #[test]
+    fn short_layer_becomes_no_op() {
+        let keymap = r#"{"layers": [["KC_A", "KC_B"], ["KC_1"]]}"#;
+        let converted = convert_valid(keymap, "0:a 1:b");
+        assert!(converted.contains(";; qmk-import: not translated: no key at 1\n"));
+        assert!(converted.contains("(deflayer layer1\n  1 XX\n)"));
+    }
+
+    #[test]
+    fn position_map_errors() {
+        let keymap = r#"{"layers": [["KC_A"]]}"#;
+        assert!(convert(keymap, "0:a 1:b", "test").is_err());
+        assert!(convert(keymap, "0:nokey", "test").is_err());
+        assert!(convert(keymap, "0 a", "test").is_err());
+        assert!(convert(keymap, ";; empty", "test").is_err());
+    }
+}
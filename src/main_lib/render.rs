@@ -0,0 +1,511 @@
+//! Drawings of the layers for `--render-layers`.
+//!
+//! Each layer is drawn as an SVG in the shape of `defsrc`, or of a physical layout file if one is
+//! given, along with an HTML page showing all of them.
+
+#[cfg(feature = "json")]
+use anyhow::anyhow;
+use anyhow::{Context, Result, bail};
+use kanata_keyberon::action::{Action, TapDanceConfig};
+use kanata_parser::cfg::{Cfg, DefsrcKey, LayerInfo, sexpr};
+use kanata_parser::custom_action::CustomAction;
+use kanata_parser::keys::{OsCode, str_to_oscode};
+use rustc_hash::FxHashMap as HashMap;
+#[cfg(feature = "json")]
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Size of one key unit in pixels.
+const UNIT: f64 = 60.0;
+/// Space between keys in pixels.
+const GAP: f64 = 4.0;
+
+/// The position and size of a key, in key units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct KeyRect {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// An action of the layout, which is borrowed for less than `'static`.
+type LayoutAction<'a> = Action<'a, &'a CustomAction>;
+
+/// What is written on a key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Legend {
+    /// The action on tap, or the only action.
+    main: String,
+    /// The action on hold, or a note about how the key behaves.
+    sub: Option<String>,
+    /// The alias used for the key in the configuration.
+    alias: Option<String>,
+}
+
+/// Write one SVG per layer and an `index.html` into `out_dir`.
+pub(crate) fn render_layers(cfg: &Cfg, layout_file: Option<&Path>, out_dir: &Path) -> Result<()> {
+    let rects = match layout_file {
+        Some(p) => {
+            let text = std::fs::read_to_string(p)
+                .with_context(|| format!("could not read layout file {}", p.display()))?;
+            let rects = parse_layout(&text)
+                .with_context(|| format!("invalid layout file {}", p.display()))?;
+            if rects.len() != cfg.defsrc_keys.len() {
+                bail!(
+                    "layout file {} has {} keys but defsrc has {}",
+                    p.display(),
+                    rects.len(),
+                    cfg.defsrc_keys.len()
+                );
+            }
+            rects
+        }
+        None => defsrc_geometry(&cfg.defsrc_keys),
+    };
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("could not create {}", out_dir.display()))?;
+
+    let layers = cfg.layout.b().layers;
+    let vkey_names: HashMap<usize, &str> = cfg
+        .fake_keys
+        .iter()
+        .map(|(name, idx)| (*idx, name.as_str()))
+        .collect();
+    let mut index = String::new();
+    for (i, info) in cfg.layer_info.iter().enumerate() {
+        let aliases = layer_aliases(info, &cfg.defsrc_keys);
+        let legends = cfg
+            .defsrc_keys
+            .iter()
+            .map(|key| {
+                let mut legend = legend(
+                    &layers[i][0][usize::from(key.osc)],
+                    &cfg.layer_info,
+                    &vkey_names,
+                );
+                legend.alias = aliases.get(&key.osc).cloned();
+                legend
+            })
+            .collect::<Vec<_>>();
+        let file_name = format!("{i}-{}.svg", file_stem(&info.name));
+        let path = out_dir.join(&file_name);
+        std::fs::write(&path, layer_svg(&info.name, &rects, &legends))
+            .with_context(|| format!("could not write {}", path.display()))?;
+        let _ = writeln!(
+            index,
+            "<section id=\"{id}\">\n<h2>{name}</h2>\n<img src=\"{file_name}\" alt=\"layer {name}\">\n</section>",
+            id = escape(&file_stem(&info.name)),
+            name = escape(&info.name),
+        );
+    }
+    let path = out_dir.join("index.html");
+    std::fs::write(&path, index_html(&index))
+        .with_context(|| format!("could not write {}", path.display()))?;
+    log::info!(
+        "rendered {} layers to {}",
+        cfg.layer_info.len(),
+        out_dir.display()
+    );
+    Ok(())
+}
+
+/// Lay the keys out the way they are written in `defsrc`. Each line is a row, and the columns are
+/// scaled by the most common distance between neighbouring keys.
+pub(crate) fn defsrc_geometry(keys: &[DefsrcKey]) -> Vec<KeyRect> {
+    let mut gaps: HashMap<usize, usize> = HashMap::default();
+    for pair in keys.windows(2) {
+        if pair[0].line == pair[1].line && pair[1].column > pair[0].column {
+            *gaps.entry(pair[1].column - pair[0].column).or_default() += 1;
+        }
+    }
+    // Prefer the smaller gap on ties so that keys are never squeezed together.
+    let spacing = gaps
+        .into_iter()
+        .max_by_key(|(gap, count)| (*count, std::cmp::Reverse(*gap)))
+        .map(|(gap, _)| gap as f64)
+        .unwrap_or(1.0);
+    let min_column = keys.iter().map(|k| k.column).min().unwrap_or(0);
+
+    let mut rects: Vec<KeyRect> = Vec::with_capacity(keys.len());
+    let mut row = 0.0;
+    for (i, key) in keys.iter().enumerate() {
+        let mut x = (key.column - min_column) as f64 / spacing;
+        match i.checked_sub(1).map(|prev| keys[prev].line) {
+            Some(line) if line == key.line => {
+                // Keep keys from overlapping when they are written closer than usual.
+                let prev = rects[i - 1];
+                x = x.max(prev.x + prev.w);
+            }
+            Some(_) => row += 1.0,
+            None => {}
+        }
+        rects.push(KeyRect {
+            x,
+            y: row,
+            w: 1.0,
+            h: 1.0,
+        });
+    }
+    rects
+}
+
+/// Read a physical layout: a JSON list of `{"x", "y", "w", "h"}` objects in `defsrc` order, where
+/// `w` and `h` default to 1. The `layout` list of the first entry in a QMK `info.json` `layouts`
+/// object is also accepted.
+#[cfg(feature = "json")]
+pub(crate) fn parse_layout(text: &str) -> Result<Vec<KeyRect>> {
+    let value: Value = serde_json::from_str(text)?;
+    let keys = match &value {
+        Value::Array(keys) => keys,
+        Value::Object(obj) => obj
+            .get("layouts")
+            .and_then(Value::as_object)
+            .and_then(|layouts| layouts.values().next())
+            .or(Some(&value))
+            .and_then(|layout| layout.get("layout"))
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("expected a list of keys or a QMK layouts object"))?,
+        _ => bail!("expected a list of keys or a QMK layouts object"),
+    };
+    keys.iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let num = |name: &str, default: Option<f64>| {
+                key.get(name)
+                    .and_then(Value::as_f64)
+                    .or(default)
+                    .ok_or_else(|| anyhow!("key {i} has no number for \"{name}\""))
+            };
+            Ok(KeyRect {
+                x: num("x", None)?,
+                y: num("y", None)?,
+                w: num("w", Some(1.0))?,
+                h: num("h", Some(1.0))?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "json"))]
+pub(crate) fn parse_layout(_text: &str) -> Result<Vec<KeyRect>> {
+    bail!("layout files require kanata to be compiled with the json feature")
+}
+
+/// The aliases written for keys of the layer, by the `defsrc` key they are on.
+fn layer_aliases(info: &LayerInfo, defsrc_keys: &[DefsrcKey]) -> HashMap<OsCode, String> {
+    let alias_name = |expr: &sexpr::SExpr| {
+        expr.atom(None)
+            .and_then(|a| a.strip_prefix('@'))
+            .map(|a| format!("@{a}"))
+    };
+    let Ok(exprs) = sexpr::parse(&info.cfg_text, "") else {
+        return HashMap::default();
+    };
+    let Some(layer) = exprs.first() else {
+        return HashMap::default();
+    };
+    // Skip the deflayer atom and the layer name.
+    let items = layer.t.get(2..).unwrap_or_default();
+    match layer.t[0].atom(None) {
+        Some("deflayermap") => items
+            .chunks_exact(2)
+            .filter_map(|pair| {
+                let osc = str_to_oscode(pair[0].atom(None)?)?;
+                Some((osc, alias_name(&pair[1])?))
+            })
+            .collect(),
+        _ => defsrc_keys
+            .iter()
+            .zip(items)
+            .filter_map(|(key, item)| Some((key.osc, alias_name(item)?)))
+            .collect(),
+    }
+}
+
+fn legend(
+    action: &LayoutAction,
+    layers: &[LayerInfo],
+    vkey_names: &HashMap<usize, &str>,
+) -> Legend {
+    let label = |action: &LayoutAction| action_label(action, layers, vkey_names);
+    let (main, sub) = match action {
+        Action::HoldTap(ht) => (label(&ht.tap), Some(label(&ht.hold))),
+        Action::OneShot(os) => (label(os.action), Some("one-shot".into())),
+        Action::TapDance(_) => (label(action), Some("tap-dance".into())),
+        Action::Fork(fork) => (
+            label(&fork.left),
+            Some(format!("fork {}", label(&fork.right))),
+        ),
+        _ => (label(action), None),
+    };
+    Legend {
+        main,
+        sub,
+        alias: None,
+    }
+}
+
+/// A short description of what an action does.
+fn action_label(
+    action: &LayoutAction,
+    layers: &[LayerInfo],
+    vkey_names: &HashMap<usize, &str>,
+) -> String {
+    let label = |action: &LayoutAction| action_label(action, layers, vkey_names);
+    let layer_name = |idx: usize| layers.get(idx).map(|l| l.name.as_str()).unwrap_or("?");
+    match action {
+        Action::NoOp => "✗".into(),
+        Action::Trans => "▽".into(),
+        Action::KeyCode(kc) => kc.to_string(),
+        Action::MultipleKeyCodes(kcs) => kcs
+            .iter()
+            .map(|kc| kc.to_string())
+            .collect::<Vec<_>>()
+            .join("+"),
+        Action::MultipleActions(actions) => {
+            // Keys pressed together are a chord like ‹⎈+C, anything else is listed.
+            let sep = match actions.iter().all(|a| matches!(a, Action::KeyCode(_))) {
+                true => "+",
+                false => " ",
+            };
+            actions
+                .iter()
+                .map(label)
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<_>>()
+                .join(sep)
+        }
+        Action::Layer(idx) => format!("⇑{}", layer_name(*idx)),
+        Action::DefaultLayer(idx) => format!("→{}", layer_name(*idx)),
+        Action::Sequence { .. } | Action::RepeatableSequence { .. } => "macro".into(),
+        Action::CancelSequences => "cancel".into(),
+        Action::ReleaseState(_) => "release".into(),
+        Action::HoldTap(ht) => format!("{}/{}", label(&ht.tap), label(&ht.hold)),
+        Action::Custom(custom) => custom_label(custom, vkey_names),
+        Action::OneShot(os) => label(os.action),
+        Action::OneShotIgnoreEventsTicks(_) => String::new(),
+        Action::TapDance(td) => match td.config {
+            // The actions are tap and hold pairs.
+            TapDanceConfig::LazyHold { .. } => td
+                .actions
+                .chunks(2)
+                .map(|pair| format!("{}/{}", label(pair[0]), label(pair[1])))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => td
+                .actions
+                .iter()
+                .map(|a| label(a))
+                .collect::<Vec<_>>()
+                .join(" "),
+        },
+        Action::Chords(_) => "chord".into(),
+        Action::Repeat => "repeat".into(),
+        Action::LayerLock => "lock".into(),
+        Action::Fork(fork) => label(&fork.left),
+        Action::Switch(_) => "switch".into(),
+        Action::Src => "src".into(),
+    }
+}
+
+fn custom_label(action: &CustomAction, vkey_names: &HashMap<usize, &str>) -> String {
+    match action {
+        CustomAction::Unicode(c) => c.to_string(),
+        CustomAction::Mouse(btn) | CustomAction::MouseTap(btn) => btn.to_string(),
+        CustomAction::MWheel { direction, .. } | CustomAction::MWheelNotch { direction } => {
+            direction.to_string()
+        }
+        CustomAction::MoveMouse { direction, .. }
+        | CustomAction::MoveMouseAccel { direction, .. } => format!("🖰{direction}"),
+        CustomAction::FakeKey { coord, .. } | CustomAction::FakeKeyOnRelease { coord, .. } => {
+            vkey_names
+                .get(&usize::from(coord.y))
+                .map(|name| format!("vk {name}"))
+                .unwrap_or_else(|| "vkey".into())
+        }
+        CustomAction::Cmd(_) | CustomAction::CmdLog(..) | CustomAction::CmdOutputKeys(_) => {
+            "cmd".into()
+        }
+        CustomAction::LiveReload
+        | CustomAction::LiveReloadNext
+        | CustomAction::LiveReloadPrev
+        | CustomAction::LiveReloadNum(_)
+        | CustomAction::LiveReloadFile(_) => "reload".into(),
+        CustomAction::Repeat => "repeat".into(),
+        CustomAction::CapsWord(_) => "caps-word".into(),
+        CustomAction::SequenceLeader(..) => "leader".into(),
+        CustomAction::DynamicMacroRecord(_) => "dmacro rec".into(),
+        CustomAction::DynamicMacroRecordStop(_) => "dmacro stop".into(),
+        CustomAction::DynamicMacroPlay(_) => "dmacro play".into(),
+        CustomAction::Unmodded { keys, .. } | CustomAction::Unshifted { keys } => keys
+            .iter()
+            .map(|kc| kc.to_string())
+            .collect::<Vec<_>>()
+            .join("+"),
+        CustomAction::Delay(_) | CustomAction::DelayOnRelease(_) => String::new(),
+        other => {
+            // Use the variant name for everything else.
+            let debug = format!("{other:?}");
+            debug
+                .split(|c: char| !c.is_alphanumeric())
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        }
+    }
+}
+
+fn layer_svg(name: &str, rects: &[KeyRect], legends: &[Legend]) -> String {
+    let width = rects.iter().map(|r| r.x + r.w).fold(0.0, f64::max) * UNIT + GAP;
+    let height = rects.iter().map(|r| r.y + r.h).fold(0.0, f64::max) * UNIT + GAP;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" text-anchor=\"middle\">"
+    );
+    let _ = writeln!(svg, "<title>{}</title>", escape(name));
+    for (rect, legend) in rects.iter().zip(legends) {
+        let x = rect.x * UNIT + GAP;
+        let y = rect.y * UNIT + GAP;
+        let w = rect.w * UNIT - GAP;
+        let h = rect.h * UNIT - GAP;
+        let cx = x + w / 2.0;
+        let fill = if legend.main == "▽" && legend.sub.is_none() {
+            "#f4f4f4"
+        } else {
+            "#ffffff"
+        };
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" rx=\"6\" fill=\"{fill}\" stroke=\"#888\"/>"
+        );
+        if let Some(alias) = &legend.alias {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{cx}\" y=\"{}\" font-size=\"9\" fill=\"#888\">{}</text>",
+                y + 11.0,
+                escape(alias)
+            );
+        }
+        let main_size = if legend.main.chars().count() > 4 {
+            11
+        } else {
+            16
+        };
+        let _ = writeln!(
+            svg,
+            "<text x=\"{cx}\" y=\"{}\" font-size=\"{main_size}\">{}</text>",
+            y + h / 2.0 + 5.0,
+            escape(&legend.main)
+        );
+        if let Some(sub) = &legend.sub {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{cx}\" y=\"{}\" font-size=\"9\" fill=\"#1f6fb2\">{}</text>",
+                y + h - 5.0,
+                escape(sub)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn index_html(sections: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>kanata layers</title>\n\
+         <style>body {{ font-family: sans-serif; }} img {{ max-width: 100%; }}</style>\n\
+         </head>\n<body>\n<h1>kanata layers</h1>\n{sections}</body>\n</html>\n"
+    )
+}
+
+/// A layer name usable in a file name.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanata_parser::cfg;
+
+    fn key(line: usize, column: usize) -> DefsrcKey {
+        DefsrcKey {
+            osc: OsCode::KEY_A,
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn geometry_follows_defsrc_alignment() {
+        let keys = [key(1, 2), key(1, 7), key(1, 12), key(2, 7), key(2, 9)];
+        let xs = defsrc_geometry(&keys)
+            .iter()
+            .map(|r| (r.x, r.y))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            xs,
+            vec![(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn layout_file_formats() {
+        let rects = parse_layout(r#"[{"x": 0, "y": 0}, {"x": 1, "y": 0, "w": 1.5}]"#).unwrap();
+        assert_eq!(rects[1].w, 1.5);
+        assert_eq!(rects[1].h, 1.0);
+        let qmk = r#"{"layouts": {"LAYOUT": {"layout": [{"matrix": [0, 0], "x": 2, "y": 1}]}}}"#;
+        assert_eq!(parse_layout(qmk).unwrap()[0].x, 2.0);
+        assert!(parse_layout(r#"[{"x": 0}]"#).is_err());
+    }
+
+    #[test]
+    fn renders_each_layer() {
+        let cfg = cfg::new_from_str(
+            "
+(defcfg process-unmapped-keys no)
+(defsrc a   b   c   d)
+(defalias hm (tap-hold 200 200 a lsft) nav (layer-while-held nav))
+(deflayer base @hm @nav (layer-switch nav) mlft)
+(deflayer nav  _   _    XX    (multi lctl c))
+",
+            Default::default(),
+        )
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("kanata-render-{}", std::process::id()));
+        render_layers(&cfg, None, &dir).unwrap();
+
+        let base = std::fs::read_to_string(dir.join("0-base.svg")).unwrap();
+        for text in [">@hm<", ">A<", ">‹⇧<", ">⇑nav<", ">→nav<", ">‹🖰<"] {
+            assert!(base.contains(text), "{text} not in {base}");
+        }
+        let nav = std::fs::read_to_string(dir.join("1-nav.svg")).unwrap();
+        for text in [">▽<", ">✗<", ">‹⎈+C<"] {
+            assert!(nav.contains(text), "{text} not in {nav}");
+        }
+        let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(index.contains("src=\"1-nav.svg\""));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,467 @@
+//! Generation of `deflocalkeys-linux` and `defoslayout` from an XKB keymap for
+//! `--gen-localkeys xkb`.
+//!
+//! The keymap is the text output of `xkbcomp`, e.g. `xkbcomp -xkb $DISPLAY keymap.xkb`. The
+//! `xkb_keycodes` section gives the keycode of each key name and the `xkb_symbols` section gives
+//! the keysyms of each level of the first group. XKB keycodes are evdev codes plus 8.
+
+use anyhow::{Result, anyhow, bail};
+use kanata_parser::keys::{KEY_NAMES, OsCode, str_to_oscode};
+use rustc_hash::FxHashMap as HashMap;
+
+/// Prefix of the comments for everything that could not be translated.
+const MARKER: &str = "xkb-import:";
+
+/// Offset of XKB keycodes from evdev codes.
+const XKB_KEYCODE_OFFSET: u16 = 8;
+
+/// The modifier prefixes of the first four levels: base, shift, AltGr and shift+AltGr.
+const LEVEL_PREFIXES: [&str; 4] = ["", "S-", "AG-", "S-AG-"];
+
+/// Keysym names of printable ASCII characters other than letters and digits.
+const ASCII_KEYSYMS: &[(&str, char)] = &[
+    ("space", ' '),
+    ("exclam", '!'),
+    ("quotedbl", '"'),
+    ("numbersign", '#'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("apostrophe", '\''),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("asterisk", '*'),
+    ("plus", '+'),
+    ("comma", ','),
+    ("minus", '-'),
+    ("period", '.'),
+    ("slash", '/'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("less", '<'),
+    ("equal", '='),
+    ("greater", '>'),
+    ("question", '?'),
+    ("at", '@'),
+    ("bracketleft", '['),
+    ("backslash", '\\'),
+    ("bracketright", ']'),
+    ("asciicircum", '^'),
+    ("underscore", '_'),
+    ("grave", '`'),
+    ("braceleft", '{'),
+    ("bar", '|'),
+    ("braceright", '}'),
+    ("asciitilde", '~'),
+    ("EuroSign", '€'),
+];
+
+/// Keysym names of the Latin-1 characters U+00A0 to U+00FF, in order.
+const LATIN1_KEYSYMS: [&str; 96] = [
+    "nobreakspace",
+    "exclamdown",
+    "cent",
+    "sterling",
+    "currency",
+    "yen",
+    "brokenbar",
+    "section",
+    "diaeresis",
+    "copyright",
+    "ordfeminine",
+    "guillemotleft",
+    "notsign",
+    "hyphen",
+    "registered",
+    "macron",
+    "degree",
+    "plusminus",
+    "twosuperior",
+    "threesuperior",
+    "acute",
+    "mu",
+    "paragraph",
+    "periodcentered",
+    "cedilla",
+    "onesuperior",
+    "masculine",
+    "guillemotright",
+    "onequarter",
+    "onehalf",
+    "threequarters",
+    "questiondown",
+    "Agrave",
+    "Aacute",
+    "Acircumflex",
+    "Atilde",
+    "Adiaeresis",
+    "Aring",
+    "AE",
+    "Ccedilla",
+    "Egrave",
+    "Eacute",
+    "Ecircumflex",
+    "Ediaeresis",
+    "Igrave",
+    "Iacute",
+    "Icircumflex",
+    "Idiaeresis",
+    "ETH",
+    "Ntilde",
+    "Ograve",
+    "Oacute",
+    "Ocircumflex",
+    "Otilde",
+    "Odiaeresis",
+    "multiply",
+    "Oslash",
+    "Ugrave",
+    "Uacute",
+    "Ucircumflex",
+    "Udiaeresis",
+    "Yacute",
+    "THORN",
+    "ssharp",
+    "agrave",
+    "aacute",
+    "acircumflex",
+    "atilde",
+    "adiaeresis",
+    "aring",
+    "ae",
+    "ccedilla",
+    "egrave",
+    "eacute",
+    "ecircumflex",
+    "ediaeresis",
+    "igrave",
+    "iacute",
+    "icircumflex",
+    "idiaeresis",
+    "eth",
+    "ntilde",
+    "ograve",
+    "oacute",
+    "ocircumflex",
+    "otilde",
+    "odiaeresis",
+    "division",
+    "oslash",
+    "ugrave",
+    "uacute",
+    "ucircumflex",
+    "udiaeresis",
+    "yacute",
+    "thorn",
+    "ydiaeresis",
+];
+
+/// Generate `deflocalkeys-linux` with a key name for each character on the base level that has
+/// no kanata key name yet, and `defoslayout` with the key for each character on the first four
+/// levels.
+pub(crate) fn convert(keymap: &str, file_name: &str) -> Result<String> {
+    let keycodes = parse_keycodes(
+        section(keymap, "xkb_keycodes")
+            .ok_or_else(|| anyhow!("the keymap has no xkb_keycodes section"))?,
+    );
+    let symbols = section(keymap, "xkb_symbols")
+        .ok_or_else(|| anyhow!("the keymap has no xkb_symbols section"))?;
+
+    let mut local_keys: Vec<(char, u16)> = vec![];
+    let mut layout: Vec<(char, String)> = vec![];
+    let mut unknown_keysyms: Vec<String> = vec![];
+    for (key, keysyms) in parse_symbols(symbols) {
+        let Some(code) = keycodes
+            .get(key)
+            .and_then(|code| code.checked_sub(XKB_KEYCODE_OFFSET))
+        else {
+            continue;
+        };
+        let key_name = kanata_key_name(code);
+        for (level, keysym) in keysyms.iter().enumerate().take(LEVEL_PREFIXES.len()) {
+            let Some(c) = keysym_char(keysym) else {
+                if !is_non_character_keysym(keysym) && !unknown_keysyms.iter().any(|k| k == keysym)
+                {
+                    unknown_keysyms.push(keysym.to_string());
+                }
+                continue;
+            };
+            if level == 0
+                && !c.is_whitespace()
+                && str_to_oscode(c.encode_utf8(&mut [0; 4])).is_none()
+                && !local_keys.iter().any(|(lc, _)| *lc == c)
+            {
+                local_keys.push((c, code));
+            }
+            if let Some(key_name) = key_name
+                && !layout.iter().any(|(lc, _)| *lc == c)
+            {
+                layout.push((c, format!("{}{key_name}", LEVEL_PREFIXES[level])));
+            }
+        }
+    }
+    if layout.is_empty() {
+        bail!("the xkb_symbols section has no characters");
+    }
+
+    let mut out = format!(";; Generated from the XKB keymap {file_name}.\n");
+    if !unknown_keysyms.is_empty() {
+        out.push_str(&format!(
+            ";; {MARKER} unknown keysyms not translated: {}\n",
+            unknown_keysyms.join(", ")
+        ));
+    }
+    if !local_keys.is_empty() {
+        out.push_str("\n(deflocalkeys-linux\n");
+        for (c, code) in local_keys.iter() {
+            out.push_str(&format!("  {} {code}\n", quoted(*c)));
+        }
+        out.push_str(")\n");
+    }
+    out.push_str("\n(defoslayout\n");
+    for (c, key) in layout.iter() {
+        out.push_str(&format!("  {} {key}\n", quoted(*c)));
+    }
+    out.push_str(")\n");
+    Ok(out)
+}
+
+/// The content between the braces of the first section with the given name.
+fn section<'a>(keymap: &'a str, name: &str) -> Option<&'a str> {
+    let start = keymap.find(name)?;
+    let open = start + keymap[start..].find('{')?;
+    let mut depth = 0;
+    for (i, c) in keymap[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&keymap[open + 1..open + i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Keycodes by key name, including aliases, from statements like `<AE01> = 10;` and
+/// `alias <AC12> = <BKSL>;`.
+fn parse_keycodes(keycodes: &str) -> HashMap<&str, u16> {
+    let mut codes = HashMap::default();
+    let mut aliases = vec![];
+    for statement in keycodes.split(';').map(str::trim) {
+        let Some((name, value)) = statement.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if let Some(alias) = name.trim().strip_prefix("alias") {
+            aliases.push((key_name(alias), key_name(value)));
+        } else if let Ok(code) = value.parse::<u16>() {
+            codes.insert(key_name(name), code);
+        }
+    }
+    for (alias, name) in aliases {
+        if let Some(code) = codes.get(name).copied() {
+            codes.entry(alias).or_insert(code);
+        }
+    }
+    codes
+}
+
+/// The key name within `<>`.
+fn key_name(s: &str) -> &str {
+    s.trim().trim_start_matches('<').trim_end_matches('>')
+}
+
+/// The keysyms of the first group of each key from statements like
+/// `key <AE01> { [ 1, exclam, onesuperior, exclamdown ] };`.
+fn parse_symbols(symbols: &str) -> Vec<(&str, Vec<&str>)> {
+    let mut keys = vec![];
+    let mut rest = symbols;
+    while let Some(start) = rest.find("key <") {
+        rest = &rest[start + "key ".len()..];
+        let Some(name_end) = rest.find('>') else {
+            break;
+        };
+        let name = key_name(&rest[..=name_end]);
+        let body_end = rest.find("};").unwrap_or(rest.len());
+        let body = &rest[..body_end];
+        rest = &rest[body_end..];
+        // The first list is the first group, with or without `symbols[Group1]=` before it.
+        let Some(list) = body
+            .split_once('[')
+            .and_then(|(before, after)| match before.ends_with("symbols") {
+                // `symbols[Group1]= [ ... ]`
+                true => after.split_once('[').map(|(_, list)| list),
+                false => Some(after),
+            })
+            .and_then(|list| list.split_once(']'))
+            .map(|(list, _)| list)
+        else {
+            continue;
+        };
+        keys.push((name, list.split(',').map(str::trim).collect()));
+    }
+    keys
+}
+
+/// The character typed by a keysym.
+fn keysym_char(keysym: &str) -> Option<char> {
+    let mut chars = keysym.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    if let Some((_, c)) = ASCII_KEYSYMS.iter().find(|(name, _)| *name == keysym) {
+        return Some(*c);
+    }
+    if let Some(i) = LATIN1_KEYSYMS.iter().position(|name| *name == keysym) {
+        return char::from_u32(0xA0 + i as u32);
+    }
+    // Unicode keysyms are either named like U20AC or have the value 0x01000000 + codepoint.
+    let codepoint = match (keysym.strip_prefix('U'), keysym.strip_prefix("0x")) {
+        (Some(hex), _) if hex.len() >= 4 => u32::from_str_radix(hex, 16).ok()?,
+        (_, Some(hex)) => u32::from_str_radix(hex, 16)
+            .ok()?
+            .checked_sub(0x0100_0000)?,
+        _ => return None,
+    };
+    char::from_u32(codepoint).filter(|c| !c.is_control())
+}
+
+/// Keysyms that do not type a character, such as dead keys and modifiers.
+fn is_non_character_keysym(keysym: &str) -> bool {
+    // Dead keys, modifiers and most function keys have an underscore, e.g. dead_acute or Shift_L.
+    keysym.is_empty()
+        || keysym.contains('_')
+        || keysym.starts_with("XF86")
+        || matches!(
+            keysym,
+            "NoSymbol" | "VoidSymbol" | "Escape" | "Tab" | "Return" | "BackSpace" | "Delete"
+        )
+}
+
+/// The first kanata key name of an evdev code.
+fn kanata_key_name(code: u16) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .copied()
+        .find(|name| str_to_oscode(name).is_some_and(|osc: OsCode| osc as u16 == code))
+}
+
+/// A character as an atom of the configuration.
+fn quoted(c: char) -> String {
+    match c {
+        '"' => r##"r#"""#"##.to_string(),
+        '(' | ')' | ';' | ' ' => format!("\"{c}\""),
+        _ => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYMAP: &str = r#"
+xkb_keymap {
+xkb_keycodes "evdev+aliases(qwertz)" {
+    minimum = 8;
+    maximum = 255;
+    <AE01> = 10;
+    <AE08> = 17;
+    <AD03> = 26;
+    <AD06> = 29;
+    <AD11> = 34;
+    <AB01> = 52;
+    <SPCE> = 65;
+    <LFSH> = 50;
+    <TLDE> = 49;
+    alias <AC12> = <BKSL>;
+};
+xkb_types "complete" {
+    type "ONE_LEVEL" {
+        modifiers= none;
+    };
+};
+xkb_symbols "pc+de+inet(evdev)" {
+    name[group1]="German";
+    key <TLDE> {         [     dead_circumflex,          degree ] };
+    key <AE01> {         [               1,          exclam,     onesuperior,      exclamdown ] };
+    key <AE08> {         [               8,       parenleft,     bracketleft,       trademark ] };
+    key <AD03> {
+        type= "FOUR_LEVEL_SEMIALPHABETIC",
+        symbols[Group1]= [               e,               E,        EuroSign,        EuroSign ]
+    };
+    key <AD06> {         [               z,               Z,       leftarrow,             yen ] };
+    key <AD11> {         [      udiaeresis,      Udiaeresis,  dead_diaeresis,  dead_abovering ] };
+    key <AB01> {         [               y,               Y,          guillemotright,  U203A ] };
+    key <SPCE> {         [           space ] };
+    key <LFSH> {         [         Shift_L ] };
+    modifier_map Shift { <LFSH> };
+};
+};
+"#;
+
+    #[test]
+    fn converts_keymap() {
+        let converted = convert(KEYMAP, "de.xkb").unwrap();
+        assert_eq!(
+            converted,
+            r#";; Generated from the XKB keymap de.xkb.
+;; xkb-import: unknown keysyms not translated: trademark, leftarrow
+
+(deflocalkeys-linux
+  ü 26
+)
+
+(defoslayout
+  ° S-grv
+  1 1
+  ! S-1
+  ¹ AG-1
+  ¡ S-AG-1
+  8 8
+  "(" S-8
+  [ AG-8
+  e e
+  E S-e
+  € AG-e
+  z y
+  Z S-y
+  ¥ S-AG-y
+  ü lbrc
+  Ü S-lbrc
+  y z
+  Y S-z
+  » AG-z
+  › S-AG-z
+  " " spc
+)
+"#
+        );
+        if let Err(e) = kanata_parser::cfg::new_from_str(
+            &format!("{converted}(defsrc a) (deflayer base (type-text \"Üz\"))"),
+            Default::default(),
+        ) {
+            panic!("{e:?}\n{converted}");
+        }
+    }
+
+    #[test]
+    fn keysym_chars() {
+        assert_eq!(keysym_char("a"), Some('a'));
+        assert_eq!(keysym_char("ssharp"), Some('ß'));
+        assert_eq!(keysym_char("ydiaeresis"), Some('ÿ'));
+        assert_eq!(keysym_char("U20AC"), Some('€'));
+        assert_eq!(keysym_char("0x10020ac"), Some('€'));
+        assert_eq!(keysym_char("dead_acute"), None);
+    }
+
+    #[test]
+    fn missing_sections() {
+        assert!(convert("xkb_keymap { };", "test").is_err());
+        assert!(convert("xkb_keycodes { <AE01> = 10; };", "test").is_err());
+        assert!(convert("xkb_keycodes { }; xkb_symbols { };", "test").is_err());
+    }
+}
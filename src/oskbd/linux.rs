@@ -814,12 +814,14 @@ fn handle_signals(symlink: Option<Symlink>) {
         if let Some(signal) = (&mut signals).into_iter().next() {
             match signal {
                 SIGINT | SIGTERM => {
+                    crate::kanata::save_state_before_exit();
                     drop(symlink);
                     signal_hook::low_level::emulate_default_handler(signal)
                         .expect("run original sighandlers");
                     unreachable!();
                 }
                 SIGTSTP => {
+                    crate::kanata::save_state_before_exit();
                     drop(symlink);
                     log::warn!("got SIGTSTP, exiting instead of pausing so keyboards don't hang");
                     std::process::exit(SIGTSTP);
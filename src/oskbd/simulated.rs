@@ -12,7 +12,7 @@ pub fn concat_os_str2(a: &OsStr, b: &OsStr) -> OsString {
     ret.push(b); // doesn't allocate
     ret
 }
-fn append_file_name(path: impl AsRef<Path>, appendix: impl AsRef<OsStr>) -> PathBuf {
+pub fn append_file_name(path: impl AsRef<Path>, appendix: impl AsRef<OsStr>) -> PathBuf {
     let path = path.as_ref();
     let mut result = path.to_owned();
     let stem_in = path.file_stem().unwrap_or(OsStr::new(""));
@@ -24,7 +24,7 @@ fn append_file_name(path: impl AsRef<Path>, appendix: impl AsRef<OsStr>) -> Path
     result
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LogFmtT {
     InKeyUp,
     InKeyDown,
@@ -40,10 +40,57 @@ pub enum LogFmtT {
     Code,
     RawUp,
     RawDown,
+    Scroll,
+    Cmd,
+}
+
+impl LogFmtT {
+    /// Whether the event is a simulated input (`in`) or an action taken by kanata (`out`).
+    pub fn direction(self) -> &'static str {
+        match self {
+            LogFmtT::InKeyUp | LogFmtT::InKeyDown | LogFmtT::InKeyRep | LogFmtT::InTick => "in",
+            _ => "out",
+        }
+    }
+
+    /// Short, stable name of the event kind for machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            LogFmtT::InKeyUp | LogFmtT::KeyUp => "release",
+            LogFmtT::InKeyDown | LogFmtT::KeyDown => "press",
+            LogFmtT::InKeyRep => "repeat",
+            LogFmtT::InTick | LogFmtT::Tick => "tick",
+            LogFmtT::MouseUp => "mouse-release",
+            LogFmtT::MouseDown => "mouse-press",
+            LogFmtT::MouseMove => "mouse-move",
+            LogFmtT::Scroll => "scroll",
+            LogFmtT::Unicode => "unicode",
+            LogFmtT::Code => "code",
+            LogFmtT::RawUp => "raw-release",
+            LogFmtT::RawDown => "raw-press",
+            LogFmtT::Cmd => "cmd",
+        }
+    }
+}
+
+/// A single simulated event in structured form,
+/// recorded alongside the text table for machine-readable output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimEvent {
+    /// Milliseconds elapsed since the start of the simulation.
+    pub tick: u64,
+    pub kind: LogFmtT,
+    /// Key name, mouse button, mouse movement, unicode character or command.
+    pub value: String,
+    /// Layer that was active when the event happened, as last set via [`LogFmt::set_layer`].
+    pub layer: Option<String>,
 }
 
 pub struct LogFmt {
     ticks: u64,
+    elapsed: u64,
+    layer: Option<String>,
+    pub events: Vec<SimEvent>,
     //In       	//
     in_time: String,
     in_key_up: String,
@@ -72,6 +119,9 @@ impl LogFmt {
     pub fn new() -> Self {
         Self {
             ticks: 0,
+            elapsed: 0,
+            layer: None,
+            events: vec![],
             //In       	//
             in_time: String::new(),
             in_key_up: String::new(),
@@ -92,6 +142,23 @@ impl LogFmt {
             combo: String::new(),
         }
     }
+    /// Record a structured event without adding it to the text table.
+    pub fn record(&mut self, kind: LogFmtT, value: String) {
+        self.events.push(SimEvent {
+            tick: self.elapsed,
+            kind,
+            value,
+            layer: self.layer.clone(),
+        });
+    }
+
+    /// Set the layer that events recorded from now on are attributed to.
+    pub fn set_layer(&mut self, layer: &str) {
+        if self.layer.as_deref() != Some(layer) {
+            self.layer = Some(layer.to_string());
+        }
+    }
+
     pub fn fmt(&mut self, key: LogFmtT, value: String) {
         let mut pad = value.len();
         let mut time = "".to_string();
@@ -160,7 +227,7 @@ impl LogFmt {
         } else {
             &blank
         };
-        self.mouse_move += if key == LogFmtT::MouseMove {
+        self.mouse_move += if matches!(key, LogFmtT::MouseMove | LogFmtT::Scroll) {
             self.in_combo += &blank;
             self.combo += &val;
             &val
@@ -200,49 +267,63 @@ impl LogFmt {
     #[cfg(any(target_os = "linux", target_os = "android"))]
     pub fn write_raw(&mut self, event: InputEvent) {
         let key_name = KeyCode::from(OsCode::from(event.code));
-        if event.up {
-            self.fmt(LogFmtT::RawUp, key_name.to_string())
+        let kind = if event.up {
+            LogFmtT::RawUp
         } else {
-            self.fmt(LogFmtT::RawDown, key_name.to_string())
-        }
+            LogFmtT::RawDown
+        };
+        self.record(kind, format!("{key_name:?}"));
+        self.fmt(kind, key_name.to_string())
     }
     pub fn in_tick(&mut self, t: u128) {
         self.fmt(LogFmtT::InTick, t.to_string())
     }
     pub fn in_press_key(&mut self, key: OsCode) {
+        self.record(LogFmtT::InKeyDown, format!("{:?}", KeyCode::from(key)));
         self.fmt(LogFmtT::InKeyDown, KeyCode::from(key).to_string())
     }
     pub fn in_release_key(&mut self, key: OsCode) {
+        self.record(LogFmtT::InKeyUp, format!("{:?}", KeyCode::from(key)));
         self.fmt(LogFmtT::InKeyUp, KeyCode::from(key).to_string())
     }
     pub fn in_repeat_key(&mut self, key: OsCode) {
+        self.record(LogFmtT::InKeyRep, format!("{:?}", KeyCode::from(key)));
         self.fmt(LogFmtT::InKeyRep, KeyCode::from(key).to_string())
     }
     pub fn press_key(&mut self, key: OsCode) {
+        self.record(LogFmtT::KeyDown, format!("{:?}", KeyCode::from(key)));
         self.fmt(LogFmtT::KeyDown, KeyCode::from(key).to_string())
     }
     pub fn release_key(&mut self, key: OsCode) {
+        self.record(LogFmtT::KeyUp, format!("{:?}", KeyCode::from(key)));
         self.fmt(LogFmtT::KeyUp, KeyCode::from(key).to_string())
     }
     pub fn send_unicode(&mut self, c: char) {
+        self.record(LogFmtT::Unicode, c.to_string());
         self.fmt(LogFmtT::Unicode, c.to_string())
     }
     pub fn click_btn(&mut self, btn: Btn) {
+        self.record(LogFmtT::MouseDown, format!("{btn:?}"));
         self.fmt(LogFmtT::MouseDown, btn.to_string())
     }
     pub fn release_btn(&mut self, btn: Btn) {
+        self.record(LogFmtT::MouseUp, format!("{btn:?}"));
         self.fmt(LogFmtT::MouseUp, btn.to_string())
     }
     pub fn set_mouse(&mut self, x: u16, y: u16) {
+        self.record(LogFmtT::MouseMove, format!("@{x},{y}"));
         self.fmt(LogFmtT::MouseMove, format!("@{x},{y}"))
     }
     pub fn scroll(&mut self, dir: MWheelDirection, dist: u16) {
-        self.fmt(LogFmtT::MouseMove, format!("{dir}{dist}"))
+        self.record(LogFmtT::Scroll, format!("{dir:?},{dist}"));
+        self.fmt(LogFmtT::Scroll, format!("{dir}{dist}"))
     }
     pub fn move_mouse(&mut self, dir: MoveDirection, dist: u16) {
+        self.record(LogFmtT::MouseMove, format!("{dir:?},{dist}"));
         self.fmt(LogFmtT::MouseMove, format!("{dir}{dist}"))
     }
     pub fn write_code(&mut self, code: u32, value: KeyValue) {
+        self.record(LogFmtT::Code, format!("{code};{value:?}"));
         self.fmt(LogFmtT::Code, format!("{code};{value:?}"))
     }
 
@@ -447,9 +528,14 @@ impl KbdOut {
         log::info!("out🖰:@{x},{y}");
         Ok(())
     }
+    pub fn log_cmd<S: AsRef<str>>(&mut self, cmd: &[S]) {
+        let cmd = cmd.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+        self.log.record(LogFmtT::Cmd, cmd.join(" "));
+    }
     pub fn tick(&mut self) {
         self.outputs.ticks += 1;
         self.log.ticks += 1;
+        self.log.elapsed += 1;
     }
 }
 
@@ -23,6 +23,8 @@ pub type Connections = Arc<Mutex<HashMap<String, TcpStream>>>;
 #[cfg(not(feature = "tcp_server"))]
 pub type Connections = ();
 
+#[cfg(feature = "tcp_server")]
+use crate::kanata::adaptive_keys;
 #[cfg(feature = "tcp_server")]
 use kanata_parser::custom_action::FakeKeyAction;
 
@@ -259,6 +261,123 @@ impl TcpServer {
                                                 }
                                                 drop(k);
                                             }
+                                            ClientMessage::RequestStateVars {} => {
+                                                let k = kanata.lock();
+                                                let msg = ServerMessage::StateVars {
+                                                    vars: k
+                                                        .state_var_names
+                                                        .iter()
+                                                        .zip(k.layout.b().state_vars.iter())
+                                                        .map(|(name, value)| StateVarValue {
+                                                            name: name.clone(),
+                                                            value: *value,
+                                                        })
+                                                        .collect(),
+                                                };
+                                                drop(k);
+                                                match stream.write_all(&msg.as_bytes()) {
+                                                    Ok(_) => {}
+                                                    Err(err) => log::error!(
+                                                        "server could not send response: {err}"
+                                                    ),
+                                                }
+                                            }
+                                            ClientMessage::RequestLayerLock {} => {
+                                                let k = kanata.lock();
+                                                let msg = ServerMessage::LayerLock {
+                                                    locked: k.layout.b().locked_layer.map(
+                                                        |layer| {
+                                                            k.layer_info[usize::from(layer)]
+                                                                .name
+                                                                .clone()
+                                                        },
+                                                    ),
+                                                };
+                                                drop(k);
+                                                match stream.write_all(&msg.as_bytes()) {
+                                                    Ok(_) => {}
+                                                    Err(err) => log::error!(
+                                                        "server could not send response: {err}"
+                                                    ),
+                                                }
+                                            }
+                                            ClientMessage::RequestAdaptiveTapHold {} => {
+                                                let k = kanata.lock();
+                                                let keys =
+                                                    adaptive_keys(&k.layout.b().adaptive_tap_hold);
+                                                drop(k);
+                                                let msg = ServerMessage::AdaptiveTapHold {
+                                                    keys: keys
+                                                        .into_iter()
+                                                        .map(|(key, learned)| AdaptiveTapHoldKey {
+                                                            key,
+                                                            timeout: learned.timeout,
+                                                            prior_idle: learned.prior_idle,
+                                                            taps: learned.taps,
+                                                            holds: learned.holds,
+                                                            avg_tap_ms: learned.avg_tap_ticks(),
+                                                            avg_hold_ms: learned.avg_hold_ticks(),
+                                                            false_holds: learned.false_holds,
+                                                            false_taps: learned.false_taps,
+                                                        })
+                                                        .collect(),
+                                                };
+                                                match stream.write_all(&msg.as_bytes()) {
+                                                    Ok(_) => {}
+                                                    Err(err) => log::error!(
+                                                        "server could not send response: {err}"
+                                                    ),
+                                                }
+                                            }
+                                            ClientMessage::RequestStats {} => {
+                                                let k = kanata.lock();
+                                                let msg = match &k.stats {
+                                                    Some(stats) => ServerMessage::Stats {
+                                                        stats: stats.snapshot(),
+                                                    },
+                                                    None => ServerMessage::Error {
+                                                        msg: "stats are not enabled in defcfg"
+                                                            .to_string(),
+                                                    },
+                                                };
+                                                drop(k);
+                                                match stream.write_all(&msg.as_bytes()) {
+                                                    Ok(_) => {}
+                                                    Err(err) => log::error!(
+                                                        "server could not send response: {err}"
+                                                    ),
+                                                }
+                                            }
+                                            ClientMessage::SetStateVar { name, value } => {
+                                                let mut k = kanata.lock();
+                                                match k
+                                                    .state_var_names
+                                                    .iter()
+                                                    .position(|var| *var == name)
+                                                {
+                                                    Some(index) => {
+                                                        log::info!(
+                                                            "tcp server set state variable: {name} {value}"
+                                                        );
+                                                        k.layout.bm().state_vars[index] = value;
+                                                    }
+                                                    None => {
+                                                        drop(k);
+                                                        if let Err(e) = stream.write_all(
+                                                            &ServerMessage::Error {
+                                                                msg: format!(
+                                                                    "unknown state variable: {name}"
+                                                                ),
+                                                            }
+                                                            .as_bytes(),
+                                                        ) {
+                                                            log::error!("stream write error: {e}");
+                                                            connections.lock().remove(&addr);
+                                                            break;
+                                                        }
+                                                    }
+                                                }
+                                            }
                                             ClientMessage::SetMouse { x, y } => {
                                                 log::info!(
                                                     "tcp server SetMouse action: x {x} y {y}"
@@ -322,6 +441,10 @@ impl TcpServer {
                                                     "current-layer-info".to_string(),
                                                     "fake-key".to_string(),
                                                     "set-mouse".to_string(),
+                                                    "state-vars".to_string(),
+                                                    "layer-lock".to_string(),
+                                                    "adaptive-tap-hold".to_string(),
+                                                    "stats".to_string(),
                                                 ];
                                                 let msg = ServerMessage::HelloOk {
                                                     version,
@@ -0,0 +1,107 @@
+use super::*;
+
+static AUTOCORRECT_CFG: &str = "
+(defcfg process-unmapped-keys yes)
+(defsrc f1)
+(deflayer base autocorrect-toggle)
+(defautocorrect autocorrect.txt)
+";
+
+fn simulate_autocorrect(sim: &str) -> String {
+    let mut file_content = FxHashMap::default();
+    file_content.insert(
+        "autocorrect.txt".into(),
+        "# comment\n\n:teh: -> the\nrecieve -> receive\n".into(),
+    );
+    simulate_with_file_content(AUTOCORRECT_CFG, sim, file_content)
+        .no_time()
+        .to_ascii()
+}
+
+#[test]
+fn autocorrect_typo_at_boundary() {
+    let result = simulate_autocorrect("d:t u:t d:e u:e d:h u:h d:spc u:spc t:50");
+    assert_eq!(
+        "dn:T up:T dn:E up:E dn:H up:H \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:H up:H dn:E up:E dn:Space up:Space",
+        result
+    );
+    // a typo inside of a longer word is not corrected
+    let result = simulate_autocorrect("d:t u:t d:e u:e d:h u:h d:s u:s d:spc u:spc t:50");
+    assert!(!result.contains("BSpace"), "{result}");
+    let result = simulate_autocorrect("d:a u:a d:t u:t d:e u:e d:h u:h d:spc u:spc t:50");
+    assert!(!result.contains("BSpace"), "{result}");
+}
+
+#[test]
+fn autocorrect_carries_over_case_and_tracks_backspace() {
+    let result = simulate_autocorrect("d:lsft d:t u:t u:lsft d:e u:e d:h u:h d:ret u:ret t:50");
+    assert!(
+        result.contains("dn:LShift dn:T up:T up:LShift dn:H up:H dn:E up:E dn:Enter"),
+        "{result}"
+    );
+    let result =
+        simulate_autocorrect("d:t u:t d:e u:e d:h u:h d:s u:s d:bspc u:bspc d:spc u:spc t:50");
+    assert!(result.contains("dn:E up:E dn:Space"), "{result}");
+}
+
+#[test]
+fn autocorrect_undo_on_backspace() {
+    let result =
+        simulate_autocorrect("d:t u:t d:e u:e d:h u:h d:spc u:spc d:bspc u:bspc d:spc u:spc t:50");
+    assert_eq!(
+        "dn:T up:T dn:E up:E dn:H up:H \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:H up:H dn:E up:E dn:Space up:Space \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:E up:E dn:H up:H dn:Space up:Space dn:Space up:Space",
+        result
+    );
+    // the boundary key is typed again as it was, here with shift
+    let result = simulate_autocorrect(
+        "d:t u:t d:e u:e d:h u:h d:lsft d:spc u:spc u:lsft d:bspc u:bspc t:50",
+    );
+    assert!(
+        result.ends_with("dn:T up:T dn:E up:E dn:H up:H dn:LShift dn:Space up:Space up:LShift"),
+        "{result}"
+    );
+}
+
+#[test]
+fn autocorrect_skips_expanded_words() {
+    let cfg = "(defcfg process-unmapped-keys yes) (defsrc) (deflayer base) \
+         (defexpand expansions (teh \"tee hee\")) \
+         (defautocorrect autocorrect.txt)";
+    let mut file_content = FxHashMap::default();
+    file_content.insert("autocorrect.txt".into(), "teh -> the\n".into());
+    let result = simulate_with_file_content(
+        cfg,
+        "d:t u:t d:e u:e d:h u:h d:spc u:spc t:50",
+        file_content,
+    )
+    .no_time()
+    .to_ascii();
+    assert_eq!(
+        "dn:T up:T dn:E up:E dn:H up:H \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:E up:E dn:E up:E dn:Space up:Space dn:H up:H dn:E up:E dn:E up:E \
+         dn:Space up:Space",
+        result
+    );
+}
+
+#[test]
+fn autocorrect_toggle() {
+    let result = simulate_autocorrect(
+        "d:f1 u:f1 d:t u:t d:e u:e d:h u:h d:spc u:spc \
+         d:f1 u:f1 d:t u:t d:e u:e d:h u:h d:spc u:spc t:50",
+    );
+    assert_eq!(
+        "dn:T up:T dn:E up:E dn:H up:H dn:Space up:Space \
+         dn:T up:T dn:E up:E dn:H up:H \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:H up:H dn:E up:E dn:Space up:Space",
+        result
+    );
+}
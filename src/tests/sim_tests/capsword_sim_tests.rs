@@ -102,3 +102,72 @@ fn caps_word_custom_toggle_does_toggle() {
         result
     );
 }
+
+const CASE_WORD_CFG: &str = "
+(defsrc 1 2 3 4 5 a b spc .)
+(deflayer base
+  (case-word 1000 snake)
+  (case-word 1000 screaming-kebab)
+  (case-word 1000 camel)
+  (case-word 1000 (separator .))
+  (case-word-custom-toggle 1000 pascal (a) ())
+  a b spc .
+)
+";
+
+#[test]
+fn case_word_snake_replaces_space() {
+    let result = simulate(
+        CASE_WORD_CFG,
+        "d:1 u:1 d:a u:a d:spc u:spc d:b u:b t:10 d:. u:. d:spc u:spc t:10",
+    )
+    .no_time();
+    assert_eq!(
+        "out:↓A out:↑A out:↓LShift out:↓Minus out:↑LShift out:↑Minus out:↓B out:↑B \
+         out:↓Dot out:↑Dot out:↓Space out:↑Space",
+        result
+    );
+}
+
+#[test]
+fn case_word_screaming_kebab_capitalizes() {
+    let result = simulate(CASE_WORD_CFG, "d:2 u:2 d:a u:a d:spc u:spc d:b u:b t:10").no_time();
+    assert_eq!(
+        "out:↓LShift out:↓A out:↑LShift out:↑A out:↓Minus out:↑Minus \
+         out:↓LShift out:↓B out:↑LShift out:↑B",
+        result
+    );
+}
+
+#[test]
+fn case_word_camel_capitalizes_after_space() {
+    let result = simulate(
+        CASE_WORD_CFG,
+        "d:3 u:3 d:a u:a d:spc u:spc d:b u:b d:a u:a t:10",
+    )
+    .no_time();
+    assert_eq!(
+        "out:↓A out:↑A out:↓LShift out:↓B out:↑LShift out:↑B out:↓A out:↑A",
+        result
+    );
+}
+
+#[test]
+fn case_word_custom_separator() {
+    let result = simulate(CASE_WORD_CFG, "d:4 u:4 d:a u:a d:spc u:spc d:b u:b t:10").no_time();
+    assert_eq!("out:↓A out:↑A out:↓Dot out:↑Dot out:↓B out:↑B", result);
+}
+
+#[test]
+fn case_word_custom_pascal_toggle() {
+    let result = simulate(
+        CASE_WORD_CFG,
+        "d:5 u:5 d:a u:a d:a u:a d:spc u:spc d:a u:a d:5 u:5 t:10 d:spc u:spc t:10",
+    )
+    .no_time();
+    assert_eq!(
+        "out:↓LShift out:↓A out:↑LShift out:↑A out:↓A out:↑A \
+         out:↓LShift out:↓A out:↑LShift out:↑A out:↓Space out:↑Space",
+        result
+    );
+}
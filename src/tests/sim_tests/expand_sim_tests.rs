@@ -0,0 +1,122 @@
+use super::*;
+
+static EXPAND_CFG: &str = "
+(defcfg process-unmapped-keys yes)
+(defsrc)
+(deflayer base)
+(defexpand expansions (btw \"by the way\"))
+";
+
+#[test]
+fn expand_trigger_at_boundary() {
+    let result = simulate(EXPAND_CFG, "d:b u:b d:t u:t d:w u:w d:spc u:spc t:50")
+        .no_time()
+        .to_ascii();
+    assert_eq!(
+        "dn:B up:B dn:T up:T dn:W up:W \
+         dn:BSpace up:BSpace dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:B up:B dn:Y up:Y dn:Space up:Space dn:T up:T dn:H up:H dn:E up:E \
+         dn:Space up:Space dn:W up:W dn:A up:A dn:Y up:Y \
+         dn:Space up:Space",
+        result
+    );
+}
+
+#[test]
+fn expand_carries_over_case() {
+    let result = simulate(
+        EXPAND_CFG,
+        "d:lsft d:b u:b u:lsft d:t u:t d:w u:w d:spc u:spc t:50",
+    )
+    .no_time()
+    .to_ascii();
+    assert!(
+        result.contains("dn:LShift dn:B up:B up:LShift dn:Y up:Y dn:Space"),
+        "{result}"
+    );
+    let result = simulate(
+        EXPAND_CFG,
+        "d:lsft d:b u:b d:t u:t d:w u:w u:lsft d:spc u:spc t:50",
+    )
+    .no_time()
+    .to_ascii();
+    assert!(
+        result.contains("dn:LShift dn:T up:T up:LShift dn:LShift dn:H up:H up:LShift"),
+        "{result}"
+    );
+}
+
+#[test]
+fn expand_tracks_backspace_and_words() {
+    // backspace fixes the trigger
+    let result = simulate(
+        EXPAND_CFG,
+        "d:b u:b d:t u:t d:e u:e d:bspc u:bspc d:w u:w d:spc u:spc t:50",
+    )
+    .no_time()
+    .to_ascii();
+    assert!(result.contains("dn:B up:B dn:Y up:Y"), "{result}");
+    // the trigger inside of a word does not expand
+    let result = simulate(
+        EXPAND_CFG,
+        "d:a u:a d:b u:b d:t u:t d:w u:w d:spc u:spc t:50",
+    )
+    .no_time()
+    .to_ascii();
+    assert!(!result.contains("BSpace"), "{result}");
+}
+
+#[test]
+fn expand_from_file() {
+    let mut file_content = FxHashMap::default();
+    file_content.insert(
+        "expansions.tsv".into(),
+        "// comment\nomw\ton my way\n\nty\tthank you\n".into(),
+    );
+    let cfg = "(defcfg process-unmapped-keys yes) (defsrc) (deflayer base) \
+         (defexpand file expansions.tsv boundary-keys (.))";
+    let result =
+        simulate_with_file_content(cfg, "d:t u:t d:y u:y d:. u:. t:50", file_content.clone())
+            .no_time()
+            .to_ascii();
+    assert_eq!(
+        "dn:T up:T dn:Y up:Y dn:BSpace up:BSpace dn:BSpace up:BSpace \
+         dn:T up:T dn:H up:H dn:A up:A dn:N up:N dn:K up:K \
+         dn:Space up:Space dn:Y up:Y dn:O up:O dn:U up:U \
+         dn:Dot up:Dot",
+        result
+    );
+    // space is not a boundary key here, so it is part of the word
+    let result = simulate_with_file_content(
+        cfg,
+        "d:o u:o d:spc u:spc d:t u:t d:y u:y d:. u:. t:50",
+        file_content,
+    )
+    .no_time()
+    .to_ascii();
+    assert!(!result.contains("BSpace"), "{result}");
+}
+
+#[test]
+fn expand_shifted_symbols_and_os_layout() {
+    let cfg = "(defcfg process-unmapped-keys yes) (defsrc) (deflayer base) \
+         (defoslayout y z  @ AG-q) \
+         (defexpand expansions (hi \"hi (you)!\" em \"y@x\"))";
+    let result = simulate(cfg, "d:h u:h d:i u:i d:spc u:spc t:50")
+        .no_time()
+        .to_ascii();
+    assert!(
+        result.contains(
+            "dn:LShift dn:Kb9 up:Kb9 up:LShift dn:Z up:Z dn:O up:O dn:U up:U \
+             dn:LShift dn:Kb0 up:Kb0 up:LShift dn:LShift dn:Kb1 up:Kb1 up:LShift"
+        ),
+        "{result}"
+    );
+    let result = simulate(cfg, "d:e u:e d:m u:m d:spc u:spc t:50")
+        .no_time()
+        .to_ascii();
+    assert!(
+        result.contains("dn:Z up:Z dn:RAlt dn:Q up:Q up:RAlt dn:X up:X"),
+        "{result}"
+    );
+}
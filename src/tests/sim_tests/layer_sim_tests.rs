@@ -182,6 +182,117 @@ fn ls_sim_transparent_no_delegate() {
     assert_eq!("dn:Left t:10ms up:Left t:10ms dn:B t:10ms up:B", result);
 }
 
+#[test]
+fn layer_conditions_tri_layer() {
+    let cfg = "(defcfg transparent-key-resolution layer-stack) \
+         (defsrc a b c d) \
+         (deflayer base (layer-while-held nav) (layer-while-held sym) c d) \
+         (deflayer nav _ _ 1 2) \
+         (deflayer sym _ _ 3 4) \
+         (deflayer num _ _ 5 _) \
+         (deflayerconditions (nav sym) -> num)";
+    let result = simulate(
+        cfg,
+        "d:a d:b t:10 d:c u:c d:d u:d t:10 u:a t:10 d:c u:c t:10",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:10ms dn:Kb5 t:1ms up:Kb5 t:1ms dn:Kb4 t:1ms up:Kb4 t:17ms dn:Kb3 t:1ms up:Kb3",
+        result
+    );
+}
+
 // =============================================================================
 // End Layer Switch Simulator Input Tests
 // =============================================================================
+
+#[test]
+fn layer_lock() {
+    let cfg = "(defcfg layer-lock-unlock-keys (esc)) \
+         (defsrc a b c esc) \
+         (deflayer base (layer-while-held nav) b c esc) \
+         (deflayer nav _ layer-lock 1 2)";
+    let result = simulate(
+        cfg,
+        "d:a d:b t:10 u:b u:a t:10 d:c u:c t:10 d:b u:b t:10 d:c u:c t:10",
+    )
+    .to_ascii();
+    assert_eq!("t:20ms dn:Kb1 t:1ms up:Kb1 t:19ms dn:C t:1ms up:C", result);
+    let result = simulate(
+        cfg,
+        "d:a d:b t:10 u:b u:a t:10 d:esc u:esc t:10 d:c u:c t:10",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:20ms dn:Escape t:1ms up:Escape t:9ms dn:C t:1ms up:C",
+        result
+    );
+}
+
+#[test]
+fn layer_word() {
+    let cfg = "(defsrc a b c spc) \
+         (deflayer base (layer-word num 100 (1 2 bspc)) b c spc) \
+         (deflayer num _ 1 2 _)";
+    // digits continue the layer and space ends it
+    let result = simulate(
+        cfg,
+        "d:a u:a t:10 d:b u:b t:10 d:c u:c t:10 d:spc u:spc t:10 d:b u:b t:10",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:10ms dn:Kb1 t:1ms up:Kb1 t:9ms dn:Kb2 t:1ms up:Kb2 t:9ms \
+         dn:Space t:1ms up:Space t:9ms dn:B t:1ms up:B",
+        result
+    );
+    // the timeout ends the layer
+    let result = simulate(cfg, "d:a u:a t:10 d:b u:b t:200 d:b u:b t:10").to_ascii();
+    assert_eq!("t:10ms dn:Kb1 t:1ms up:Kb1 t:199ms dn:B t:1ms up:B", result);
+}
+
+#[test]
+fn layer_word_is_separate_from_layer_lock() {
+    let cfg = "(defcfg layer-lock-unlock-keys (esc)) \
+         (defsrc a b c esc) \
+         (deflayer base (layer-word num 100 (1 esc)) b spc esc) \
+         (deflayer num layer-lock 1 _ _)";
+    // an unlock key does not end the word
+    let result = simulate(cfg, "d:a u:a t:10 d:esc u:esc t:10 d:b u:b t:10").to_ascii();
+    assert_eq!(
+        "t:10ms dn:Escape t:1ms up:Escape t:9ms dn:Kb1 t:1ms up:Kb1",
+        result
+    );
+    // a layer locked during the word stays locked after the word ends
+    let result = simulate(cfg, "d:a u:a t:10 d:a u:a t:10 d:c u:c t:10 d:b u:b t:10").to_ascii();
+    assert_eq!(
+        "t:20ms dn:Space t:1ms up:Space t:9ms dn:Kb1 t:1ms up:Kb1",
+        result
+    );
+}
+
+#[test]
+fn swap_hands() {
+    let cfg = "(defsrc f j spc tab) \
+         (defmirror f j) \
+         (deflayer base f (tap-hold 200 200 j lsft) \
+           (tap-hold-press 200 200 spc swap-hands) swap-hands-toggle)";
+    // hold: the held swap key mirrors f to the tap-hold on j
+    let result = simulate(cfg, "d:spc t:10 d:f t:300 u:f t:10 u:spc t:10").to_ascii();
+    assert_eq!("t:216ms dn:LShift t:94ms up:LShift", result);
+    // hold: tapping the mirrored tap-hold
+    let result = simulate(cfg, "d:spc t:10 d:f t:10 u:f t:10 u:spc t:10").to_ascii();
+    assert_eq!("t:20ms dn:J t:6ms up:J", result);
+    // toggle
+    let result = simulate(
+        cfg,
+        "d:tab u:tab t:10 d:j u:j t:10 d:tab u:tab t:10 d:j t:10 u:j t:10",
+    )
+    .to_ascii();
+    assert_eq!("t:10ms dn:F t:1ms up:F t:29ms dn:J t:6ms up:J", result);
+
+    let cfg = "(defsrc f j tab) \
+         (defmirror f j) \
+         (deflayer base f j swap-hands-one-shot)";
+    let result = simulate(cfg, "d:tab u:tab t:10 d:f u:f t:10 d:f u:f t:10").to_ascii();
+    assert_eq!("t:10ms dn:J t:1ms up:J t:9ms dn:F t:1ms up:F", result);
+}
@@ -143,3 +143,29 @@ fn test_release(cfg: &str) {
         result
     );
 }
+
+#[test]
+fn type_text_uses_os_layout() {
+    let cfg = r#"
+(defsrc a b c)
+(deflayer base (type-text "Hy!") (type-text "Grüß @") (type-text "YÜ"))
+(defoslayout y z  ! S-1  @ AG-q  ü [)
+"#;
+    let result = simulate(cfg, "d:a u:a t:50").no_time().to_ascii();
+    assert_eq!(
+        "dn:LShift dn:H up:H up:LShift dn:Z up:Z dn:LShift dn:Kb1 up:Kb1 up:LShift",
+        result
+    );
+    let result = simulate(cfg, "d:b u:b t:50").no_time().to_ascii();
+    assert_eq!(
+        "dn:LShift dn:G up:G up:LShift dn:R up:R dn:LBracket up:LBracket outU:ß \
+         dn:Space up:Space dn:RAlt dn:Q up:Q up:RAlt",
+        result
+    );
+    // uppercase letters use the key of their lowercase letter
+    let result = simulate(cfg, "d:c u:c t:50").no_time().to_ascii();
+    assert_eq!(
+        "dn:LShift dn:Z up:Z up:LShift dn:LShift dn:LBracket up:LBracket up:LShift",
+        result
+    );
+}
@@ -55,10 +55,12 @@ fn apply_layer_switch(k: &mut Kanata, layer_name: &str) {
     k.layout.bm().set_default_layer(layer_idx);
 }
 
+mod autocorrect_sim_tests;
 mod block_keys_tests;
 mod capsword_sim_tests;
 mod chord_sim_tests;
 mod delay_tests;
+mod expand_sim_tests;
 mod layer_sim_tests;
 mod macro_sim_tests;
 mod mouse_sim_tests;
@@ -68,6 +70,8 @@ mod override_tests;
 mod release_sim_tests;
 mod repeat_sim_tests;
 mod seq_sim_tests;
+#[cfg(feature = "stats")]
+mod stats_sim_tests;
 mod switch_sim_tests;
 mod tap_dance_tests;
 mod tap_hold_tests;
@@ -89,6 +93,19 @@ fn simulate_with_file_content<S: AsRef<str>>(
     sim: S,
     file_content: FxHashMap<String, String>,
 ) -> String {
+    simulate_kanata(cfg, sim, file_content)
+        .kbd_out
+        .outputs
+        .events
+        .join("\n")
+}
+
+/// Simulate the input and return kanata afterwards, to check its state.
+fn simulate_kanata<S: AsRef<str>>(
+    cfg: S,
+    sim: S,
+    file_content: FxHashMap<String, String>,
+) -> Kanata {
     init_log();
     let _lk = match CFG_PARSE_LOCK.lock() {
         Ok(guard) => guard,
@@ -153,7 +170,7 @@ fn simulate_with_file_content<S: AsRef<str>>(
         }
     }
     drop(_lk);
-    k.kbd_out.outputs.events.join("\n")
+    k
 }
 
 #[allow(unused)]
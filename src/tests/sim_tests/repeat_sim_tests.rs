@@ -134,3 +134,33 @@ fn repeat_delegate_to_base_layer_trans() {
         result
     );
 }
+
+#[test]
+fn alt_repeat() {
+    let cfg = "
+         (defsrc a b c d lctl lsft)
+         (deflayer base a b S-9 alt-repeat lctl lsft)
+         (defaltrepeat S-9 S-0 C-z C-y a b)
+        ";
+    let result = simulate(cfg, "d:c t:10 u:c t:10 d:d t:10 u:d t:10").to_ascii();
+    assert_eq!(
+        "dn:LShift dn:Kb9 t:10ms up:LShift up:Kb9 t:10ms \
+         dn:LShift up:Kb0 dn:Kb0 up:Kb0 up:LShift",
+        result
+    );
+    let result = simulate(cfg, "d:lctl d:z t:10 u:z u:lctl t:10 d:d t:10 u:d t:10").to_ascii();
+    assert!(
+        result.ends_with("dn:LCtrl up:Y dn:Y up:Y up:LCtrl"),
+        "{result}"
+    );
+    // Modifiers held with a key that is not modded in defaltrepeat are kept.
+    let result = simulate(cfg, "d:lsft d:a t:10 u:a u:lsft t:10 d:d t:10 u:d t:10").to_ascii();
+    assert_eq!(
+        "dn:LShift t:1ms dn:A t:9ms up:A t:1ms up:LShift t:9ms \
+         dn:LShift up:B dn:B up:B up:LShift",
+        result
+    );
+    // Keys without an alt-repeat output do nothing.
+    let result = simulate(cfg, "d:b t:10 u:b t:10 d:d t:10 u:d t:10").to_ascii();
+    assert_eq!("dn:B t:10ms up:B", result);
+}
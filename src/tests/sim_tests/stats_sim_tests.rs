@@ -0,0 +1,49 @@
+use super::*;
+
+#[test]
+fn stats_count_layers_tap_holds_chords_and_sequences() {
+    let k = simulate_kanata(
+        "(defcfg process-unmapped-keys yes concurrent-tap-hold yes stats yes)
+         (defsrc a s d f g h)
+         (deflayer base (tap-hold 200 200 a lctl) s d f (layer-while-held nav) sldr)
+         (deflayer nav 1 2 3 4 _ _)
+         (defchordsv2 (s d) x 50 all-released ())
+         (defvirtualkeys hi (macro h i))
+         (defseq hi (f f))",
+        "d:a t:10 u:a t:300 d:a t:300 u:a t:10
+         d:g t:10 d:s u:s t:10 u:g t:10
+         d:s d:d t:10 u:s u:d t:100
+         d:h u:h t:10 d:f u:f t:10 d:f u:f t:100",
+        Default::default(),
+    );
+    let stats = k.stats.as_ref().expect("stats are enabled").snapshot();
+    // Keys are counted by their defsrc key: `s` pressed on `nav` is counted as `s`, not `2`.
+    let keys = stats
+        .keys
+        .iter()
+        .map(|key| (key.defsrc_key.as_str(), key.presses, key.taps, key.holds))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        vec![
+            ("a", 2, 1, 1),
+            ("d", 1, 0, 0),
+            ("f", 2, 0, 0),
+            ("g", 1, 0, 0),
+            ("h", 1, 0, 0),
+            ("s", 2, 0, 0),
+        ]
+    );
+    let counts = |counts: &[kanata_tcp_protocol::UsageCount]| {
+        counts
+            .iter()
+            .map(|usage| (usage.name.clone(), usage.count))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        counts(&stats.layers),
+        vec![("base".to_owned(), 8), ("nav".to_owned(), 1)]
+    );
+    assert_eq!(counts(&stats.chords), vec![("d+s".to_owned(), 1)]);
+    assert_eq!(counts(&stats.sequences), vec![("hi".to_owned(), 1)]);
+}
@@ -118,3 +118,26 @@ fn definputdevices_accepts_variables() {
     .to_ascii();
     assert_eq!("dn:A t:10ms up:A", result);
 }
+
+#[test]
+fn sim_switch_state() {
+    let result = simulate(
+        "
+         (defcfg)
+         (defsrc a b c d e)
+         (defstate mode 0 caps false)
+         (defalias b (switch
+            ((state mode 0)) x break
+            ((state mode 2)) y break
+            ((state caps true)) z break))
+         (deflayer base (state-inc mode) @b (state-toggle caps) (state-dec mode 3) (state-reset mode))
+        ",
+        "d:b u:b t:10 d:a u:a d:a u:a t:10 d:b u:b t:10 d:c u:c t:10 d:b u:b t:10 \
+         d:d u:d t:10 d:b u:b t:10 d:e u:e t:10 d:b u:b t:10",
+    )
+    .no_time();
+    assert_eq!(
+        "out:↓X out:↑X out:↓Y out:↑Y out:↓Y out:↑Y out:↓Z out:↑Z out:↓X out:↑X",
+        result
+    );
+}
@@ -21,3 +21,33 @@ fn tap_dance_eager_with_mlft() {
         result
     );
 }
+
+#[test]
+fn tap_dance_hold_chooses_tap_or_hold_per_count() {
+    let cfg = "
+        (defsrc a b)
+        (deflayer base
+            (tap-dance-hold 200 100 ((x lctl) (y lalt)))
+            b
+        )
+        ";
+    // tap
+    let result = simulate(cfg, "d:a t:20 u:a t:300").no_time().to_ascii();
+    assert_eq!("dn:X up:X", result);
+    // hold: the hold timeout starts at the press
+    let result = simulate(cfg, "d:a t:400 u:a t:50").to_ascii();
+    assert_eq!("t:100ms dn:LCtrl t:300ms up:LCtrl", result);
+    // double tap
+    let result = simulate(cfg, "d:a t:20 u:a t:20 d:a t:20 u:a t:300")
+        .no_time()
+        .to_ascii();
+    assert_eq!("dn:Y up:Y", result);
+    // tap then hold: the hold timeout starts at the second press
+    let result = simulate(cfg, "d:a t:20 u:a t:20 d:a t:400 u:a t:50").to_ascii();
+    assert_eq!("t:140ms dn:LAlt t:300ms up:LAlt", result);
+    // held with another key: the hold timeout decides
+    let result = simulate(cfg, "d:a t:20 d:b t:20 u:b t:400 u:a t:50")
+        .no_time()
+        .to_ascii();
+    assert_eq!("dn:LCtrl dn:B up:B up:LCtrl", result);
+}
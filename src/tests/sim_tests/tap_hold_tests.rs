@@ -1153,6 +1153,46 @@ fn per_action_require_prior_idle_with_opposite_hand() {
     assert_eq!("dn:J t:10ms up:J t:210ms dn:LCtrl t:50ms up:LCtrl", result);
 }
 
+// ========== adaptive tap-hold tests ==========
+
+#[test]
+fn adaptive_tap_hold_learns_from_misfires() {
+    let cfg = "
+(defsrc a b bspc)
+(deflayer base @a b bspc)
+(defalias a (tap-hold 0 200 a lctl (adaptive 180 210 0 0)))
+    ";
+    // Without misfires the timeout of the action is used.
+    let result = simulate(cfg, "d:a t:195 u:a t:10").to_ascii();
+    assert_eq!("t:195ms dn:A t:6ms up:A", result);
+    // A tap that overlapped b lowers the timeout to 190ms.
+    let result = simulate(
+        cfg,
+        "d:a t:20 d:b t:20 u:b t:20 u:a t:20 \
+         d:a t:195 u:a t:10",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:60ms dn:A t:6ms dn:B t:1ms up:B t:1ms up:A \
+         t:202ms dn:LCtrl t:5ms up:LCtrl",
+        result
+    );
+    // A hold followed by backspace raises the timeout, up to the maximum of 210ms.
+    let result = simulate(
+        cfg,
+        "d:a t:250 u:a t:20 d:bspc t:10 u:bspc t:10 \
+         d:a t:250 u:a t:20 d:bspc t:10 u:bspc t:10 \
+         d:a t:205 u:a t:10 d:a t:215 u:a t:10",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:200ms dn:LCtrl t:50ms up:LCtrl t:20ms dn:BSpace t:10ms up:BSpace \
+         t:220ms dn:LCtrl t:40ms up:LCtrl t:20ms dn:BSpace t:10ms up:BSpace \
+         t:215ms dn:A t:6ms up:A t:214ms dn:LCtrl t:5ms up:LCtrl",
+        result
+    );
+}
+
 // ========== tap-hold-order simulation tests ==========
 // Note: t:6ms gaps after resolution are sim framework processing overhead for
 // event-triggered resolution (as opposed to timeout-triggered). This is consistent
@@ -1279,3 +1319,46 @@ fn tap_hold_order_no_prior_idle_enters_normal_resolution() {
         result
     );
 }
+
+// ========== auto-shift tests ==========
+
+#[test]
+fn auto_shift() {
+    let cfg = "
+(defsrc a 1 q lctl)
+(deflayer base a 1 q auto-shift-toggle)
+(defautoshift timeout 100 numbers-symbols-timeout 200 exclude (q))
+    ";
+    // tap, hold past the timeout, and a number held past the letter timeout only
+    let result = simulate(
+        cfg,
+        "d:a t:50 u:a t:50 d:a t:150 u:a t:50 d:1 t:150 u:1 t:50",
+    )
+    .to_ascii();
+    assert_eq!(
+        "t:50ms dn:A t:6ms up:A t:145ms dn:LShift t:1ms dn:A t:1ms up:A t:1ms up:LShift \
+         t:246ms dn:Kb1 t:6ms up:Kb1",
+        result
+    );
+    // excluded keys and toggled off
+    let result = simulate(
+        cfg,
+        "d:q t:150 u:q t:10 d:lctl u:lctl t:10 d:a t:150 u:a t:10",
+    )
+    .to_ascii();
+    assert_eq!("dn:Q t:150ms up:Q t:20ms dn:A t:150ms up:A", result);
+}
+
+#[test]
+fn auto_shift_repeat_after_hold() {
+    let cfg = "
+(defsrc a)
+(deflayer base a)
+(defautoshift timeout 100 repeat-after-hold yes)
+    ";
+    let result = simulate(cfg, "d:a t:150 r:a t:10 u:a t:10");
+    assert_eq!(
+        "t:100ms\nout:↓LShift\nout:↓A\nt:50ms\nout:↓A\nt:10ms\nout:↑LShift\nout:↑A",
+        result
+    );
+}
@@ -58,6 +58,77 @@ pub enum ServerMessage {
     TapActivated {
         key: String,
     },
+    /// Response to `RequestStateVars`, in the order the variables are declared in `defstate`.
+    StateVars {
+        vars: Vec<StateVarValue>,
+    },
+    /// Sent when a layer is locked or unlocked by `layer-lock`, and in response to
+    /// `RequestLayerLock`. `locked` is `null` when no layer is locked.
+    LayerLock {
+        locked: Option<String>,
+    },
+    /// Response to `RequestAdaptiveTapHold`, with the keys of tap-hold actions that have the
+    /// `adaptive` option.
+    AdaptiveTapHold {
+        keys: Vec<AdaptiveTapHoldKey>,
+    },
+    /// Response to `RequestStats`, with the statistics collected since kanata started.
+    Stats {
+        stats: UsageStats,
+    },
+}
+
+/// The current value of a state variable declared in `defstate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateVarValue {
+    pub name: String,
+    pub value: i16,
+}
+
+/// The learned timing and the statistics of an adaptive tap-hold key. Durations are in
+/// milliseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptiveTapHoldKey {
+    pub key: String,
+    pub timeout: u16,
+    pub prior_idle: u16,
+    pub taps: u32,
+    pub holds: u32,
+    pub avg_tap_ms: u64,
+    pub avg_hold_ms: u64,
+    pub false_holds: u32,
+    pub false_taps: u32,
+}
+
+/// Statistics of key usage from the `stats` option of `defcfg`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    pub keys: Vec<KeyStats>,
+    pub layers: Vec<UsageCount>,
+    /// Pairs of consecutively pressed keys, named `"first second"`.
+    pub bigrams: Vec<UsageCount>,
+    /// Chords of `defchordsv2`, named by their keys joined with `+`.
+    pub chords: Vec<UsageCount>,
+    /// Sequences, named by their virtual key.
+    pub sequences: Vec<UsageCount>,
+}
+
+/// Presses and tap-hold outcomes of a key of `defsrc`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyStats {
+    /// Name of the `defsrc` key that was pressed, regardless of the action it has on the active
+    /// layer, or `letter` for all letters with `stats-exclude letters`.
+    pub defsrc_key: String,
+    pub presses: u64,
+    pub taps: u64,
+    pub holds: u64,
+    pub avg_hold_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageCount {
+    pub name: String,
+    pub count: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -141,6 +212,23 @@ pub enum ClientMessage {
     /// Request server capabilities and version.
     /// Introduced in protocol v1.11.
     Hello {},
+
+    /// Request the values of the state variables declared in `defstate`.
+    RequestStateVars {},
+    /// Set a state variable declared in `defstate`.
+    SetStateVar {
+        name: String,
+        value: i16,
+    },
+
+    /// Request the layer currently locked by `layer-lock`.
+    RequestLayerLock {},
+
+    /// Request the timing learned by tap-hold actions with the `adaptive` option.
+    RequestAdaptiveTapHold {},
+
+    /// Request the statistics collected with the `stats` option of `defcfg`.
+    RequestStats {},
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -228,6 +316,99 @@ mod tests {
         let _msg: ClientMessage = serde_json::from_str(json).unwrap();
     }
 
+    #[test]
+    fn test_state_vars() {
+        let json = r#"{"SetStateVar":{"name":"mode","value":-2}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+        assert!(
+            matches!(msg, ClientMessage::SetStateVar { name, value } if name == "mode" && value == -2)
+        );
+
+        let msg = ServerMessage::StateVars {
+            vars: vec![StateVarValue {
+                name: "mode".to_string(),
+                value: 2,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"StateVars":{"vars":[{"name":"mode","value":2}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_layer_lock() {
+        let json = r#"{"RequestLayerLock":{}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+        assert!(matches!(msg, ClientMessage::RequestLayerLock {}));
+
+        let msg = ServerMessage::LayerLock {
+            locked: Some("nav".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"LayerLock":{"locked":"nav"}}"#
+        );
+        let msg = ServerMessage::LayerLock { locked: None };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"LayerLock":{"locked":null}}"#
+        );
+    }
+
+    #[test]
+    fn test_adaptive_tap_hold() {
+        let json = r#"{"RequestAdaptiveTapHold":{}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+        assert!(matches!(msg, ClientMessage::RequestAdaptiveTapHold {}));
+
+        let msg = ServerMessage::AdaptiveTapHold {
+            keys: vec![AdaptiveTapHoldKey {
+                key: "a".to_string(),
+                timeout: 210,
+                prior_idle: 130,
+                taps: 120,
+                holds: 14,
+                avg_tap_ms: 80,
+                avg_hold_ms: 300,
+                false_holds: 2,
+                false_taps: 1,
+            }],
+        };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"AdaptiveTapHold":{"keys":[{"key":"a","timeout":210,"prior_idle":130,"taps":120,"holds":14,"avg_tap_ms":80,"avg_hold_ms":300,"false_holds":2,"false_taps":1}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let json = r#"{"RequestStats":{}}"#;
+        let msg: ClientMessage = serde_json::from_str(json).unwrap();
+        assert!(matches!(msg, ClientMessage::RequestStats {}));
+
+        let msg = ServerMessage::Stats {
+            stats: UsageStats {
+                keys: vec![KeyStats {
+                    defsrc_key: "a".to_string(),
+                    presses: 3,
+                    taps: 2,
+                    holds: 1,
+                    avg_hold_ms: 250,
+                }],
+                layers: vec![UsageCount {
+                    name: "base".to_string(),
+                    count: 3,
+                }],
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&msg).unwrap(),
+            r#"{"Stats":{"stats":{"keys":[{"defsrc_key":"a","presses":3,"taps":2,"holds":1,"avg_hold_ms":250}],"layers":[{"name":"base","count":3}],"bigrams":[],"chords":[],"sequences":[]}}}"#
+        );
+    }
+
     #[test]
     fn test_request_fake_key_names() {
         let json = r#"{"RequestFakeKeyNames":{}}"#;